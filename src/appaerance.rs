@@ -1,2 +1,5 @@
 pub mod button;
+pub mod container;
+pub mod icon;
 pub mod text;
+pub mod visualizer;
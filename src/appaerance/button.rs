@@ -20,6 +20,21 @@ pub fn secondary(theme: &Theme, status: Status, selected: bool) -> Style {
     button(foreground, background, background_hover, status)
 }
 
+/// Text that reads as the secondary text around it until hovered.
+pub fn link(theme: &Theme, status: Status) -> Style {
+    let palette = theme.extended_palette();
+
+    let text_color = match status {
+        Status::Hovered | Status::Pressed => palette.primary.strong.color,
+        Status::Active | Status::Disabled => palette.secondary.base.color,
+    };
+
+    Style {
+        text_color,
+        ..Default::default()
+    }
+}
+
 fn button(foreground: Color, background: Color, background_hover: Color, status: Status) -> Style {
     match status {
         Status::Active | Status::Pressed => Style {
@@ -0,0 +1,52 @@
+use iced::{border, widget::container::Style, Color, Shadow, Theme, Vector};
+
+pub fn badge(theme: &Theme) -> Style {
+    let palette = theme.extended_palette();
+
+    Style::default()
+        .background(palette.primary.weak.color)
+        .color(palette.primary.weak.text)
+        .border(border::rounded(4))
+}
+
+/// Background faintly tinted by `color`, the plain background without one.
+pub fn tinted(theme: &Theme, color: Option<Color>) -> Style {
+    let palette = theme.extended_palette();
+
+    let background = color.map_or(palette.background.base.color, |color| Color {
+        a: 0.25,
+        ..color
+    });
+
+    Style::default()
+        .background(background)
+        .border(border::rounded(4))
+}
+
+/// Floating panel, raised above whatever it covers.
+pub fn panel(theme: &Theme) -> Style {
+    let palette = theme.extended_palette();
+
+    Style {
+        shadow: Shadow {
+            color: Color::BLACK,
+            offset: Vector::new(0.0, 4.0),
+            blur_radius: 16.0,
+        },
+        ..Style::default()
+            .background(palette.background.base.color)
+            .border(
+                border::rounded(6)
+                    .color(palette.background.strong.color)
+                    .width(1),
+            )
+    }
+}
+
+/// Dims the screen behind a panel.
+pub fn backdrop(_theme: &Theme) -> Style {
+    Style::default().background(Color {
+        a: 0.5,
+        ..Color::BLACK
+    })
+}
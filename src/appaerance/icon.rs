@@ -0,0 +1,86 @@
+use iced::{
+    widget::{text, Text},
+    Font,
+};
+use std::borrow::Cow;
+
+/// Family of `fonts/oxify-icon.ttf`, which has the logo and every icon.
+const FONT: Font = Font::with_name("fontcustom");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Logo,
+    Home,
+    Logs,
+    Back,
+    Forward,
+    Play,
+    Pause,
+    Next,
+    Previous,
+    Shuffle,
+    Repeat,
+    RepeatOne,
+    Heart,
+    Queue,
+    Search,
+    Settings,
+    Library,
+    Close,
+    MiniPlayer,
+    VolumeMuted,
+    VolumeLow,
+    VolumeMedium,
+    VolumeHigh,
+}
+
+impl Icon {
+    /// Speaker with as many waves as the volume `percent` is loud.
+    pub fn volume(percent: f32) -> Self {
+        match percent {
+            p if p <= 0.0 => Icon::VolumeMuted,
+            p if p < 34.0 => Icon::VolumeLow,
+            p if p < 67.0 => Icon::VolumeMedium,
+            _ => Icon::VolumeHigh,
+        }
+    }
+
+    /// Private use code points of the icon font, in the order of the variants.
+    fn glyph(self) -> &'static str {
+        match self {
+            Icon::Logo => "\u{f100}",
+            Icon::Home => "\u{f101}",
+            Icon::Logs => "\u{f102}",
+            Icon::Back => "\u{f103}",
+            Icon::Forward => "\u{f104}",
+            Icon::Play => "\u{f105}",
+            Icon::Pause => "\u{f106}",
+            Icon::Next => "\u{f107}",
+            Icon::Previous => "\u{f108}",
+            Icon::Shuffle => "\u{f109}",
+            Icon::Repeat => "\u{f10a}",
+            Icon::RepeatOne => "\u{f10b}",
+            Icon::Heart => "\u{f10c}",
+            Icon::Queue => "\u{f10d}",
+            Icon::Search => "\u{f10e}",
+            Icon::Settings => "\u{f10f}",
+            Icon::Library => "\u{f110}",
+            Icon::Close => "\u{f111}",
+            Icon::MiniPlayer => "\u{f112}",
+            Icon::VolumeMuted => "\u{f113}",
+            Icon::VolumeLow => "\u{f114}",
+            Icon::VolumeMedium => "\u{f115}",
+            Icon::VolumeHigh => "\u{f116}",
+        }
+    }
+}
+
+pub fn load() -> Cow<'static, [u8]> {
+    include_bytes!("../../fonts/oxify-icon.ttf")
+        .as_slice()
+        .into()
+}
+
+pub fn icon<'a>(icon: Icon) -> Text<'a> {
+    text(icon.glyph()).font(FONT)
+}
@@ -0,0 +1,18 @@
+use iced::{Color, Theme};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    pub foreground: Color,
+    pub accent: Color,
+    pub background: Color,
+}
+
+pub fn primary(theme: &Theme) -> Style {
+    let palette = theme.extended_palette();
+
+    Style {
+        foreground: palette.primary.base.color,
+        accent: palette.primary.strong.color,
+        background: palette.background.weak.color,
+    }
+}
@@ -1,16 +1,35 @@
 use crate::{
-    data::messages::{Message, OxifyMessage},
+    data::{
+        equalizer::Preset,
+        keybinding::Keybindings,
+        messages::{Message, OxifyMessage},
+        theme::{self, Scheme},
+        visualizer,
+    },
     environment,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveTime};
 use iced::Theme;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use tokio::runtime::Runtime;
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub appaerance: Appaerance,
     pub audio: Audio,
+    #[serde(default)]
+    pub playback: Playback,
+    #[serde(default)]
+    pub library: Library,
+    /// Action names to chords like `mod+shift+k`, several are separated by commas
+    #[serde(default)]
+    pub keybindings: Keybindings,
+    /// The file could not be read, the defaults are used but never saved over it.
+    #[serde(skip)]
+    pub unreadable: bool,
 }
 
 impl Default for Config {
@@ -19,31 +38,112 @@ impl Default for Config {
             appaerance: Appaerance {
                 window_size: default_window_size(),
                 theme: default_theme(),
+                light_theme: default_light_theme(),
+                dark_theme: default_dark_theme(),
+                day_start: default_day_start(),
+                night_start: default_night_start(),
                 font_size: default_font_size(),
+                scale_factor: default_scale_factor(),
+                font_family: None,
+                heading_font_family: None,
+                font_files: Vec::new(),
+                visualizer: Visualizer::default(),
             },
             audio: Audio {
                 format: default_audio_format(),
                 cache_limit_size: default_cache_limit_size(),
                 bitrate: default_bitrate(),
                 initial_volume: default_initial_volume(),
+                volume_ctrl: default_volume_ctrl(),
+                volume_range: default_volume_range(),
+                remember_volume: default_remember_volume(),
+                volume_step: default_volume_step(),
+                scroll_volume_step: default_scroll_volume_step(),
+                normalisation: Normalisation::default(),
+                crossfade: default_crossfade(),
+                gapless: default_gapless(),
+                equalizer: Equalizer::default(),
             },
+            playback: Playback::default(),
+            library: Library::default(),
+            keybindings: Keybindings::new(),
+            unreadable: false,
         }
     }
 }
 
+pub const AUTO_THEME: &str = "auto";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Appaerance {
+    /// Size of the window on the first run, the last one is remembered afterwards
     #[serde(default = "default_window_size")]
     pub window_size: (f32, f32),
+    /// A built-in or custom theme, or auto to switch between `light_theme` and `dark_theme`
     #[serde(default = "default_theme")]
     pub theme: String,
+    #[serde(default = "default_light_theme")]
+    pub light_theme: String,
+    #[serde(default = "default_dark_theme")]
+    pub dark_theme: String,
+    /// HH:MM, auto switches to the light theme then unless the desktop has a preference
+    #[serde(default = "default_day_start")]
+    pub day_start: String,
+    /// HH:MM, auto switches to the dark theme then unless the desktop has a preference
+    #[serde(default = "default_night_start")]
+    pub night_start: String,
     #[serde(default = "default_font_size")]
     pub font_size: f32,
+    /// Multiplies every size of the UI on top of the scaling of the display, Ctrl +/- change it
+    #[serde(default = "default_scale_factor")]
+    pub scale_factor: f64,
+    /// Family of the text, the system monospace font when unset or its files cannot be loaded
+    #[serde(default)]
+    pub font_family: Option<String>,
+    /// Family of the headings, bold `font_family` when unset
+    #[serde(default)]
+    pub heading_font_family: Option<String>,
+    /// Files in the `fonts` directory of the config dir, or absolute paths, every font there when empty
+    #[serde(default)]
+    pub font_files: Vec<String>,
+    #[serde(default)]
+    pub visualizer: Visualizer,
 }
 
 impl Appaerance {
-    pub fn get_theme(&self) -> Theme {
-        match self.theme.as_str() {
+    pub fn is_auto_theme(&self) -> bool {
+        self.theme == AUTO_THEME
+    }
+
+    /// The scheme auto follows, the one of the `desktop` or else the scheduled one.
+    pub fn scheme(&self, desktop: Option<Scheme>) -> Result<Scheme> {
+        if let Some(scheme) = desktop {
+            return Ok(scheme);
+        }
+
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| anyhow!("Incorrect time {time}, expected HH:MM"))
+        };
+
+        Ok(theme::scheduled(
+            parse(&self.day_start)?,
+            parse(&self.night_start)?,
+            Local::now().time(),
+        ))
+    }
+
+    /// A built-in theme, or else the custom one in `themes/<name>.toml`.
+    pub fn get_theme(&self, desktop: Option<Scheme>) -> Result<Theme> {
+        let name = match self.theme.as_str() {
+            AUTO_THEME => match self.scheme(desktop)? {
+                Scheme::Light => self.light_theme.as_str(),
+                Scheme::Dark => self.dark_theme.as_str(),
+            },
+            name => name,
+        };
+
+        let theme = match name {
             "Light" => Theme::Light,
             "Dark" => Theme::Dark,
             "Dracula" => Theme::Dracula,
@@ -66,7 +166,31 @@ impl Appaerance {
             "Nightfly" => Theme::Nightfly,
             "Oxocarbon" => Theme::Oxocarbon,
             "Ferra" => Theme::Ferra,
-            _ => Theme::GruvboxDark,
+            name => return theme::load(name),
+        };
+
+        Ok(theme)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Visualizer {
+    #[serde(default)]
+    pub enabled: bool,
+    /// bars, oscilloscope or waveform
+    #[serde(default = "default_visualizer_style")]
+    pub style: String,
+    /// Redraws per second while playing, from 1 to 60
+    #[serde(default = "default_visualizer_fps")]
+    pub fps: u32,
+}
+
+impl Default for Visualizer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            style: default_visualizer_style(),
+            fps: default_visualizer_fps(),
         }
     }
 }
@@ -79,8 +203,133 @@ pub struct Audio {
     pub cache_limit_size: String,
     #[serde(default = "default_bitrate")]
     pub bitrate: u32,
+    /// Percentage
     #[serde(default = "default_initial_volume")]
     pub initial_volume: u16,
+    /// cubic, log, linear or fixed
+    #[serde(default = "default_volume_ctrl")]
+    pub volume_ctrl: String,
+    /// dB, only used by the cubic and log volume controls
+    #[serde(default = "default_volume_range")]
+    pub volume_range: f64,
+    /// Starts with the volume of the previous run instead of the initial one
+    #[serde(default = "default_remember_volume")]
+    pub remember_volume: bool,
+    /// Percentage changed on each key press
+    #[serde(default = "default_volume_step")]
+    pub volume_step: u16,
+    /// Percentage changed on each scroll wheel line
+    #[serde(default = "default_scroll_volume_step")]
+    pub scroll_volume_step: u16,
+    #[serde(default)]
+    pub normalisation: Normalisation,
+    /// Seconds, from 0 (disabled) to 12
+    #[serde(default = "default_crossfade")]
+    pub crossfade: u64,
+    /// Plays consecutive album tracks without crossfade nor gaps
+    #[serde(default = "default_gapless")]
+    pub gapless: bool,
+    #[serde(default)]
+    pub equalizer: Equalizer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Normalisation {
+    #[serde(default)]
+    pub enabled: bool,
+    /// album, track or auto
+    #[serde(rename = "type", default = "default_normalisation_type")]
+    pub kind: String,
+    /// dB
+    #[serde(default)]
+    pub pregain: f64,
+    /// basic or dynamic
+    #[serde(default = "default_normalisation_method")]
+    pub method: String,
+    /// dBFS
+    #[serde(default = "default_normalisation_threshold")]
+    pub threshold: f64,
+    /// ms
+    #[serde(default = "default_normalisation_attack")]
+    pub attack: u64,
+    /// ms
+    #[serde(default = "default_normalisation_release")]
+    pub release: u64,
+    /// dB
+    #[serde(default = "default_normalisation_knee")]
+    pub knee: f64,
+}
+
+impl Default for Normalisation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kind: default_normalisation_type(),
+            pregain: 0.0,
+            method: default_normalisation_method(),
+            threshold: default_normalisation_threshold(),
+            attack: default_normalisation_attack(),
+            release: default_normalisation_release(),
+            knee: default_normalisation_knee(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Equalizer {
+    #[serde(default)]
+    pub enabled: bool,
+    /// flat, bass, treble, vocal, rock, electronic or custom
+    #[serde(default = "default_equalizer_preset")]
+    pub preset: String,
+    /// dB, from 31 Hz to 16 kHz. Only used by the custom preset
+    #[serde(default)]
+    pub bands: [f64; 10],
+    /// dB
+    #[serde(default)]
+    pub preamp: f64,
+    /// From -1.0 (left) to 1.0 (right)
+    #[serde(default)]
+    pub balance: f64,
+    #[serde(default)]
+    pub mono: bool,
+    #[serde(default = "default_equalizer_limiter")]
+    pub limiter: bool,
+}
+
+impl Default for Equalizer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            preset: default_equalizer_preset(),
+            bands: [0.0; 10],
+            preamp: 0.0,
+            balance: 0.0,
+            mono: false,
+            limiter: default_equalizer_limiter(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Playback {
+    #[serde(default = "default_restore_session")]
+    pub restore_session: bool,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            restore_session: default_restore_session(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Library {
+    /// Directories scanned for FLAC, MP3, OGG Vorbis and Opus files
+    #[serde(default)]
+    pub local_paths: Vec<PathBuf>,
 }
 
 // Appaerance
@@ -92,10 +341,38 @@ fn default_theme() -> String {
     Theme::GruvboxDark.to_string()
 }
 
+fn default_light_theme() -> String {
+    Theme::GruvboxLight.to_string()
+}
+
+fn default_dark_theme() -> String {
+    Theme::GruvboxDark.to_string()
+}
+
+fn default_day_start() -> String {
+    String::from("07:00")
+}
+
+fn default_night_start() -> String {
+    String::from("19:00")
+}
+
 fn default_font_size() -> f32 {
     16.0
 }
 
+fn default_scale_factor() -> f64 {
+    1.0
+}
+
+fn default_visualizer_style() -> String {
+    visualizer::Style::Bars.to_string()
+}
+
+fn default_visualizer_fps() -> u32 {
+    30
+}
+
 // Audio
 fn default_audio_format() -> String {
     String::from("S16")
@@ -113,6 +390,71 @@ fn default_initial_volume() -> u16 {
     50
 }
 
+fn default_volume_ctrl() -> String {
+    String::from("log")
+}
+
+fn default_volume_range() -> f64 {
+    60.0
+}
+
+fn default_remember_volume() -> bool {
+    true
+}
+
+fn default_volume_step() -> u16 {
+    5
+}
+
+fn default_scroll_volume_step() -> u16 {
+    2
+}
+
+fn default_crossfade() -> u64 {
+    0
+}
+
+fn default_gapless() -> bool {
+    true
+}
+
+fn default_normalisation_type() -> String {
+    String::from("auto")
+}
+
+fn default_normalisation_method() -> String {
+    String::from("dynamic")
+}
+
+fn default_normalisation_threshold() -> f64 {
+    -2.0
+}
+
+fn default_normalisation_attack() -> u64 {
+    5
+}
+
+fn default_normalisation_release() -> u64 {
+    100
+}
+
+fn default_normalisation_knee() -> f64 {
+    5.0
+}
+
+fn default_equalizer_preset() -> String {
+    Preset::Flat.to_string()
+}
+
+fn default_equalizer_limiter() -> bool {
+    true
+}
+
+// Playback
+fn default_restore_session() -> bool {
+    true
+}
+
 impl Config {
     pub async fn load() -> Self {
         let config_dir = environment::config_dir();
@@ -135,9 +477,13 @@ impl Config {
         match tokio::fs::read_to_string(&config_path).await {
             Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
                 log::warn!(
-                    "Config file found but cannot be loaded: {err}\nUsing default config instead"
+                    "Config file found but cannot be loaded: {err}\nUsing default config instead, \
+                    changes are not saved until it is fixed and reloaded"
                 );
-                Config::default()
+                Config {
+                    unreadable: true,
+                    ..Config::default()
+                }
             }),
             Err(err) => match err.kind() {
                 std::io::ErrorKind::NotFound => {
@@ -151,11 +497,44 @@ impl Config {
 
                     config
                 }
-                _ => todo!(),
+                _ => {
+                    log::warn!(
+                        "Cannot read the config file: {err}\nUsing default config instead, \
+                        changes are not saved until it can be read and reloaded"
+                    );
+                    Config {
+                        unreadable: true,
+                        ..Config::default()
+                    }
+                }
             },
         }
     }
 
+    /// Writes to the file only what changed since `previous`, the config as last loaded
+    /// or saved. Its comments, layout and the keys this version does not know are kept.
+    pub async fn save(&self, previous: &Config) -> Result<()> {
+        if self.unreadable {
+            return Err(anyhow!(
+                "the config file has errors, fix it and reload the config to save changes"
+            ));
+        }
+
+        let config_path = environment::config_dir().join(environment::CONFIG_FILE_NAME);
+        let content = toml::to_string(self)?;
+
+        let content = match tokio::fs::read_to_string(&config_path).await {
+            Ok(existing) => merge(&existing, &toml::to_string(previous)?, &content)
+                .map_err(|err| anyhow!("the config file has errors, it is left as is: {err}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => content,
+            Err(err) => return Err(err.into()),
+        };
+
+        tokio::fs::write(config_path, content).await?;
+
+        Ok(())
+    }
+
     pub async fn reload(&self) -> Message {
         let new_config = (|| -> Result<Config> {
             let rt = Runtime::new()?;
@@ -176,3 +555,175 @@ impl Config {
         }
     }
 }
+
+/// Applies over the `existing` file what changed from the `previous` config to `config`.
+fn merge(existing: &str, previous: &str, config: &str) -> Result<String> {
+    let mut document = existing.parse::<DocumentMut>()?;
+    let previous = previous.parse::<DocumentMut>()?;
+    let config = config.parse::<DocumentMut>()?;
+
+    merge_table(
+        document.as_table_mut(),
+        previous.as_table(),
+        config.as_table(),
+    );
+
+    Ok(document.to_string())
+}
+
+/// Keys neither config has, whether unknown, newer or commented out, are left alone.
+fn merge_table(target: &mut dyn TableLike, previous: &dyn TableLike, config: &dyn TableLike) {
+    // Known before and gone now, like an option that was unset
+    let removed: Vec<String> = previous
+        .iter()
+        .map(|(key, _)| key)
+        .filter(|key| !config.contains_key(key))
+        .map(String::from)
+        .collect();
+
+    for key in removed {
+        target.remove(&key);
+    }
+
+    let empty = Table::new();
+    for (key, item) in config.iter() {
+        let before = previous.get(key);
+        if before.is_some_and(|before| same_item(before, item)) {
+            continue;
+        }
+
+        let Some(existing) = target.get_mut(key) else {
+            target.insert(key, item.clone());
+            continue;
+        };
+
+        if let (Some(existing), Some(table)) = (existing.as_table_like_mut(), item.as_table_like())
+        {
+            let before = before.and_then(Item::as_table_like).unwrap_or(&empty);
+            merge_table(existing, before, table);
+            continue;
+        }
+
+        match (existing, item) {
+            (Item::Value(existing), Item::Value(value)) => {
+                // Keeps the comment after the value
+                let decor = existing.decor().clone();
+                *existing = value.clone();
+                *existing.decor_mut() = decor;
+            }
+            (existing, item) => *existing = item.clone(),
+        }
+    }
+}
+
+fn same_item(a: &Item, b: &Item) -> bool {
+    match (
+        a.as_table_like(),
+        b.as_table_like(),
+        a.as_value(),
+        b.as_value(),
+    ) {
+        (Some(a), Some(b), _, _) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_item(a, b)))
+        }
+        (_, _, Some(a), Some(b)) => same_value(a, b),
+        _ => a.to_string() == b.to_string(),
+    }
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b))
+        }
+        (Value::InlineTable(a), Value::InlineTable(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_value(a, b)))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING: &str = "\
+# My config
+[audio]
+# Loud please
+crossfade = 3 # seconds
+bitrate = 320
+";
+
+    // Defaults the file leaves out are in the serialized config
+    const PREVIOUS: &str = "[audio]\ncrossfade = 3\nbitrate = 320\ngapless = true\n";
+
+    #[test]
+    fn merge_without_changes_keeps_the_file() {
+        assert_eq!(merge(EXISTING, PREVIOUS, PREVIOUS).unwrap(), EXISTING);
+    }
+
+    #[test]
+    fn merge_keeps_comments_around_changed_values() {
+        let config = "[audio]\ncrossfade = 5\nbitrate = 320\ngapless = true\n";
+
+        let merged = merge(EXISTING, PREVIOUS, config).unwrap();
+
+        for kept in ["# My config", "# Loud please", "crossfade = 5 # seconds"] {
+            assert!(merged.contains(kept), "{kept:?} is missing from:\n{merged}");
+        }
+        // Unchanged defaults stay out of the file
+        assert!(!merged.contains("gapless"));
+    }
+
+    #[test]
+    fn merge_keeps_the_keys_the_config_does_not_know() {
+        let existing = format!("{EXISTING}future = \"yes\"\n\n# [library]\n# local_paths = []\n");
+        let config = "[audio]\ncrossfade = 5\nbitrate = 320\ngapless = true\n";
+
+        let merged = merge(&existing, PREVIOUS, config).unwrap();
+
+        for kept in ["future = \"yes\"", "# [library]", "# local_paths = []"] {
+            assert!(merged.contains(kept), "{kept:?} is missing from:\n{merged}");
+        }
+    }
+
+    #[test]
+    fn merge_writes_only_what_changed() {
+        // Edited by hand while running, the config in memory still has 320
+        let existing = EXISTING.replace("bitrate = 320", "bitrate = 160");
+        let previous = "[audio]\ncrossfade = 3\nbitrate = 320\ndevice = \"hw:0\"\n";
+        let config = "[audio]\ncrossfade = 3\nbitrate = 320\nvolume = 50\n";
+
+        let merged = merge(&format!("{existing}device = \"hw:0\"\n"), previous, config).unwrap();
+
+        assert_eq!(
+            toml::from_str::<toml::Table>(&merged).unwrap(),
+            toml::from_str::<toml::Table>("[audio]\ncrossfade = 3\nbitrate = 160\nvolume = 50\n")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn unreadable_configs_are_not_saved() {
+        let config = Config {
+            unreadable: true,
+            ..Config::default()
+        };
+
+        let saved = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(config.save(&Config::default()));
+
+        assert!(saved.is_err());
+    }
+}
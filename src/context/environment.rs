@@ -13,6 +13,11 @@ pub fn data_dir() -> PathBuf {
         .join("oxify")
 }
 
+/// Audio files cached by librespot, kept apart so eviction never touches the other data files.
+pub fn audio_cache_dir() -> PathBuf {
+    data_dir().join("audio")
+}
+
 pub fn cache_dir() -> PathBuf {
     dirs_next::cache_dir()
         .expect("expected valid cache dir")
@@ -0,0 +1,75 @@
+use crate::data::theme::Scheme;
+use iced::Subscription;
+
+/// Color scheme preferred by the desktop, `None` when it has no preference or it is unknown.
+#[cfg(target_os = "linux")]
+pub fn color_scheme() -> Subscription<Option<Scheme>> {
+    Subscription::run(|| {
+        iced::stream::channel(1, |mut output| async move {
+            if let Err(err) = linux::watch(&mut output).await {
+                log::info!("Cannot follow the desktop color scheme, using the schedule: {err}");
+            }
+
+            // An ended subscription would be started again right away
+            std::future::pending::<()>().await;
+        })
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn color_scheme() -> Subscription<Option<Scheme>> {
+    Subscription::none()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::Scheme;
+    use iced::futures::{channel::mpsc::Sender, SinkExt, StreamExt};
+    use zbus::zvariant::{OwnedValue, Value};
+
+    const NAMESPACE: &str = "org.freedesktop.appearance";
+    const KEY: &str = "color-scheme";
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.portal.Settings",
+        default_service = "org.freedesktop.portal.Desktop",
+        default_path = "/org/freedesktop/portal/desktop"
+    )]
+    trait Settings {
+        fn read(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+        #[zbus(signal)]
+        fn setting_changed(&self, namespace: &str, key: &str, value: Value<'_>)
+            -> zbus::Result<()>;
+    }
+
+    /// Sends the current scheme, then every change of it.
+    pub async fn watch(output: &mut Sender<Option<Scheme>>) -> zbus::Result<()> {
+        let connection = zbus::Connection::session().await?;
+        let proxy = SettingsProxy::new(&connection).await?;
+        let mut changes = proxy.receive_setting_changed().await?;
+
+        let current = proxy.read(NAMESPACE, KEY).await?;
+        let _ = output.send(scheme(&current)).await;
+
+        while let Some(change) = changes.next().await {
+            let args = change.args()?;
+
+            if args.namespace() == &NAMESPACE && args.key() == &KEY {
+                let _ = output.send(scheme(args.value())).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 1 is dark and 2 light, `Read` wraps the value in one more variant than the signal does.
+    fn scheme(value: &Value) -> Option<Scheme> {
+        match value {
+            Value::U32(1) => Some(Scheme::Dark),
+            Value::U32(2) => Some(Scheme::Light),
+            Value::Value(value) => scheme(value),
+            _ => None,
+        }
+    }
+}
@@ -1,3 +1,20 @@
+pub mod cache;
+pub mod cover;
+pub mod detail;
+pub mod equalizer;
 pub mod font;
+pub mod keybinding;
+pub mod library;
+pub mod local;
 pub mod log;
 pub mod messages;
+pub mod palette;
+pub mod playback;
+pub mod queue;
+pub mod search;
+pub mod session;
+pub mod storage;
+pub mod theme;
+pub mod visualizer;
+pub mod volume;
+pub mod window_state;
@@ -0,0 +1,474 @@
+use super::library::{Playlist, Selection, Track};
+use crate::environment;
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+const FILE_NAME: &str = "cache.db";
+
+// Albums and artists of the library are stored as items too, the uri tells them apart
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS items (
+        uri TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        artist TEXT NOT NULL,
+        album TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS playlists (
+        id TEXT PRIMARY KEY,
+        uri TEXT NOT NULL,
+        name TEXT NOT NULL,
+        owner TEXT NOT NULL,
+        snapshot_id TEXT NOT NULL,
+        total INTEGER NOT NULL,
+        position INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS collections (
+        key TEXT PRIMARY KEY,
+        snapshot_id TEXT,
+        synced_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS entries (
+        collection TEXT NOT NULL REFERENCES collections (key) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        uri TEXT NOT NULL,
+        added_at TEXT,
+        PRIMARY KEY (collection, position)
+    );
+";
+
+/// Applied in order on top of [`SCHEMA`], `user_version` counts those already applied.
+const MIGRATIONS: [&str; 2] = [
+    "ALTER TABLE items ADD COLUMN images TEXT",
+    // Forgetting the snapshots refetches the playlists, to fill the new columns in
+    "ALTER TABLE items ADD COLUMN album_uri TEXT;
+     ALTER TABLE items ADD COLUMN artist_uri TEXT;
+     UPDATE collections SET snapshot_id = NULL;",
+];
+
+/// Library metadata stored in the data directory, so the library renders without a connection.
+#[derive(Clone)]
+pub struct Cache {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Cache {
+    /// Falls back to an in-memory database when the file cannot be opened.
+    pub fn open() -> Self {
+        let connection = open_file()
+            .inspect_err(|err| log::warn!("Cannot open the metadata cache: {err}"))
+            .or_else(|_| open_memory())
+            .expect("An in-memory database can always be opened");
+
+        Self {
+            connection: Arc::new(Mutex::new(connection)),
+        }
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        let empty = self.connection()?.query_row(
+            "SELECT NOT EXISTS (SELECT 1 FROM playlists) AND NOT EXISTS (SELECT 1 FROM collections)",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(empty)
+    }
+
+    /// Stored items and playlists.
+    pub fn len(&self) -> Result<usize> {
+        let len = self.connection()?.query_row(
+            "SELECT (SELECT COUNT(*) FROM items) + (SELECT COUNT(*) FROM playlists)",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(len)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.connection()?.execute_batch(
+            "DELETE FROM entries; DELETE FROM collections; DELETE FROM playlists; DELETE FROM items;",
+        )?;
+
+        Ok(())
+    }
+
+    pub fn playlists(&self) -> Result<Vec<Playlist>> {
+        let connection = self.connection()?;
+        let mut statement = connection.prepare(
+            "SELECT id, uri, name, owner, snapshot_id, total FROM playlists ORDER BY position",
+        )?;
+
+        let playlists = statement
+            .query_map([], |row| {
+                Ok(Playlist {
+                    id: row.get(0)?,
+                    uri: row.get(1)?,
+                    name: row.get(2)?,
+                    owner: row.get(3)?,
+                    snapshot_id: row.get(4)?,
+                    total: row.get(5)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(playlists)
+    }
+
+    /// Replaces the playlists, the tracks of those no longer in the library are dropped.
+    pub fn save_playlists(&self, playlists: &[Playlist]) -> Result<()> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+
+        transaction.execute("DELETE FROM playlists", [])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO playlists (id, uri, name, owner, snapshot_id, total, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+
+            for (position, playlist) in playlists.iter().enumerate() {
+                insert.execute(params![
+                    playlist.id,
+                    playlist.uri,
+                    playlist.name,
+                    playlist.owner,
+                    playlist.snapshot_id,
+                    playlist.total,
+                    position,
+                ])?;
+            }
+        }
+
+        transaction.execute(
+            "DELETE FROM collections WHERE key LIKE 'playlist:%'
+             AND key NOT IN (SELECT 'playlist:' || id FROM playlists)",
+            [],
+        )?;
+        prune(&transaction)?;
+
+        Ok(transaction.commit()?)
+    }
+
+    /// `None` when `selection` was never stored.
+    pub fn tracks(&self, selection: &Selection) -> Result<Option<Vec<Track>>> {
+        let connection = self.connection()?;
+        let key = key(selection);
+
+        let stored = connection
+            .query_row("SELECT 1 FROM collections WHERE key = ?1", [&key], |_| {
+                Ok(())
+            })
+            .optional()?;
+        if stored.is_none() {
+            return Ok(None);
+        }
+
+        let mut statement = connection.prepare(
+            "SELECT items.uri, name, artist, album, duration_ms, added_at, images,
+                    album_uri, artist_uri
+             FROM entries JOIN items ON items.uri = entries.uri
+             WHERE collection = ?1 ORDER BY position",
+        )?;
+
+        let tracks = statement
+            .query_map([&key], |row| {
+                Ok(Track {
+                    uri: row.get(0)?,
+                    name: row.get(1)?,
+                    artist: row.get(2)?,
+                    album: row.get(3)?,
+                    duration_ms: row.get(4)?,
+                    added_at: row.get(5)?,
+                    images: row
+                        .get::<_, Option<String>>(6)?
+                        .and_then(|images| serde_json::from_str(&images).ok())
+                        .unwrap_or_default(),
+                    album_uri: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+                    artist_uri: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(tracks))
+    }
+
+    /// Replaces the tracks of `selection`, playlists keep the snapshot they were fetched at.
+    pub fn save_tracks(&self, selection: &Selection, tracks: &[Track]) -> Result<()> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+        let key = key(selection);
+        let playlist_id = match selection {
+            Selection::Playlist(id) => Some(id),
+            Selection::Section(_) => None,
+        };
+
+        transaction.execute("DELETE FROM entries WHERE collection = ?1", [&key])?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO collections (key, snapshot_id, synced_at)
+             VALUES (?1, (SELECT snapshot_id FROM playlists WHERE id = ?2), ?3)",
+            params![key, playlist_id, chrono::Utc::now()],
+        )?;
+        {
+            let mut item = transaction.prepare(
+                "INSERT OR REPLACE INTO items
+                 (uri, name, artist, album, duration_ms, images, album_uri, artist_uri)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut entry = transaction.prepare(
+                "INSERT INTO entries (collection, position, uri, added_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+
+            for (position, track) in tracks.iter().enumerate() {
+                item.execute(params![
+                    track.uri,
+                    track.name,
+                    track.artist,
+                    track.album,
+                    track.duration_ms,
+                    serde_json::to_string(&track.images)?,
+                    track.album_uri,
+                    track.artist_uri,
+                ])?;
+                entry.execute(params![key, position, track.uri, track.added_at])?;
+            }
+        }
+        prune(&transaction)?;
+
+        Ok(transaction.commit()?)
+    }
+
+    /// Snapshot at which the tracks of each stored playlist were fetched, by playlist id.
+    pub fn snapshots(&self) -> Result<HashMap<String, Option<String>>> {
+        let connection = self.connection()?;
+        let mut statement = connection.prepare(
+            "SELECT substr(key, length('playlist:') + 1), snapshot_id
+             FROM collections WHERE key LIKE 'playlist:%'",
+        )?;
+
+        let snapshots = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        Ok(snapshots)
+    }
+
+    /// Whether the stored tracks of a playlist match its latest known snapshot.
+    pub fn is_fresh(&self, id: &str) -> Result<bool> {
+        let fresh = self
+            .connection()?
+            .query_row(
+                "SELECT collections.snapshot_id = playlists.snapshot_id
+                 FROM collections JOIN playlists ON collections.key = 'playlist:' || playlists.id
+                 WHERE playlists.id = ?1",
+                [id],
+                |row| row.get::<_, Option<bool>>(0),
+            )
+            .optional()?;
+
+        Ok(fresh.flatten().unwrap_or(false))
+    }
+
+    fn connection(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.connection
+            .lock()
+            .map_err(|_| anyhow!("The metadata cache is poisoned"))
+    }
+}
+
+pub fn path() -> PathBuf {
+    environment::data_dir().join(FILE_NAME)
+}
+
+fn open_file() -> Result<Connection> {
+    let parent = environment::data_dir();
+
+    if !parent.exists() {
+        fs::create_dir_all(&parent)?;
+    }
+
+    init(Connection::open(path())?)
+}
+
+fn open_memory() -> Result<Connection> {
+    init(Connection::open_in_memory()?)
+}
+
+fn init(mut connection: Connection) -> Result<Connection> {
+    connection.pragma_update(None, "foreign_keys", true)?;
+    connection.execute_batch(SCHEMA)?;
+    migrate(&mut connection, &MIGRATIONS)?;
+
+    Ok(connection)
+}
+
+/// Each migration commits along with its `user_version`, one failing halfway is retried
+/// from the start rather than applied twice.
+fn migrate(connection: &mut Connection, migrations: &[&str]) -> Result<()> {
+    let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (index, migration) in migrations.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+    }
+
+    Ok(())
+}
+
+fn key(selection: &Selection) -> String {
+    match selection {
+        Selection::Section(section) => format!("section:{section:?}"),
+        Selection::Playlist(id) => format!("playlist:{id}"),
+    }
+}
+
+/// Drops the items no collection refers to anymore.
+fn prune(connection: &Connection) -> rusqlite::Result<usize> {
+    connection.execute(
+        "DELETE FROM items WHERE uri NOT IN (SELECT uri FROM entries)",
+        [],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::library::track;
+
+    fn cache() -> Cache {
+        Cache {
+            connection: Arc::new(Mutex::new(open_memory().unwrap())),
+        }
+    }
+
+    fn playlist(id: &str, snapshot_id: &str) -> Playlist {
+        Playlist {
+            id: id.to_string(),
+            uri: format!("spotify:playlist:{id}"),
+            name: id.to_string(),
+            owner: String::from("owner"),
+            snapshot_id: snapshot_id.to_string(),
+            total: 1,
+        }
+    }
+
+    fn version(connection: &Connection) -> usize {
+        connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrations_upgrade_an_old_database() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(SCHEMA).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO items (uri, name, artist, album, duration_ms)
+                 VALUES ('spotify:track:a', 'a', 'artist', 'album', 1000);
+                 INSERT INTO collections (key, snapshot_id, synced_at)
+                 VALUES ('playlist:p', 's1', '2026-01-01T00:00:00Z');",
+            )
+            .unwrap();
+
+        let connection = init(connection).unwrap();
+
+        assert_eq!(version(&connection), MIGRATIONS.len());
+        let columns: (Option<String>, Option<String>, Option<String>) = connection
+            .query_row(
+                "SELECT images, album_uri, artist_uri FROM items",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(columns, (None, None, None));
+        // Forgotten, so the playlist is fetched again with the new columns
+        let snapshot: Option<String> = connection
+            .query_row("SELECT snapshot_id FROM collections", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(snapshot, None);
+    }
+
+    #[test]
+    fn migrations_only_run_once() {
+        let connection = init(Connection::open_in_memory().unwrap()).unwrap();
+
+        // Adding the same columns again would fail
+        let connection = init(connection).unwrap();
+
+        assert_eq!(version(&connection), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn failed_migrations_are_rolled_back() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(SCHEMA).unwrap();
+
+        let failing = [
+            "ALTER TABLE items ADD COLUMN first TEXT",
+            "ALTER TABLE items ADD COLUMN second TEXT;
+             ALTER TABLE missing ADD COLUMN third TEXT;",
+        ];
+        assert!(migrate(&mut connection, &failing).is_err());
+
+        assert_eq!(version(&connection), 1);
+        // The second migration is applied again from its start, without a duplicate column
+        migrate(
+            &mut connection,
+            &[failing[0], "ALTER TABLE items ADD COLUMN second TEXT"],
+        )
+        .unwrap();
+        assert_eq!(version(&connection), 2);
+    }
+
+    #[test]
+    fn new_snapshot_makes_a_playlist_stale() {
+        let cache = cache();
+        let selection = Selection::Playlist(String::from("p"));
+
+        cache.save_playlists(&[playlist("p", "s1")]).unwrap();
+        assert!(!cache.is_fresh("p").unwrap());
+
+        cache.save_tracks(&selection, &[track("a")]).unwrap();
+        assert!(cache.is_fresh("p").unwrap());
+        assert_eq!(
+            cache.snapshots().unwrap(),
+            HashMap::from([(String::from("p"), Some(String::from("s1")))])
+        );
+
+        cache.save_playlists(&[playlist("p", "s2")]).unwrap();
+        assert!(!cache.is_fresh("p").unwrap());
+        // The outdated tracks still render until the new ones arrive
+        assert_eq!(cache.tracks(&selection).unwrap(), Some(vec![track("a")]));
+
+        cache
+            .save_tracks(&selection, &[track("a"), track("b")])
+            .unwrap();
+        assert!(cache.is_fresh("p").unwrap());
+    }
+
+    #[test]
+    fn removed_playlists_drop_their_tracks() {
+        let cache = cache();
+        let selection = Selection::Playlist(String::from("p"));
+        cache.save_playlists(&[playlist("p", "s1")]).unwrap();
+        cache.save_tracks(&selection, &[track("a")]).unwrap();
+
+        cache.save_playlists(&[]).unwrap();
+
+        assert_eq!(cache.tracks(&selection).unwrap(), None);
+        assert!(cache.snapshots().unwrap().is_empty());
+        assert!(cache.is_empty().unwrap());
+        assert_eq!(cache.len().unwrap(), 0);
+    }
+}
@@ -0,0 +1,271 @@
+use super::{library::Image, storage};
+use crate::environment;
+use anyhow::{anyhow, Result};
+use iced::{widget::image::Handle, Color};
+use image::RgbaImage;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Bytes of covers kept on disk, the least recently shown are evicted above it.
+const LIMIT: u64 = 64 * 1024 * 1024;
+
+/// Sizes a cover is fetched in, the small one for the bars and the large one for the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    Small,
+    Large,
+}
+
+impl Size {
+    /// Smallest size that is not upscaled when shown `pixels` wide.
+    pub fn fitting(pixels: f32) -> Self {
+        if pixels <= Size::Small.width() as f32 {
+            Size::Small
+        } else {
+            Size::Large
+        }
+    }
+
+    /// Spotify serves covers 64, 300 and 640 pixels wide, the bars show them at most 64
+    /// and the queue at 96, which stays sharp on HiDPI screens.
+    fn width(self) -> u32 {
+        match self {
+            Size::Small => 64,
+            Size::Large => 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cover {
+    /// Url of the large size, which tells the cover of a track.
+    pub url: String,
+    small: Handle,
+    large: Handle,
+    /// Most common vivid color, to tint whatever the cover is shown on.
+    pub color: Option<Color>,
+}
+
+impl Cover {
+    pub fn handle(&self, size: Size) -> &Handle {
+        match size {
+            Size::Small => &self.small,
+            Size::Large => &self.large,
+        }
+    }
+}
+
+/// Url of the narrowest image at least as wide as `size`, or of the widest one.
+pub fn url(images: &[Image], size: Size) -> Option<&str> {
+    let width = size.width();
+
+    images
+        .iter()
+        .filter(|image| image.width.is_some_and(|w| w >= width))
+        .min_by_key(|image| image.width)
+        .or_else(|| images.iter().max_by_key(|image| image.width))
+        .map(|image| image.url.as_str())
+}
+
+/// Reads every size of the cover from the disk cache or downloads it,
+/// then decodes them off the UI thread.
+pub async fn load(client: reqwest::Client, images: Vec<Image>) -> Result<Cover> {
+    let (Some(small), Some(large)) = (url(&images, Size::Small), url(&images, Size::Large)) else {
+        return Err(anyhow!("The track has no cover"));
+    };
+
+    let small = fetch(&client, small).await?;
+    // Both sizes are the same image when the track has a single one
+    let large = if large == small.url {
+        None
+    } else {
+        Some(fetch(&client, large).await?)
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let small_image = decode(&small)?;
+        let large_image = large.as_ref().map(decode).transpose()?;
+        let color = dominant_color(&small_image);
+
+        anyhow::Ok(Cover {
+            url: large.map_or(small.url, |large| large.url),
+            large: handle(large_image.unwrap_or_else(|| small_image.clone())),
+            small: handle(small_image),
+            color,
+        })
+    })
+    .await?
+}
+
+/// Bytes of a cover, fresh from the disk cache or the network.
+struct Fetched {
+    url: String,
+    path: PathBuf,
+    bytes: Vec<u8>,
+    downloaded: bool,
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Fetched> {
+    let path = path(url);
+
+    let (bytes, downloaded) = match tokio::fs::read(&path).await {
+        Ok(bytes) => (bytes, false),
+        Err(_) => {
+            log::debug!("GET {url}");
+            let bytes = client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+
+            (bytes.to_vec(), true)
+        }
+    };
+
+    Ok(Fetched {
+        url: url.to_string(),
+        path,
+        bytes,
+        downloaded,
+    })
+}
+
+fn directory() -> PathBuf {
+    environment::cache_dir().join("covers")
+}
+
+/// Covers are named after the image id, the last segment of their url.
+fn path(url: &str) -> PathBuf {
+    let name: String = url
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+
+    directory().join(name)
+}
+
+/// The cover is still shown when it cannot be cached.
+fn save(path: &Path, bytes: &[u8]) {
+    let result = fs::create_dir_all(directory())
+        .and_then(|_| fs::write(path, bytes))
+        .and_then(|_| storage::evict_files(&directory(), LIMIT, |_| false));
+
+    if let Err(err) = result {
+        log::warn!("Cannot cache the cover {}: {err}", path.display());
+    }
+}
+
+/// Marks the cover as recently used for the eviction.
+fn touch(path: &Path) {
+    let _ = fs::File::options()
+        .append(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+}
+
+/// Caches the cover once it is known to decode.
+fn decode(fetched: &Fetched) -> Result<RgbaImage> {
+    let image = image::load_from_memory(&fetched.bytes)?.into_rgba8();
+
+    if fetched.downloaded {
+        save(&fetched.path, &fetched.bytes);
+    } else {
+        touch(&fetched.path);
+    }
+
+    Ok(image)
+}
+
+fn handle(image: RgbaImage) -> Handle {
+    let (width, height) = image.dimensions();
+
+    Handle::from_rgba(width, height, image.into_raw())
+}
+
+/// Average of the most common quantized color, the grays most covers have are skipped.
+fn dominant_color(image: &RgbaImage) -> Option<Color> {
+    let mut buckets: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+
+    // Every fourth pixel is plenty to find the dominant color
+    for pixel in image.pixels().step_by(4) {
+        let [r, g, b, _] = pixel.0;
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+
+        if max < 40 || min > 215 || max - min < 30 {
+            continue;
+        }
+
+        let (count, sum) = buckets.entry([r >> 5, g >> 5, b >> 5]).or_default();
+        *count += 1;
+        sum[0] += r as u32;
+        sum[1] += g as u32;
+        sum[2] += b as u32;
+    }
+
+    buckets
+        .into_values()
+        .max_by_key(|(count, _)| *count)
+        .map(|(count, sum)| {
+            Color::from_rgb8(
+                (sum[0] / count) as u8,
+                (sum[1] / count) as u8,
+                (sum[2] / count) as u8,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn images(widths: &[u32]) -> Vec<Image> {
+        widths
+            .iter()
+            .map(|width| Image {
+                url: format!("https://i.scdn.co/image/{width}"),
+                width: Some(*width),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn each_size_gets_the_narrowest_image_wide_enough() {
+        let images = images(&[640, 300, 64]);
+
+        assert_eq!(
+            url(&images, Size::Small),
+            Some("https://i.scdn.co/image/64")
+        );
+        assert_eq!(
+            url(&images, Size::Large),
+            Some("https://i.scdn.co/image/300")
+        );
+    }
+
+    #[test]
+    fn narrow_covers_fall_back_to_the_widest_image() {
+        let images = images(&[64, 32]);
+
+        assert_eq!(
+            url(&images, Size::Large),
+            Some("https://i.scdn.co/image/64")
+        );
+        assert_eq!(url(&[], Size::Small), None);
+    }
+
+    #[test]
+    fn sizes_fit_where_the_cover_is_shown() {
+        assert_eq!(Size::fitting(48.0), Size::Small);
+        assert_eq!(Size::fitting(64.0), Size::Small);
+        assert_eq!(Size::fitting(96.0), Size::Large);
+    }
+}
@@ -0,0 +1,105 @@
+use super::library::{format_total, Track};
+
+/// Why a detail page could not be fetched, shown in its place until it is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    Offline,
+    Error,
+}
+
+impl Failure {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Failure::Offline => "Not available while offline",
+            Failure::Error => "Cannot load it right now",
+        }
+    }
+}
+
+/// An album with every one of its tracks.
+#[derive(Debug, Clone)]
+pub struct AlbumDetails {
+    pub uri: String,
+    pub name: String,
+    pub artist: String,
+    pub artist_uri: String,
+    /// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, as precisely as spotify knows it.
+    pub release_date: String,
+    pub label: String,
+    pub tracks: Vec<Track>,
+}
+
+impl AlbumDetails {
+    pub fn duration(&self) -> String {
+        format_total(&self.tracks)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArtistDetails {
+    pub uri: String,
+    pub name: String,
+    pub followers: u32,
+    pub genres: Vec<String>,
+    pub top_tracks: Vec<Track>,
+    /// Releases by group, in the order of [`AlbumGroup`], without the empty groups.
+    pub discography: Vec<(AlbumGroup, Vec<Release>)>,
+    /// Artists as search has them, empty when spotify does not serve them.
+    pub related: Vec<Track>,
+    /// Unknown when spotify did not tell, the artist still opens without it.
+    pub following: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub uri: String,
+    pub name: String,
+    pub release_date: String,
+}
+
+impl Release {
+    pub fn year(&self) -> &str {
+        self.release_date.get(..4).unwrap_or(&self.release_date)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum AlbumGroup {
+    Albums,
+    #[strum(serialize = "Singles and EPs")]
+    Singles,
+    Compilations,
+    #[strum(serialize = "Appears On")]
+    AppearsOn,
+}
+
+impl AlbumGroup {
+    pub fn api_type(&self) -> &'static str {
+        match self {
+            AlbumGroup::Albums => "album",
+            AlbumGroup::Singles => "single",
+            AlbumGroup::Compilations => "compilation",
+            AlbumGroup::AppearsOn => "appears_on",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaylistDetails {
+    pub uri: String,
+    pub name: String,
+    /// Plain text, spotify serves it as html.
+    pub description: String,
+    pub owner: String,
+    pub collaborative: bool,
+    /// Names of the users other than the owner who added tracks.
+    pub collaborators: Vec<String>,
+    pub followers: u32,
+    pub tracks: Vec<Track>,
+}
+
+impl PlaylistDetails {
+    pub fn duration(&self) -> String {
+        format_total(&self.tracks)
+    }
+}
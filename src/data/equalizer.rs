@@ -0,0 +1,40 @@
+/// Center frequencies of the graphic equalizer bands, in Hz.
+pub const FREQUENCIES: [f64; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Gain range of each band and of the preamp, in dB.
+pub const GAIN_RANGE: std::ops::RangeInclusive<f64> = -12.0..=12.0;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, strum::Display, strum::EnumString, strum::EnumIter,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Flat,
+    Bass,
+    Treble,
+    Vocal,
+    Rock,
+    Electronic,
+    /// Whatever the bands in the config are set to.
+    Custom,
+}
+
+impl Preset {
+    /// Band gains in dB, `None` for [`Preset::Custom`].
+    pub fn gains(self) -> Option<[f64; 10]> {
+        let gains = match self {
+            Preset::Flat => [0.0; 10],
+            Preset::Bass => [6.0, 5.0, 4.0, 2.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0],
+            Preset::Treble => [0.0, 0.0, 0.0, 0.0, 0.0, 0.5, 2.0, 4.0, 5.0, 6.0],
+            Preset::Vocal => [-2.0, -2.0, -1.0, 1.0, 3.0, 3.5, 3.0, 1.5, 0.0, -1.0],
+            Preset::Rock => [4.5, 3.5, 2.0, -0.5, -1.5, -1.0, 1.0, 2.5, 3.5, 4.0],
+            Preset::Electronic => [5.0, 4.0, 1.5, 0.0, -1.5, 1.0, 0.5, 1.5, 4.0, 4.5],
+            Preset::Custom => return None,
+        };
+
+        Some(gains)
+    }
+}
@@ -1,74 +1,141 @@
-use iced::font;
-use std::borrow::Cow;
-use std::sync::OnceLock;
+use crate::{context::config::Appaerance, environment};
+use anyhow::{anyhow, Result};
+use iced::font::{Family, Weight};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, RwLock},
+};
 
-pub static MONO: Font = Font::new(false, false);
-pub static MONO_BOLD: Font = Font::new(true, false);
-pub static MONO_ITALICS: Font = Font::new(false, true);
-pub static MONO_BOLD_ITALICS: Font = Font::new(true, true);
+const DIRECTORY: &str = "fonts";
+const EXTENSIONS: [&str; 3] = ["ttf", "otf", "ttc"];
 
-#[derive(Debug, Clone)]
+pub static BODY: Font = Font::new(Weight::Normal);
+pub static HEADING: Font = Font::new(Weight::Bold);
+
+/// A font whose family follows the config, so it is read again on every view.
+#[derive(Debug)]
 pub struct Font {
-    bold: bool,
-    italics: bool,
-    inner: OnceLock<iced::Font>,
+    weight: Weight,
+    family: RwLock<Family>,
 }
 
 impl Font {
-    const fn new(bold: bool, italics: bool) -> Self {
+    const fn new(weight: Weight) -> Self {
         Self {
-            bold,
-            italics,
-            inner: OnceLock::new(),
+            weight,
+            family: RwLock::new(Family::Monospace),
         }
     }
 
-    fn set(&self, name: String) {
-        let name = Box::leak(name.into_boxed_str());
-        let weight = if self.bold {
-            font::Weight::Bold
-        } else {
-            font::Weight::Normal
-        };
-        let style = if self.italics {
-            font::Style::Italic
-        } else {
-            font::Style::Normal
-        };
-
-        let _ = self.inner.set(iced::Font {
-            weight,
-            style,
-            ..iced::Font::with_name(name)
-        });
+    pub fn get(&self) -> iced::Font {
+        iced::Font {
+            family: *self.family.read().expect("font family is readable"),
+            weight: self.weight,
+            ..iced::Font::MONOSPACE
+        }
+    }
+
+    fn set(&self, family: Family) {
+        *self.family.write().expect("font family is writable") = family;
+    }
+}
+
+/// Uses the configured families, or the system monospace font when their files could not be loaded.
+pub fn set(appaerance: &Appaerance, fallback: bool) {
+    if fallback {
+        BODY.set(Family::Monospace);
+        HEADING.set(Family::Monospace);
+        return;
     }
+
+    let body = appaerance
+        .font_family
+        .as_deref()
+        .map_or(Family::Monospace, family);
+    let heading = appaerance
+        .heading_font_family
+        .as_deref()
+        .map_or(body, family);
+
+    BODY.set(body);
+    HEADING.set(heading);
 }
 
-impl From<Font> for iced::Font {
-    fn from(value: Font) -> Self {
-        value.inner.get().copied().expect("font is set on startup")
+/// Reads the configured font files, or every font in the `fonts` directory when none are.
+pub async fn read(files: Vec<String>) -> Result<Vec<Vec<u8>>> {
+    let directory = environment::config_dir().join(DIRECTORY);
+
+    let paths = if files.is_empty() {
+        fonts_in(&directory).await
+    } else {
+        files.iter().map(|file| directory.join(file)).collect()
+    };
+
+    let mut fonts = Vec::with_capacity(paths.len());
+
+    for path in paths {
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|err| anyhow!("Cannot load the font {}: {err}", path.display()))?;
+
+        if !is_font(&bytes) {
+            return Err(anyhow!("{} is not a font", path.display()));
+        }
+
+        fonts.push(bytes);
     }
+
+    Ok(fonts)
 }
 
-pub fn set() {
-    let family = String::from("Iosevka Term");
+async fn fonts_in(directory: &Path) -> Vec<PathBuf> {
+    let Ok(mut entries) = tokio::fs::read_dir(directory).await else {
+        return Vec::new();
+    };
+
+    let mut paths = Vec::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let is_font = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+
+        if is_font {
+            paths.push(path);
+        }
+    }
+
+    paths
+}
 
-    MONO.set(family.clone());
-    MONO_BOLD.set(family.clone());
-    MONO_ITALICS.set(family.clone());
-    MONO_BOLD_ITALICS.set(family);
+/// The renderer silently skips what it cannot parse, so the magic number is checked here.
+fn is_font(bytes: &[u8]) -> bool {
+    matches!(
+        bytes.get(..4),
+        Some(
+            [0, 1, 0, 0]
+                | [b'O', b'T', b'T', b'O']
+                | [b't', b'r', b'u', b'e']
+                | [b't', b't', b'c', b'f']
+        )
+    )
 }
 
-pub fn load() -> Vec<Cow<'static, [u8]>> {
-    vec![
-        include_bytes!("../../fonts/iosevka-term-regular.ttf")
-            .as_slice()
-            .into(),
-        include_bytes!("../../fonts/iosevka-term-bold.ttf")
-            .as_slice()
-            .into(),
-        include_bytes!("../../fonts/iosevka-term-italic.ttf")
-            .as_slice()
-            .into(),
-    ]
+/// iced needs the family names to be static, so each distinct one is leaked once.
+fn family(name: &str) -> Family {
+    static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    let mut names = NAMES.lock().expect("font names are readable");
+    let name = match names.iter().find(|known| **known == name) {
+        Some(known) => *known,
+        None => {
+            let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.push(leaked);
+            leaked
+        }
+    };
+
+    Family::Name(name)
 }
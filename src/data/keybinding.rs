@@ -0,0 +1,328 @@
+use iced::keyboard::{key::Named, Key, Modifiers};
+use std::{collections::BTreeMap, str::FromStr};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    PlayPause,
+    Next,
+    Prev,
+    SeekForward,
+    SeekBackward,
+    VolumeUp,
+    VolumeDown,
+    FocusSearch,
+    GoToLibrary,
+    ToggleQueue,
+    Back,
+    Forward,
+    ReloadConfig,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    CommandPalette,
+    /// Moves the cursor of the list on screen.
+    Up,
+    Down,
+    First,
+    Last,
+    /// Plays the track under the cursor.
+    Activate,
+}
+
+impl Action {
+    /// Chords separated by commas, `mod` is Cmd on macOS and Ctrl elsewhere.
+    fn default_chords(self) -> &'static str {
+        match self {
+            Action::PlayPause => "space",
+            Action::Next => "mod+right",
+            Action::Prev => "mod+left",
+            Action::SeekForward => "shift+right",
+            Action::SeekBackward => "shift+left",
+            Action::VolumeUp => "mod+up",
+            Action::VolumeDown => "mod+down",
+            Action::FocusSearch => "mod+f, /",
+            Action::GoToLibrary => "mod+l",
+            Action::ToggleQueue => "mod+u",
+            Action::Back => "alt+left",
+            Action::Forward => "alt+right",
+            Action::ReloadConfig => "mod+r",
+            Action::ZoomIn => "mod+=, mod+plus",
+            Action::ZoomOut => "mod+-",
+            Action::ResetZoom => "mod+0",
+            Action::CommandPalette => "mod+k",
+            Action::Up => "k, up",
+            Action::Down => "j, down",
+            Action::First => "g, home",
+            Action::Last => "shift+g, end",
+            Action::Activate => "enter",
+        }
+    }
+
+    /// Name shown in the command palette.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::PlayPause => "Play / Pause",
+            Action::Next => "Next track",
+            Action::Prev => "Previous track",
+            Action::SeekForward => "Seek forward",
+            Action::SeekBackward => "Seek backward",
+            Action::VolumeUp => "Volume up",
+            Action::VolumeDown => "Volume down",
+            Action::FocusSearch => "Search",
+            Action::GoToLibrary => "Go to library",
+            Action::ToggleQueue => "Toggle queue",
+            Action::Back => "Go back",
+            Action::Forward => "Go forward",
+            Action::ReloadConfig => "Reload config",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::ResetZoom => "Reset zoom",
+            Action::CommandPalette => "Command palette",
+            Action::Up => "Move cursor up",
+            Action::Down => "Move cursor down",
+            Action::First => "Move cursor to the first item",
+            Action::Last => "Move cursor to the last item",
+            Action::Activate => "Play the item under the cursor",
+        }
+    }
+}
+
+/// `[keybindings]` of the config, from action names to chords. Only the actions given
+/// there are rebound, unknown names are reported rather than failing the whole config.
+pub type Keybindings = BTreeMap<String, String>;
+
+/// A key with the modifiers that have to be held with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Chord {
+    key: Key,
+    modifiers: Modifiers,
+}
+
+impl Chord {
+    fn parse(chord: &str) -> Result<Self, String> {
+        let mut modifiers = Modifiers::empty();
+        let mut parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "mod" => {
+                    if cfg!(target_os = "macos") {
+                        Modifiers::LOGO
+                    } else {
+                        Modifiers::CTRL
+                    }
+                }
+                "ctrl" | "control" => Modifiers::CTRL,
+                "shift" => Modifiers::SHIFT,
+                "alt" | "option" => Modifiers::ALT,
+                "super" | "cmd" | "logo" => Modifiers::LOGO,
+                _ => return Err(format!("unknown modifier \"{part}\" in \"{chord}\"")),
+            };
+        }
+
+        let key = match key.to_lowercase().as_str() {
+            "space" => Key::Named(Named::Space),
+            "enter" | "return" => Key::Named(Named::Enter),
+            "esc" | "escape" => Key::Named(Named::Escape),
+            "tab" => Key::Named(Named::Tab),
+            "backspace" => Key::Named(Named::Backspace),
+            "delete" => Key::Named(Named::Delete),
+            "up" => Key::Named(Named::ArrowUp),
+            "down" => Key::Named(Named::ArrowDown),
+            "left" => Key::Named(Named::ArrowLeft),
+            "right" => Key::Named(Named::ArrowRight),
+            "home" => Key::Named(Named::Home),
+            "end" => Key::Named(Named::End),
+            "pageup" => Key::Named(Named::PageUp),
+            "pagedown" => Key::Named(Named::PageDown),
+            "plus" => Key::Character("+".into()),
+            "comma" => Key::Character(",".into()),
+            "" => return Err(format!("missing key in \"{chord}\"")),
+            name if name.chars().count() == 1 => Key::Character(name.into()),
+            name => return Err(format!("unknown key \"{name}\" in \"{chord}\"")),
+        };
+
+        Ok(Self { key, modifiers })
+    }
+
+    /// Letters are compared lowercase. Shift is ignored for other characters, whether
+    /// it is needed to type them depends on the keyboard layout.
+    fn matches(&self, key: &Key, modifiers: Modifiers) -> bool {
+        match (&self.key, key) {
+            (Key::Character(expected), Key::Character(pressed)) => {
+                let is_letter = expected.chars().all(char::is_alphabetic);
+                let ignored = if is_letter {
+                    Modifiers::empty()
+                } else {
+                    Modifiers::SHIFT
+                };
+
+                expected.to_lowercase() == pressed.to_lowercase()
+                    && self.modifiers.difference(ignored) == modifiers.difference(ignored)
+            }
+            (expected, pressed) => expected == pressed && self.modifiers == modifiers,
+        }
+    }
+}
+
+/// The chords of every action, the configured ones replacing the defaults.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    bindings: Vec<(Chord, Action)>,
+}
+
+impl Keymap {
+    /// Invalid chords are skipped and a chord bound twice only triggers its first action,
+    /// both are reported as warnings. Configured actions come first, so they win over defaults,
+    /// which silently lose the chords taken from them.
+    pub fn new(keybindings: &Keybindings) -> (Self, Vec<String>) {
+        let mut bindings: Vec<(Chord, Action)> = Vec::new();
+        let mut warnings: Vec<String> = keybindings
+            .keys()
+            .filter(|name| Action::from_str(name).is_err())
+            .map(|name| format!("Unknown keybinding action \"{name}\""))
+            .collect();
+
+        let configured = |action: Action| keybindings.contains_key(&action.to_string());
+        let (user, defaults): (Vec<Action>, Vec<Action>) =
+            Action::iter().partition(|action| configured(*action));
+
+        for action in user.into_iter().chain(defaults) {
+            let chords = keybindings
+                .get(&action.to_string())
+                .map_or(action.default_chords(), String::as_str);
+
+            for chord in chords.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+                let parsed = match Chord::parse(chord) {
+                    Ok(parsed) => parsed,
+                    Err(err) => {
+                        warnings.push(format!("Invalid keybinding for {action}: {err}"));
+                        continue;
+                    }
+                };
+
+                match bindings.iter().find(|(bound, _)| *bound == parsed) {
+                    Some((_, other)) if configured(*other) && !configured(action) => {}
+                    Some((_, other)) => warnings.push(format!(
+                        "\"{chord}\" is bound to both {other} and {action}, only {other} applies"
+                    )),
+                    None => bindings.push((parsed, action)),
+                }
+            }
+        }
+
+        (Self { bindings }, warnings)
+    }
+
+    pub fn action(&self, key: &Key, modifiers: Modifiers) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(chord, _)| chord.matches(key, modifiers))
+            .map(|(_, action)| *action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character(key: &str) -> Key {
+        Key::Character(key.into())
+    }
+
+    fn keymap(bindings: &[(&str, &str)]) -> (Keymap, Vec<String>) {
+        Keymap::new(
+            &bindings
+                .iter()
+                .map(|(action, chords)| (action.to_string(), chords.to_string()))
+                .collect::<Keybindings>(),
+        )
+    }
+
+    #[test]
+    fn chords_parse_modifiers_and_keys() {
+        let chord = Chord::parse("ctrl + Shift+Left").unwrap();
+        assert_eq!(chord.key, Key::Named(Named::ArrowLeft));
+        assert_eq!(chord.modifiers, Modifiers::CTRL | Modifiers::SHIFT);
+
+        let command = if cfg!(target_os = "macos") {
+            Modifiers::LOGO
+        } else {
+            Modifiers::CTRL
+        };
+        assert_eq!(Chord::parse("mod+k").unwrap().modifiers, command);
+        assert_eq!(Chord::parse("plus").unwrap().key, character("+"));
+        assert_eq!(Chord::parse("/").unwrap().key, character("/"));
+    }
+
+    #[test]
+    fn invalid_chords_are_errors() {
+        assert!(Chord::parse("hyper+k").is_err());
+        assert!(Chord::parse("ctrl+").is_err());
+        assert!(Chord::parse("ctrl+nope").is_err());
+    }
+
+    #[test]
+    fn letters_match_in_any_case_but_with_the_same_modifiers() {
+        let chord = Chord::parse("shift+g").unwrap();
+
+        assert!(chord.matches(&character("G"), Modifiers::SHIFT));
+        assert!(!chord.matches(&character("g"), Modifiers::empty()));
+    }
+
+    #[test]
+    fn shift_is_ignored_for_other_characters() {
+        let chord = Chord::parse("ctrl+plus").unwrap();
+
+        assert!(chord.matches(&character("+"), Modifiers::CTRL | Modifiers::SHIFT));
+        assert!(chord.matches(&character("+"), Modifiers::CTRL));
+        assert!(!chord.matches(&character("+"), Modifiers::empty()));
+    }
+
+    #[test]
+    fn named_keys_need_the_exact_modifiers() {
+        let chord = Chord::parse("shift+right").unwrap();
+
+        assert!(chord.matches(&Key::Named(Named::ArrowRight), Modifiers::SHIFT));
+        assert!(!chord.matches(
+            &Key::Named(Named::ArrowRight),
+            Modifiers::SHIFT | Modifiers::ALT
+        ));
+    }
+
+    #[test]
+    fn configured_chords_silently_win_over_defaults() {
+        let (keymap, warnings) = keymap(&[("next", "space")]);
+
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(
+            keymap.action(&Key::Named(Named::Space), Modifiers::empty()),
+            Some(Action::Next)
+        );
+    }
+
+    #[test]
+    fn configured_chords_bound_twice_are_reported() {
+        let (keymap, warnings) = keymap(&[("next", "n"), ("prev", "n")]);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            keymap.action(&character("n"), Modifiers::empty()),
+            Some(Action::Next)
+        );
+    }
+
+    #[test]
+    fn unknown_actions_and_invalid_chords_are_reported() {
+        let (keymap, warnings) = keymap(&[("dance", "d"), ("next", "hyper+n")]);
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            keymap.action(&Key::Named(Named::ArrowRight), Modifiers::CTRL),
+            None
+        );
+    }
+}
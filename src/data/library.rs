@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Track {
+    pub uri: String,
+    pub name: String,
+    pub artist: String,
+    pub album: String,
+    pub duration_ms: u32,
+    pub added_at: Option<DateTime<Utc>>,
+    /// Album the track is on, empty for local files and anything not on an album.
+    #[serde(default)]
+    pub album_uri: String,
+    /// First of the artists, the one the artist name links to.
+    #[serde(default)]
+    pub artist_uri: String,
+    /// Cover art of the album, or the picture of an artist, show or playlist.
+    #[serde(default)]
+    pub images: Vec<Image>,
+}
+
+/// Spotify serves every picture in a few sizes, widest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Image {
+    pub url: String,
+    pub width: Option<u32>,
+}
+
+impl Track {
+    pub fn duration(&self) -> String {
+        if self.duration_ms == 0 {
+            return String::new();
+        }
+
+        format_ms(self.duration_ms)
+    }
+
+    pub fn is_local(&self) -> bool {
+        super::local::is_local(&self.uri)
+    }
+
+    pub fn date_added(&self) -> String {
+        self.added_at
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Spotify track `name` by "artist" on "album", shared by the tests of the data modules.
+#[cfg(test)]
+pub fn track(name: &str) -> Track {
+    Track {
+        uri: format!("spotify:track:{name}"),
+        name: name.to_string(),
+        artist: String::from("artist"),
+        album: String::from("album"),
+        duration_ms: 1000,
+        added_at: None,
+        album_uri: String::new(),
+        artist_uri: String::new(),
+        images: Vec::new(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Playlist {
+    pub id: String,
+    pub uri: String,
+    pub name: String,
+    pub owner: String,
+    pub snapshot_id: String,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumIter)]
+pub enum Section {
+    #[strum(serialize = "Liked Songs")]
+    LikedSongs,
+    Albums,
+    Artists,
+    Podcasts,
+    #[strum(serialize = "Local Files")]
+    Local,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Selection {
+    Section(Section),
+    Playlist(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum Column {
+    Title,
+    Artist,
+    Album,
+    Duration,
+    #[strum(serialize = "Date Added")]
+    DateAdded,
+}
+
+impl Column {
+    pub fn compare(&self, a: &Track, b: &Track) -> Ordering {
+        match self {
+            Column::Title => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            Column::Artist => a.artist.to_lowercase().cmp(&b.artist.to_lowercase()),
+            Column::Album => a.album.to_lowercase().cmp(&b.album.to_lowercase()),
+            Column::Duration => a.duration_ms.cmp(&b.duration_ms),
+            Column::DateAdded => a.added_at.cmp(&b.added_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub column: Column,
+    pub ascending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self {
+            column: Column::DateAdded,
+            ascending: false,
+        }
+    }
+}
+
+impl Sort {
+    pub fn compare(&self, a: &Track, b: &Track) -> Ordering {
+        let ordering = self.column.compare(a, b);
+
+        if self.ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+
+    pub fn apply(&self, tracks: &mut [Track]) {
+        tracks.sort_by(|a, b| self.compare(a, b));
+    }
+}
+
+/// Kind and id of a spotify uri, like `("album", "4aawyAB9vmqN3uQ7FjRGTy")`.
+pub fn parse_uri(uri: &str) -> Option<(&str, &str)> {
+    let mut parts = uri.strip_prefix("spotify:")?.splitn(2, ':');
+
+    Some((parts.next()?, parts.next()?))
+}
+
+/// `m:ss`, as durations are shown.
+pub fn format_ms(ms: u32) -> String {
+    let seconds = ms / 1000;
+
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Length of a whole album or playlist, like `1 h 12 min`.
+pub fn format_total(tracks: &[Track]) -> String {
+    let minutes = tracks
+        .iter()
+        .map(|track| track.duration_ms as u64)
+        .sum::<u64>()
+        / 60_000;
+
+    match minutes / 60 {
+        0 => format!("{minutes} min"),
+        hours => format!("{hours} h {} min", minutes % 60),
+    }
+}
@@ -0,0 +1,212 @@
+use super::library::Track;
+use crate::environment;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use symphonia::core::{
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::{MetadataOptions, StandardTagKey},
+    probe::Hint,
+};
+
+const FILE_NAME: &str = "local.json";
+const EXTENSIONS: [&str; 5] = ["flac", "mp3", "ogg", "oga", "opus"];
+/// Local tracks use `local:` followed by the absolute path of the file as their uri.
+const URI_PREFIX: &str = "local:";
+
+/// Tags of the audio files found in `[library] local_paths`, stored in the data directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Index {
+    files: HashMap<PathBuf, Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    modified: SystemTime,
+    track: Track,
+}
+
+impl Index {
+    pub fn load() -> Self {
+        fs::read_to_string(path())
+            .ok()
+            .and_then(|content| {
+                serde_json::from_str(&content)
+                    .inspect_err(|err| log::warn!("Cannot read the local files index: {err}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let parent = environment::data_dir();
+
+        if !parent.exists() {
+            fs::create_dir_all(&parent)?;
+        }
+
+        fs::write(path(), serde_json::to_string(self)?)
+    }
+
+    /// Walks `directories` again, only files that changed since the last scan have their tags read.
+    pub fn scan(&mut self, directories: &[PathBuf]) {
+        let mut files = HashMap::new();
+        let mut visited = HashSet::new();
+
+        for directory in directories {
+            for file in audio_files(directory, &mut visited) {
+                let Ok(modified) = fs::metadata(&file).and_then(|metadata| metadata.modified())
+                else {
+                    continue;
+                };
+
+                let entry = match self.files.remove(&file) {
+                    Some(entry) if entry.modified == modified => entry,
+                    _ => match read_tags(&file, modified) {
+                        Ok(track) => Entry { modified, track },
+                        Err(err) => {
+                            log::warn!("Cannot read the tags of {}: {err}", file.display());
+                            continue;
+                        }
+                    },
+                };
+
+                files.insert(file, entry);
+            }
+        }
+
+        self.files = files;
+    }
+
+    /// Every indexed track, sorted by path so albums stay together.
+    pub fn tracks(&self) -> Vec<Track> {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        files
+            .into_iter()
+            .map(|(_, entry)| entry.track.clone())
+            .collect()
+    }
+}
+
+/// Path of the file behind a local track uri.
+pub fn file(uri: &str) -> Option<&Path> {
+    uri.strip_prefix(URI_PREFIX).map(Path::new)
+}
+
+pub fn is_local(uri: &str) -> bool {
+    uri.starts_with(URI_PREFIX)
+}
+
+/// Case insensitive match on the title, artist or album.
+pub fn search<'a>(tracks: &'a [Track], query: &str) -> impl Iterator<Item = &'a Track> {
+    let query = query.trim().to_lowercase();
+
+    tracks.iter().filter(move |track| {
+        [&track.name, &track.artist, &track.album]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&query))
+    })
+}
+
+fn path() -> PathBuf {
+    environment::data_dir().join(FILE_NAME)
+}
+
+/// Symlinks are followed, `visited` keeps a link back up the tree from looping forever.
+fn audio_files(directory: &Path, visited: &mut HashSet<PathBuf>) -> Vec<PathBuf> {
+    let (Ok(canonical), Ok(entries)) = (directory.canonicalize(), fs::read_dir(directory)) else {
+        log::warn!("Cannot read the local files in {}", directory.display());
+        return Vec::new();
+    };
+
+    if !visited.insert(canonical) {
+        return Vec::new();
+    }
+
+    let mut files = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            files.extend(audio_files(&path, visited));
+        } else if is_audio(&path) {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+fn read_tags(file: &Path, modified: SystemTime) -> anyhow::Result<Track> {
+    let source = MediaSourceStream::new(Box::new(File::open(file)?), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = file.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let mut probed = symphonia::default::get_probe().format(
+        &hint,
+        source,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+
+    // Tags may come before the stream, as ID3 does, or inside of it
+    let mut revision = probed
+        .metadata
+        .get()
+        .and_then(|metadata| metadata.current().cloned());
+    if let Some(current) = probed.format.metadata().current() {
+        revision = Some(current.clone());
+    }
+
+    let tag = |key: StandardTagKey| {
+        revision
+            .as_ref()
+            .and_then(|revision| revision.tags().iter().find(|tag| tag.std_key == Some(key)))
+            .map(|tag| tag.value.to_string())
+    };
+
+    let duration_ms = probed
+        .format
+        .default_track()
+        .and_then(|track| {
+            let params = &track.codec_params;
+            let time = params.time_base?.calc_time(params.n_frames?);
+
+            Some(time.seconds * 1000 + (time.frac * 1000.0) as u64)
+        })
+        .unwrap_or_default();
+
+    let name = tag(StandardTagKey::TrackTitle).unwrap_or_else(|| {
+        file.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+
+    Ok(Track {
+        uri: format!("{URI_PREFIX}{}", file.display()),
+        name,
+        artist: tag(StandardTagKey::Artist).unwrap_or_default(),
+        album: tag(StandardTagKey::Album).unwrap_or_default(),
+        duration_ms: duration_ms as u32,
+        added_at: Some(DateTime::<Utc>::from(modified)),
+        album_uri: String::new(),
+        artist_uri: String::new(),
+        images: Vec::new(),
+    })
+}
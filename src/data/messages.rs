@@ -1,16 +1,76 @@
-use super::log::Record;
-use crate::context::config::Config;
+use super::{
+    cover::Cover,
+    detail::{AlbumDetails, ArtistDetails, Failure, PlaylistDetails},
+    library::{Column, Playlist, Section, Selection, Track},
+    log::Record,
+    playback::Modes,
+    search::{Group, Navigation, Results},
+    storage::{Kind, Pins, Usage},
+    theme::Scheme,
+    window_state::Change,
+};
+use crate::{
+    context::config::{Config, Equalizer},
+    screen::Screen,
+    spotify::{local::LocalEvent, Service},
+};
+use iced::{
+    keyboard::{Key, Modifiers},
+    mouse::ScrollDelta,
+    widget::scrollable::Viewport,
+    window,
+};
+use librespot::playback::player::PlayerEvent;
+use std::time::SystemTime;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     OxifyMessage(OxifyMessage),
     WelcomeMessage(WelcomeMessage),
+    SidebarMessage(SidebarMessage),
+    HomeMessage(HomeMessage),
+    LibraryMessage(LibraryMessage),
+    SearchMessage(SearchMessage),
+    AlbumMessage(AlbumMessage),
+    ArtistMessage(ArtistMessage),
+    PlaylistMessage(PlaylistMessage),
+    QueueMessage(QueueMessage),
+    SettingsMessage(SettingsMessage),
+    PaletteMessage(PaletteMessage),
+    LogsMessage(LogsMessage),
 }
 
 #[derive(Debug, Clone)]
 pub enum OxifyMessage {
     Logging(Vec<Record>),
     ConfigReloaded(Config),
+    DesktopScheme(Option<Scheme>),
+    ThemeTick,
+    /// Why the fonts could not be loaded, if they could not.
+    FontsLoaded(Option<String>),
+    ServiceConnected(Service),
+    Offline,
+    /// Checks the session is still alive, or tries to connect again while offline.
+    ConnectionTick,
+    Reconnected(Service),
+    ReconnectFailed,
+    Player(PlayerEvent),
+    Local(LocalEvent),
+    LocalScanned(Vec<Track>),
+    CoverLoaded(Cover),
+    /// Looks for playback modes changed from the command line.
+    ModesTick,
+    /// Modes written to the file at the given time, by someone else.
+    ModesChanged(SystemTime, Modes),
+    SaveSession,
+    WindowClosed(window::Id),
+    WindowChanged(window::Id, Change),
+    /// Change of the main window, and whether it is maximized after it.
+    MainWindowChanged(Change, bool),
+    VisualizerTick,
+    /// Redraws the progress of the current track.
+    ProgressTick,
+    KeyPressed(Key, Modifiers),
 }
 
 #[derive(Debug, Clone)]
@@ -20,3 +80,147 @@ pub enum WelcomeMessage {
     ReloadConfig,
     OpenWebsite,
 }
+
+#[derive(Debug, Clone)]
+pub enum SidebarMessage {
+    Open(Screen),
+    Back,
+    Forward,
+    ToggleQueue,
+}
+
+#[derive(Debug, Clone)]
+pub enum HomeMessage {
+    /// Index in the history of the queue.
+    Replay(usize),
+    Open(Selection),
+    OpenUri(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum LibraryMessage {
+    PlaylistsFetched(Vec<Playlist>),
+    SectionFetched(Section, Vec<Track>),
+    PlaylistFetched(String, Vec<Track>),
+    Select(Selection),
+    SortBy(Column),
+    Scrolled(Viewport),
+    Play(usize),
+    Enqueue(usize),
+    /// Opens the page of the followed artist at the index.
+    Open(usize),
+    OpenUri(String),
+    Navigate(Navigation),
+}
+
+#[derive(Debug, Clone)]
+pub enum SearchMessage {
+    QueryChanged(String),
+    Debounced(u64),
+    ResultsFetched(String, Vec<(Group, Results)>),
+    /// The search for the query failed.
+    Failed(String),
+    MoreFetched(String, Group, Results),
+    LoadMore(Group),
+    Select(Group, usize),
+    Play(Group, usize),
+    PlayNext(Group, usize),
+    Queue(Group, usize),
+    Open(Group, usize),
+    OpenUri(String),
+    Navigate(Navigation),
+}
+
+/// Detail pages are keyed by the id of what they show.
+#[derive(Debug, Clone)]
+pub enum AlbumMessage {
+    Fetched(String, AlbumDetails),
+    Failed(String, Failure),
+    Retry(String),
+    Play(String, usize),
+    OpenUri(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum ArtistMessage {
+    Fetched(String, ArtistDetails),
+    Failed(String, Failure),
+    Retry(String),
+    /// Index in the top tracks.
+    Play(String, usize),
+    ToggleFollow(String),
+    Followed(String, bool),
+    OpenUri(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum PlaylistMessage {
+    Fetched(String, PlaylistDetails),
+    Failed(String, Failure),
+    Retry(String),
+    FilterChanged(String, String),
+    SortBy(String, Column),
+    Scrolled(Viewport),
+    /// Index in the tracks as shown, filtered and sorted.
+    Play(String, usize),
+    OpenUri(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum QueueMessage {
+    DragStart(usize),
+    DragOver(usize),
+    Drop(usize),
+    /// The mouse was released somewhere, ends a drag that missed every row.
+    DragEnd,
+    Remove(usize),
+    Clear,
+    Replay(usize),
+    ToggleHistory,
+    TogglePlayback,
+    Next,
+    Previous,
+    ToggleMiniPlayer,
+    CycleShuffle,
+    CycleRepeat,
+    CycleVisualizer,
+    /// Percentage
+    SetVolume(f32),
+    ScrollVolume(ScrollDelta),
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+    ToggleNormalisation(bool),
+    ToggleVisualizer(bool),
+    /// Applied live while a slider is being dragged, saved on release.
+    PreviewEqualizer(Equalizer),
+    SetEqualizer(Equalizer),
+    SaveEqualizer,
+    CacheUsageFetched(Vec<(Kind, Usage)>),
+    ClearCache(Kind),
+    CacheLimitChanged(String),
+    SetCacheLimit,
+    PinInputChanged(String),
+    Pin,
+    Unpin(String),
+    PinsChanged(Pins),
+    Back,
+}
+
+#[derive(Debug, Clone)]
+pub enum PaletteMessage {
+    QueryChanged(String),
+    Navigate(Navigation),
+    /// Runs the command under the cursor.
+    Submit,
+    Run(usize),
+    Close,
+    /// Key the query input took, which may still be bound to close the palette.
+    KeyPressed(Key, Modifiers),
+}
+
+#[derive(Debug, Clone)]
+pub enum LogsMessage {
+    Clear,
+}
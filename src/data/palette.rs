@@ -0,0 +1,193 @@
+use super::{keybinding::Action, library::Section};
+use crate::{environment, screen::Screen};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+const FILE_NAME: &str = "recent_commands.json";
+const RECENT_LIMIT: usize = 10;
+
+/// Everything the command palette can run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Action(Action),
+    Open(Screen),
+    ToggleMiniPlayer,
+    OpenConfigDir,
+    OpenWebsite,
+    Theme(String),
+    Section(Section),
+    Playlist { id: String, name: String },
+}
+
+impl Command {
+    pub fn label(&self) -> String {
+        match self {
+            Command::Action(action) => action.label().to_string(),
+            Command::Open(screen) => format!("Go to {}", screen.to_string().to_lowercase()),
+            Command::ToggleMiniPlayer => "Toggle mini player".to_string(),
+            Command::OpenConfigDir => "Open config directory".to_string(),
+            Command::OpenWebsite => "Open website".to_string(),
+            Command::Theme(name) => format!("Theme: {name}"),
+            Command::Section(section) => format!("Library: {section}"),
+            Command::Playlist { name, .. } => format!("Playlist: {name}"),
+        }
+    }
+
+    /// Stays the same across runs, playlists are told apart by id since they can be renamed.
+    fn key(&self) -> String {
+        match self {
+            Command::Action(action) => format!("action:{action}"),
+            Command::Open(screen) => format!("open:{screen}"),
+            Command::ToggleMiniPlayer => "toggle_mini_player".to_string(),
+            Command::OpenConfigDir => "open_config_dir".to_string(),
+            Command::OpenWebsite => "open_website".to_string(),
+            Command::Theme(name) => format!("theme:{name}"),
+            Command::Section(section) => format!("section:{section}"),
+            Command::Playlist { id, .. } => format!("playlist:{id}"),
+        }
+    }
+}
+
+/// Commands last run from the palette, the most recent first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Recent(Vec<String>);
+
+impl Recent {
+    pub fn load() -> Self {
+        let Ok(content) = fs::read_to_string(path()) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&content).unwrap_or_else(|err| {
+            log::warn!("Cannot read the recent commands: {err}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let parent = environment::data_dir();
+
+        if !parent.exists() {
+            fs::create_dir_all(&parent)?;
+        }
+
+        fs::write(path(), serde_json::to_string(self)?)
+    }
+
+    /// Moves `command` to the front, forgetting the oldest ones past the limit.
+    pub fn push(&mut self, command: &Command) {
+        let key = command.key();
+
+        self.0.retain(|recent| *recent != key);
+        self.0.insert(0, key);
+        self.0.truncate(RECENT_LIMIT);
+    }
+
+    /// How many other commands ran since `command`, if it ran lately.
+    pub fn age(&self, command: &Command) -> Option<usize> {
+        let key = command.key();
+
+        self.0.iter().position(|recent| *recent == key)
+    }
+}
+
+/// Indices of the `commands` matching `query`, best first. Recent commands win ties,
+/// and an empty query lists them before the rest.
+pub fn rank(commands: &[Command], query: &str, recent: &Recent) -> Vec<usize> {
+    let mut ranked: Vec<(i32, usize, usize)> = commands
+        .iter()
+        .enumerate()
+        .filter_map(|(index, command)| {
+            let score = score(query, &command.label())?;
+            let age = recent.age(command).unwrap_or(usize::MAX);
+
+            Some((score, age, index))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    ranked.into_iter().map(|(_, _, index)| index).collect()
+}
+
+/// Fuzzy match of `query` in `label`, ignoring case and spaces: every character has to
+/// appear in order. Consecutive characters and word starts score higher, and so do
+/// shorter labels, being closer to the query.
+fn score(query: &str, label: &str) -> Option<i32> {
+    if query.trim().is_empty() {
+        return Some(0);
+    }
+
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..label.len()).find(|&index| label[index] == wanted)?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !label[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        previous = Some(found);
+        next = found + 1;
+    }
+
+    Some(score * 16 - label.len() as i32)
+}
+
+fn path() -> PathBuf {
+    environment::data_dir().join(FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_character_has_to_appear_in_order() {
+        assert!(score("nt", "Next track").is_some());
+        assert!(score("kcart", "Next track").is_none());
+        assert!(score("xyz", "Next track").is_none());
+    }
+
+    #[test]
+    fn case_and_spaces_are_ignored() {
+        assert_eq!(score("N T", "Next track"), score("nt", "Next track"));
+        assert_eq!(score("  ", "Next track"), Some(0));
+    }
+
+    #[test]
+    fn consecutive_characters_score_higher() {
+        assert!(score("abc", "abcxyz") > score("abc", "axbycz"));
+    }
+
+    #[test]
+    fn word_starts_score_higher() {
+        assert!(score("zi", "zoom in") > score("zi", "zoomxin"));
+    }
+
+    #[test]
+    fn shorter_labels_score_higher() {
+        assert!(score("zoom", "Zoom in") > score("zoom", "Zoom out"));
+    }
+
+    #[test]
+    fn recent_commands_win_ties() {
+        let commands = vec![
+            Command::OpenConfigDir,
+            Command::OpenWebsite,
+            Command::ToggleMiniPlayer,
+        ];
+        let mut recent = Recent::default();
+        recent.push(&Command::OpenWebsite);
+
+        assert_eq!(rank(&commands, "", &recent), vec![1, 0, 2]);
+        assert_eq!(rank(&commands, "open", &recent), vec![1, 0]);
+    }
+}
@@ -0,0 +1,347 @@
+use super::library::Track;
+use crate::environment;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, io, path::PathBuf, str::FromStr, time::SystemTime};
+
+const FILE_NAME: &str = "playback.json";
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Shuffle {
+    #[default]
+    Off,
+    On,
+    /// Shuffles but avoids playing the same artist back to back whenever possible.
+    Smart,
+}
+
+impl Shuffle {
+    pub fn cycle(self) -> Self {
+        match self {
+            Shuffle::Off => Shuffle::On,
+            Shuffle::On => Shuffle::Smart,
+            Shuffle::Smart => Shuffle::Off,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Repeat {
+    #[default]
+    Off,
+    Context,
+    Track,
+}
+
+impl Repeat {
+    pub fn cycle(self) -> Self {
+        match self {
+            Repeat::Off => Repeat::Context,
+            Repeat::Context => Repeat::Track,
+            Repeat::Track => Repeat::Off,
+        }
+    }
+}
+
+/// Shuffle and repeat state, persisted in the data dir so it survives restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modes {
+    #[serde(default)]
+    pub shuffle: Shuffle,
+    #[serde(default)]
+    pub repeat: Repeat,
+    #[serde(default = "random_seed")]
+    pub seed: u64,
+}
+
+impl Default for Modes {
+    fn default() -> Self {
+        Self {
+            shuffle: Shuffle::default(),
+            repeat: Repeat::default(),
+            seed: random_seed(),
+        }
+    }
+}
+
+impl Modes {
+    pub fn load() -> Self {
+        let Ok(content) = fs::read_to_string(path()) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&content).unwrap_or_else(|err| {
+            log::warn!("Cannot read playback modes: {err}. Using defaults.");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let parent = environment::data_dir();
+
+        if !parent.exists() {
+            fs::create_dir_all(&parent)?;
+        }
+
+        fs::write(path(), serde_json::to_string_pretty(self)?)
+    }
+
+    /// When the file was last written, by this or another instance or from the command line.
+    pub fn modified() -> Option<SystemTime> {
+        fs::metadata(path())
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), strum::ParseError> {
+        match key {
+            "shuffle" => self.shuffle = Shuffle::from_str(value)?,
+            "repeat" => self.repeat = Repeat::from_str(value)?,
+            _ => return Err(strum::ParseError::VariantNotFound),
+        }
+
+        Ok(())
+    }
+}
+
+fn path() -> PathBuf {
+    environment::data_dir().join(FILE_NAME)
+}
+
+fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, RandomState};
+
+    RandomState::new().hash_one(std::time::SystemTime::now())
+}
+
+/// Returns a play order for `tracks` that only depends on the tracks and the seed,
+/// so the same seed always reproduces the same shuffle.
+///
+/// `first` is kept at the start of the order, it is used to keep the playing track
+/// in place when shuffle is turned on.
+pub fn shuffle(tracks: &[Track], mode: Shuffle, seed: u64, first: Option<usize>) -> Vec<usize> {
+    let first = first.filter(|first| *first < tracks.len());
+    let mut order: Vec<usize> = (0..tracks.len()).filter(|i| Some(*i) != first).collect();
+
+    if mode != Shuffle::Off {
+        let mut rng = SplitMix64(seed);
+
+        // Fisher-Yates
+        for i in (1..order.len()).rev() {
+            let j = (rng.next() % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+    }
+
+    if mode == Shuffle::Smart {
+        let previous = first.map(|first| tracks[first].artist.as_str());
+
+        order = spread_artists(tracks, order, previous);
+    }
+
+    if let Some(first) = first {
+        order.insert(0, first);
+    }
+
+    order
+}
+
+/// Rebuilds the order so that two consecutive tracks never share an artist, unless
+/// there is no other choice left. Tracks keep their shuffled order otherwise.
+fn spread_artists<'a>(
+    tracks: &'a [Track],
+    mut remaining: Vec<usize>,
+    mut previous: Option<&'a str>,
+) -> Vec<usize> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for index in &remaining {
+        *counts.entry(tracks[*index].artist.as_str()).or_default() += 1;
+    }
+
+    let most = counts.values().copied().max().unwrap_or_default();
+    let mut spread = Vec::with_capacity(remaining.len());
+
+    while !remaining.is_empty() {
+        let left = remaining.len();
+
+        // An artist holding more than half of what is left has to play now,
+        // later there would not be enough other tracks to keep it apart
+        let forced = (most * 2 > left)
+            .then(|| {
+                counts.iter().find_map(|(artist, count)| {
+                    (Some(*artist) != previous && count * 2 > left).then_some(*artist)
+                })
+            })
+            .flatten();
+
+        let position = match forced {
+            Some(forced) => remaining
+                .iter()
+                .position(|index| tracks[*index].artist == forced),
+            None => remaining
+                .iter()
+                .position(|index| Some(tracks[*index].artist.as_str()) != previous),
+        };
+
+        let index = remaining.remove(position.unwrap_or_default());
+        let artist = tracks[index].artist.as_str();
+
+        if let Some(count) = counts.get_mut(artist) {
+            *count -= 1;
+        }
+        previous = Some(artist);
+        spread.push(index);
+    }
+
+    spread
+}
+
+/// Small, stable PRNG. The shuffle must not change between releases of a
+/// dependency, so it is implemented here instead of relying on `rand`.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+}
+
+pub fn next_seed(seed: u64) -> u64 {
+    SplitMix64(seed).next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::library;
+
+    fn track(name: &str, artist: &str) -> Track {
+        Track {
+            artist: artist.to_string(),
+            ..library::track(name)
+        }
+    }
+
+    fn tracks(count: usize) -> Vec<Track> {
+        (0..count)
+            .map(|i| track(&i.to_string(), &format!("artist {i}")))
+            .collect()
+    }
+
+    fn is_permutation(order: &[usize], len: usize) -> bool {
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+
+        sorted == (0..len).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_order() {
+        let tracks = tracks(50);
+
+        for mode in [Shuffle::On, Shuffle::Smart] {
+            let order = shuffle(&tracks, mode, 7, None);
+
+            assert!(is_permutation(&order, tracks.len()));
+            assert_eq!(order, shuffle(&tracks, mode, 7, None));
+            assert_ne!(order, shuffle(&tracks, mode, next_seed(7), None));
+        }
+    }
+
+    #[test]
+    fn off_keeps_the_original_order() {
+        let tracks = tracks(5);
+
+        assert_eq!(shuffle(&tracks, Shuffle::Off, 7, None), [0, 1, 2, 3, 4]);
+        assert_eq!(shuffle(&tracks, Shuffle::Off, 7, Some(3)), [3, 0, 1, 2, 4]);
+    }
+
+    #[test]
+    fn first_stays_at_the_start() {
+        let tracks = tracks(20);
+
+        for mode in [Shuffle::Off, Shuffle::On, Shuffle::Smart] {
+            for seed in 0..10 {
+                let order = shuffle(&tracks, mode, seed, Some(13));
+
+                assert_eq!(order[0], 13);
+                assert!(is_permutation(&order, tracks.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_first_is_ignored() {
+        let tracks = tracks(3);
+
+        assert!(is_permutation(
+            &shuffle(&tracks, Shuffle::On, 7, Some(3)),
+            3
+        ));
+    }
+
+    #[test]
+    fn smart_avoids_consecutive_artists() {
+        // Three artists with four tracks each can always be spread out
+        let tracks: Vec<Track> = (0..12)
+            .map(|i| track(&i.to_string(), &format!("artist {}", i % 3)))
+            .collect();
+
+        for seed in 0..20 {
+            let first = (seed % 2 == 0).then_some(seed as usize % tracks.len());
+            let order = shuffle(&tracks, Shuffle::Smart, seed, first);
+
+            assert!(is_permutation(&order, tracks.len()));
+            assert!(
+                order
+                    .windows(2)
+                    .all(|pair| tracks[pair[0]].artist != tracks[pair[1]].artist),
+                "seed {seed} plays an artist twice in a row: {order:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn smart_falls_back_when_one_artist_dominates() {
+        let mut tracks: Vec<Track> = (0..5).map(|i| track(&i.to_string(), "solo")).collect();
+        tracks.push(track("5", "other"));
+
+        let order = shuffle(&tracks, Shuffle::Smart, 7, None);
+
+        assert!(is_permutation(&order, tracks.len()));
+        // The only other track at least breaks one of the runs
+        assert_ne!(tracks[order[0]].artist, tracks[order[1]].artist);
+    }
+}
@@ -0,0 +1,401 @@
+use super::{
+    library::Track,
+    playback::{self, Modes, Repeat, Shuffle},
+};
+use serde::{Deserialize, Serialize};
+
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Queue {
+    current: Option<Track>,
+    /// Tracks explicitly queued by the user, they always play before the context.
+    upcoming: Vec<Track>,
+    context: Context,
+    history: Vec<Track>,
+    modes: Modes,
+}
+
+/// The list a track was started from (a playlist, an album, liked songs...).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Context {
+    pub uri: Option<String>,
+    tracks: Vec<Track>,
+    order: Vec<usize>,
+    position: Option<usize>,
+}
+
+impl Context {
+    fn upcoming(&self) -> impl Iterator<Item = &Track> {
+        let start = self.position.map_or(0, |position| position + 1);
+
+        self.order[start.min(self.order.len())..]
+            .iter()
+            .map(|index| &self.tracks[*index])
+    }
+
+    fn reorder(&mut self, modes: &Modes) {
+        let current = self.position.map(|position| self.order[position]);
+
+        self.order = playback::shuffle(&self.tracks, modes.shuffle, modes.seed, current);
+        self.position = current.map(|current| {
+            self.order
+                .iter()
+                .position(|index| *index == current)
+                .unwrap_or_default()
+        });
+    }
+}
+
+impl Queue {
+    pub fn new(modes: Modes) -> Self {
+        Self {
+            modes,
+            ..Default::default()
+        }
+    }
+
+    pub fn current(&self) -> Option<&Track> {
+        self.current.as_ref()
+    }
+
+    pub fn upcoming(&self) -> &[Track] {
+        &self.upcoming
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    pub fn context_upcoming(&self) -> impl Iterator<Item = &Track> {
+        self.context.upcoming()
+    }
+
+    pub fn history(&self) -> &[Track] {
+        &self.history
+    }
+
+    pub fn modes(&self) -> Modes {
+        self.modes
+    }
+
+    pub fn shuffle(&self) -> Shuffle {
+        self.modes.shuffle
+    }
+
+    pub fn repeat(&self) -> Repeat {
+        self.modes.repeat
+    }
+
+    pub fn set_shuffle(&mut self, shuffle: Shuffle) {
+        self.modes.shuffle = shuffle;
+        self.context.reorder(&self.modes);
+    }
+
+    pub fn set_repeat(&mut self, repeat: Repeat) {
+        self.modes.repeat = repeat;
+    }
+
+    /// Replaces every mode at once, the context is only reshuffled if the shuffle changed.
+    pub fn set_modes(&mut self, modes: Modes) {
+        let reshuffle = modes.shuffle != self.modes.shuffle || modes.seed != self.modes.seed;
+
+        self.modes = modes;
+
+        if reshuffle {
+            self.context.reorder(&self.modes);
+        }
+    }
+
+    /// Track that will be loaded once the current one ends.
+    pub fn peek(&self) -> Option<&Track> {
+        if self.modes.repeat == Repeat::Track {
+            return self.current.as_ref();
+        }
+
+        self.upcoming.first().or_else(|| {
+            self.context.upcoming().next().or_else(|| {
+                // Wrapping around keeps the order, it is only reshuffled when
+                // the context actually starts again
+                (self.modes.repeat == Repeat::Context && self.modes.shuffle == Shuffle::Off)
+                    .then(|| self.context.order.first())
+                    .flatten()
+                    .map(|index| &self.context.tracks[*index])
+            })
+        })
+    }
+
+    /// Replaces the context and starts playing its track at `index`.
+    pub fn play_context(
+        &mut self,
+        uri: Option<String>,
+        tracks: Vec<Track>,
+        index: usize,
+    ) -> Option<Track> {
+        let track = tracks.get(index).cloned()?;
+
+        self.context = Context {
+            uri,
+            order: playback::shuffle(&tracks, self.modes.shuffle, self.modes.seed, Some(index)),
+            tracks,
+            position: Some(0),
+        };
+        self.play_now(track.clone());
+
+        Some(track)
+    }
+
+    pub fn play_now(&mut self, track: Track) {
+        if let Some(current) = self.current.replace(track) {
+            self.push_history(current);
+        }
+    }
+
+    pub fn play_next(&mut self, track: Track) {
+        self.upcoming.insert(0, track);
+    }
+
+    pub fn enqueue(&mut self, track: Track) {
+        self.upcoming.push(track);
+    }
+
+    pub fn reorder(&mut self, from: usize, to: usize) {
+        if from >= self.upcoming.len() || from == to {
+            return;
+        }
+
+        let track = self.upcoming.remove(from);
+        let to = to.min(self.upcoming.len());
+
+        self.upcoming.insert(to, track);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Track> {
+        (index < self.upcoming.len()).then(|| self.upcoming.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.upcoming.clear();
+    }
+
+    /// Skips to the upcoming track at `index`, dropping everything queued before it.
+    pub fn skip_to(&mut self, index: usize) -> Option<Track> {
+        if index >= self.upcoming.len() {
+            return None;
+        }
+
+        self.upcoming.drain(..index);
+
+        self.next()
+    }
+
+    /// Called when a track ends on its own, honours the repeat mode.
+    pub fn advance(&mut self) -> Option<Track> {
+        if self.modes.repeat == Repeat::Track {
+            return self.current.clone();
+        }
+
+        self.next()
+    }
+
+    /// Moves the current track to the history and makes the next one current.
+    pub fn next(&mut self) -> Option<Track> {
+        let next = if self.upcoming.is_empty() {
+            self.next_in_context()
+        } else {
+            Some(self.upcoming.remove(0))
+        };
+
+        match next {
+            Some(next) => {
+                self.play_now(next.clone());
+                Some(next)
+            }
+            None => {
+                if let Some(current) = self.current.take() {
+                    self.push_history(current);
+                }
+                None
+            }
+        }
+    }
+
+    /// Puts the current track back at the front of the queue and returns the last played one.
+    pub fn previous(&mut self) -> Option<Track> {
+        let previous = self.history.pop()?;
+
+        if let Some(current) = self.current.replace(previous.clone()) {
+            self.upcoming.insert(0, current);
+        }
+
+        Some(previous)
+    }
+
+    fn next_in_context(&mut self) -> Option<Track> {
+        let context = &mut self.context;
+        let next = context.position.map_or(0, |position| position + 1);
+
+        if next < context.order.len() {
+            context.position = Some(next);
+
+            return Some(context.tracks[context.order[next]].clone());
+        }
+
+        if self.modes.repeat != Repeat::Context || context.tracks.is_empty() {
+            return None;
+        }
+
+        if self.modes.shuffle != Shuffle::Off {
+            self.modes.seed = playback::next_seed(self.modes.seed);
+            context.order =
+                playback::shuffle(&context.tracks, self.modes.shuffle, self.modes.seed, None);
+        }
+        context.position = Some(0);
+
+        Some(context.tracks[context.order[0]].clone())
+    }
+
+    fn push_history(&mut self, track: Track) {
+        self.history.push(track);
+
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::library::track;
+
+    fn modes(shuffle: Shuffle, repeat: Repeat) -> Modes {
+        Modes {
+            shuffle,
+            repeat,
+            seed: 42,
+        }
+    }
+
+    fn names(tracks: &[Track]) -> Vec<&str> {
+        tracks.iter().map(|track| track.name.as_str()).collect()
+    }
+
+    fn queued(names: &[&str]) -> Queue {
+        let mut queue = Queue::new(modes(Shuffle::Off, Repeat::Off));
+        names.iter().for_each(|name| queue.enqueue(track(name)));
+
+        queue
+    }
+
+    #[test]
+    fn reorder_moves_an_upcoming_track() {
+        let mut queue = queued(&["a", "b", "c", "d"]);
+
+        queue.reorder(0, 2);
+        assert_eq!(names(queue.upcoming()), ["b", "c", "a", "d"]);
+
+        queue.reorder(3, 0);
+        assert_eq!(names(queue.upcoming()), ["d", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn reorder_clamps_the_target_and_ignores_unknown_tracks() {
+        let mut queue = queued(&["a", "b", "c"]);
+
+        queue.reorder(0, 10);
+        assert_eq!(names(queue.upcoming()), ["b", "c", "a"]);
+
+        queue.reorder(5, 0);
+        assert_eq!(names(queue.upcoming()), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn remove_only_touches_existing_tracks() {
+        let mut queue = queued(&["a", "b", "c"]);
+
+        assert_eq!(
+            queue.remove(1).map(|track| track.name),
+            Some(String::from("b"))
+        );
+        assert_eq!(queue.remove(2), None);
+        assert_eq!(names(queue.upcoming()), ["a", "c"]);
+    }
+
+    #[test]
+    fn next_and_previous_walk_the_history() {
+        let mut queue = queued(&["a", "b", "c"]);
+
+        queue.next();
+        queue.next();
+        assert_eq!(queue.current().map(|track| track.name.as_str()), Some("b"));
+        assert_eq!(names(queue.history()), ["a"]);
+
+        assert_eq!(
+            queue.previous().map(|track| track.name),
+            Some(String::from("a"))
+        );
+        assert!(queue.history().is_empty());
+        assert_eq!(names(queue.upcoming()), ["b", "c"]);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let names: Vec<String> = (0..HISTORY_LIMIT + 10).map(|i| i.to_string()).collect();
+        let mut queue = Queue::new(modes(Shuffle::Off, Repeat::Off));
+        names.iter().for_each(|name| queue.enqueue(track(name)));
+
+        while queue.next().is_some() {}
+
+        assert_eq!(queue.history().len(), HISTORY_LIMIT);
+        assert_eq!(queue.history()[0].name, "10");
+    }
+
+    #[test]
+    fn peek_prefers_the_queue_over_the_context() {
+        let mut queue = Queue::new(modes(Shuffle::Off, Repeat::Off));
+        let tracks = vec![track("a"), track("b"), track("c")];
+
+        queue.play_context(None, tracks, 0);
+        assert_eq!(queue.peek().map(|track| track.name.as_str()), Some("b"));
+
+        queue.enqueue(track("queued"));
+        assert_eq!(
+            queue.peek().map(|track| track.name.as_str()),
+            Some("queued")
+        );
+    }
+
+    #[test]
+    fn peek_follows_the_repeat_mode() {
+        let tracks = vec![track("a"), track("b")];
+
+        let mut queue = Queue::new(modes(Shuffle::Off, Repeat::Off));
+        queue.play_context(None, tracks, 0);
+        queue.next();
+        assert_eq!(queue.peek(), None);
+
+        queue.set_repeat(Repeat::Context);
+        assert_eq!(queue.peek().map(|track| track.name.as_str()), Some("a"));
+
+        queue.set_repeat(Repeat::Track);
+        assert_eq!(queue.peek().map(|track| track.name.as_str()), Some("b"));
+        assert_eq!(
+            queue.advance().map(|track| track.name),
+            Some(String::from("b"))
+        );
+    }
+
+    #[test]
+    fn peek_matches_the_track_played_next() {
+        let mut queue = Queue::new(modes(Shuffle::On, Repeat::Off));
+        let tracks: Vec<Track> = (0..10).map(|i| track(&i.to_string())).collect();
+
+        queue.play_context(None, tracks, 3);
+
+        while let Some(peeked) = queue.peek().cloned() {
+            assert_eq!(queue.next(), Some(peeked));
+        }
+        assert_eq!(queue.next(), None);
+    }
+}
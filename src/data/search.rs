@@ -0,0 +1,55 @@
+use super::library::Track;
+
+pub const PAGE_SIZE: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumIter)]
+pub enum Group {
+    Tracks,
+    Albums,
+    Artists,
+    Playlists,
+    Shows,
+    Episodes,
+}
+
+impl Group {
+    pub fn api_type(&self) -> &'static str {
+        match self {
+            Group::Tracks => "track",
+            Group::Albums => "album",
+            Group::Artists => "artist",
+            Group::Playlists => "playlist",
+            Group::Shows => "show",
+            Group::Episodes => "episode",
+        }
+    }
+
+    pub fn is_playable(&self) -> bool {
+        matches!(self, Group::Tracks | Group::Episodes)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Results {
+    pub items: Vec<Track>,
+    pub total: u32,
+}
+
+impl Results {
+    pub fn has_more(&self) -> bool {
+        (self.items.len() as u32) < self.total
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    Up,
+    Down,
+    First,
+    Last,
+    NextGroup,
+    PreviousGroup,
+    Play,
+    Queue,
+    Open,
+}
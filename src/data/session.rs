@@ -0,0 +1,78 @@
+use super::queue::Queue;
+use crate::environment;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf, time::Instant};
+
+const FILE_NAME: &str = "session.json";
+
+/// Everything needed to resume playback where it was left on the previous run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub queue: Queue,
+    pub position_ms: u32,
+    pub volume: u16,
+}
+
+impl Session {
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(path()).ok()?;
+
+        serde_json::from_str(&content)
+            .inspect_err(|err| log::warn!("Cannot read the previous session: {err}"))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let parent = environment::data_dir();
+
+        if !parent.exists() {
+            fs::create_dir_all(&parent)?;
+        }
+
+        // Written aside then renamed, a crash mid-write leaves the previous session intact
+        let path = path();
+        let temporary = path.with_extension("json.tmp");
+
+        fs::write(&temporary, serde_json::to_string(self)?)?;
+        fs::rename(temporary, path)
+    }
+}
+
+fn path() -> PathBuf {
+    environment::data_dir().join(FILE_NAME)
+}
+
+/// Playback position as reported by the player events, extrapolated while playing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Position {
+    position_ms: u32,
+    playing_since: Option<Instant>,
+}
+
+impl Position {
+    pub fn playing(position_ms: u32) -> Self {
+        Self {
+            position_ms,
+            playing_since: Some(Instant::now()),
+        }
+    }
+
+    pub fn paused(position_ms: u32) -> Self {
+        Self {
+            position_ms,
+            playing_since: None,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing_since.is_some()
+    }
+
+    pub fn current_ms(&self) -> u32 {
+        let elapsed = self
+            .playing_since
+            .map_or(0, |since| since.elapsed().as_millis() as u32);
+
+        self.position_ms.saturating_add(elapsed)
+    }
+}
@@ -0,0 +1,412 @@
+use super::cache::{self, Cache};
+use crate::environment;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+const PINS_FILE_NAME: &str = "pins.json";
+const CREDENTIALS_FILE_NAME: &str = "credentials.json";
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// What `oxify cache clear` and the settings can clear, each one on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum Kind {
+    Audio,
+    Metadata,
+    Credentials,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Usage {
+    pub location: PathBuf,
+    pub size: u64,
+    pub items: usize,
+}
+
+impl Kind {
+    pub fn location(&self) -> PathBuf {
+        match self {
+            Kind::Audio => environment::audio_cache_dir(),
+            Kind::Metadata => cache::path(),
+            Kind::Credentials => environment::cache_dir().join(CREDENTIALS_FILE_NAME),
+        }
+    }
+
+    pub fn usage(&self, cache: &Cache) -> Usage {
+        let location = self.location();
+
+        let (size, items) = match self {
+            Kind::Audio => files(&location)
+                .iter()
+                .fold((0, 0), |(size, items), file| (size + file.size, items + 1)),
+            Kind::Metadata => (
+                file_size(&location),
+                cache
+                    .len()
+                    .inspect_err(|err| log::warn!("Cannot count the cached items: {err}"))
+                    .unwrap_or_default(),
+            ),
+            Kind::Credentials => (file_size(&location), location.exists() as usize),
+        };
+
+        Usage {
+            location,
+            size,
+            items,
+        }
+    }
+
+    /// Pinned albums are kept when clearing the audio cache, they have to be unpinned first.
+    pub fn clear(&self, cache: &Cache, pins: &Pins) -> Result<()> {
+        match self {
+            Kind::Audio => {
+                for file in files(&self.location()) {
+                    if !pins.contains(&file.path) {
+                        fs::remove_file(&file.path)?;
+                    }
+                }
+            }
+            Kind::Metadata => cache.clear()?,
+            Kind::Credentials => match fs::remove_file(self.location()) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Albums whose audio files are never evicted, stored in the data directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pins {
+    pub albums: Vec<Pin>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    pub uri: String,
+    pub name: String,
+    /// Every format of every track, whichever ends up being played.
+    pub files: Vec<PathBuf>,
+}
+
+impl Pins {
+    pub fn load() -> Self {
+        fs::read_to_string(pins_path())
+            .ok()
+            .and_then(|content| {
+                serde_json::from_str(&content)
+                    .inspect_err(|err| log::warn!("Cannot read the pinned albums: {err}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let parent = environment::data_dir();
+
+        if !parent.exists() {
+            fs::create_dir_all(&parent)?;
+        }
+
+        fs::write(pins_path(), serde_json::to_string(self)?)
+    }
+
+    /// Pinning an album again refreshes its files.
+    pub fn add(&mut self, pin: Pin) {
+        self.remove(&pin.uri);
+        self.albums.push(pin);
+    }
+
+    pub fn remove(&mut self, uri: &str) -> bool {
+        let len = self.albums.len();
+        self.albums.retain(|pin| pin.uri != uri);
+
+        self.albums.len() != len
+    }
+
+    pub fn contains(&self, file: &Path) -> bool {
+        self.albums
+            .iter()
+            .any(|pin| pin.files.iter().any(|pinned| pinned == file))
+    }
+}
+
+/// Removes the least recently used audio files until the cache fits in `limit` bytes.
+///
+/// Pinned files count towards the limit but are never removed. Returns the bytes freed.
+pub fn evict(limit: u64, pins: &Pins) -> Result<u64, io::Error> {
+    evict_files(&environment::audio_cache_dir(), limit, |file| {
+        pins.contains(file)
+    })
+}
+
+/// Tracks ending walk the audio cache at most once per interval, `last` being the previous walk.
+pub fn eviction_due(last: Option<Instant>, now: Instant) -> bool {
+    last.is_none_or(|last| now.saturating_duration_since(last) >= EVICTION_INTERVAL)
+}
+
+/// Removes the least recently used files of `directory` until it fits in `limit` bytes,
+/// skipping those to `keep`.
+pub fn evict_files(
+    directory: &Path,
+    limit: u64,
+    keep: impl Fn(&Path) -> bool,
+) -> Result<u64, io::Error> {
+    let mut files = files(directory);
+    let mut size: u64 = files.iter().map(|file| file.size).sum();
+    let mut freed = 0;
+
+    files.sort_by_key(|file| file.used);
+
+    for file in files {
+        if size <= limit {
+            break;
+        }
+        if keep(&file.path) {
+            continue;
+        }
+
+        fs::remove_file(&file.path)?;
+        size -= file.size;
+        freed += file.size;
+    }
+
+    Ok(freed)
+}
+
+/// Audio used to be cached in the data directory itself, in the folders librespot names
+/// after the first byte of the file ids. Moves them into `audio`, returns how many were.
+pub fn move_legacy_audio(data: &Path, audio: &Path) -> Result<usize, io::Error> {
+    if !data.exists() {
+        return Ok(0);
+    }
+
+    let mut moved = 0;
+    for entry in fs::read_dir(data)?.flatten() {
+        let name = entry.file_name();
+        let is_audio = name
+            .to_str()
+            .is_some_and(|name| name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit()));
+
+        if !is_audio || !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let target = audio.join(&name);
+        if target.exists() {
+            for file in fs::read_dir(entry.path())?.flatten() {
+                fs::rename(file.path(), target.join(file.file_name()))?;
+            }
+            fs::remove_dir(entry.path())?;
+        } else {
+            fs::rename(entry.path(), target)?;
+        }
+
+        moved += 1;
+    }
+
+    Ok(moved)
+}
+
+/// `1536` is `1.5 KiB`, the same suffixes `cache_limit_size` accepts.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+fn pins_path() -> PathBuf {
+    environment::data_dir().join(PINS_FILE_NAME)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+struct File {
+    path: PathBuf,
+    size: u64,
+    used: SystemTime,
+}
+
+fn files(directory: &Path) -> Vec<File> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .flat_map(|entry| {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                return Vec::new();
+            };
+
+            if metadata.is_dir() {
+                return files(&path);
+            }
+
+            // Access times are often disabled or coarse, files can be touched to mark them as used
+            let used = [metadata.accessed(), metadata.modified()]
+                .into_iter()
+                .flatten()
+                .max()
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+
+            vec![File {
+                path,
+                size: metadata.len(),
+                used,
+            }]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spotify::setup::parse_file_size;
+    use std::fs::FileTimes;
+
+    fn directory(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("oxify-storage-{name}-{}", std::process::id()))
+    }
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"audio").unwrap();
+    }
+
+    /// A file of 100 bytes last read and written the given seconds ago.
+    fn cached(directory: &Path, name: &str, accessed: u64, modified: u64) -> PathBuf {
+        let path = directory.join(name);
+        fs::create_dir_all(directory).unwrap();
+        fs::write(&path, [0; 100]).unwrap();
+
+        let now = SystemTime::now();
+        let times = FileTimes::new()
+            .set_accessed(now - Duration::from_secs(accessed))
+            .set_modified(now - Duration::from_secs(modified));
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_times(times)
+            .unwrap();
+
+        path
+    }
+
+    /// Evicts down to `limit` a directory holding an old pinned file, then files used
+    /// 300 s ago, read 20 s ago, written 40 s ago and used 10 s ago. Returns what is left.
+    fn evict_cache(name: &str, limit: u64) -> (u64, Vec<&'static str>) {
+        let directory = directory(name);
+        let pinned = cached(&directory, "pinned", 1000, 1000);
+        cached(&directory, "old", 300, 300);
+        cached(&directory, "read", 20, 500);
+        cached(&directory, "written", 500, 40);
+        cached(&directory, "fresh", 10, 10);
+
+        let freed = evict_files(&directory, limit, |file| file == pinned.as_path()).unwrap();
+        let left = ["pinned", "old", "read", "written", "fresh"]
+            .into_iter()
+            .filter(|name| directory.join(name).exists())
+            .collect();
+        fs::remove_dir_all(&directory).unwrap();
+
+        (freed, left)
+    }
+
+    #[test]
+    fn eviction_removes_the_least_recently_read_or_written_files_first() {
+        assert_eq!(
+            evict_cache("order", 400),
+            (100, vec!["pinned", "read", "written", "fresh"])
+        );
+        assert_eq!(evict_cache("most", 200), (300, vec!["pinned", "fresh"]));
+    }
+
+    #[test]
+    fn eviction_keeps_pinned_files_even_above_the_limit() {
+        assert_eq!(evict_cache("pinned", 0), (400, vec!["pinned"]));
+    }
+
+    #[test]
+    fn eviction_stops_once_under_the_limit() {
+        assert_eq!(
+            evict_cache("under", 500),
+            (0, vec!["pinned", "old", "read", "written", "fresh"])
+        );
+    }
+
+    #[test]
+    fn eviction_is_throttled_after_tracks() {
+        let now = Instant::now();
+
+        assert!(eviction_due(None, now));
+        assert!(!eviction_due(Some(now), now));
+        assert!(!eviction_due(
+            Some(now),
+            now + EVICTION_INTERVAL - Duration::from_secs(1)
+        ));
+        assert!(eviction_due(Some(now), now + EVICTION_INTERVAL));
+    }
+
+    #[test]
+    fn cache_limits_accept_decimal_and_binary_sizes() {
+        assert_eq!(parse_file_size("10G").unwrap(), 10_000_000_000);
+        assert_eq!(parse_file_size("500MiB").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(parse_file_size("500Mi").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(parse_file_size("1.5 KiB").unwrap(), 1536);
+        assert_eq!(parse_file_size("2048").unwrap(), 2048);
+        assert!(parse_file_size("").is_err());
+        assert!(parse_file_size("10X").is_err());
+    }
+
+    #[test]
+    fn legacy_audio_is_moved_into_the_audio_directory() {
+        let data = directory("legacy");
+        let audio = data.join("audio");
+        touch(&data.join("ab").join("first"));
+        touch(&data.join("cd").join("second"));
+        touch(&audio.join("ab").join("third"));
+        touch(&data.join("zz").join("kept"));
+        touch(&data.join("cache.db"));
+
+        let moved = move_legacy_audio(&data, &audio);
+
+        let exists = |path: &[&str]| {
+            path.iter()
+                .fold(data.clone(), |dir, part| dir.join(part))
+                .exists()
+        };
+        let found = (
+            exists(&["audio", "ab", "first"]),
+            exists(&["audio", "ab", "third"]),
+            exists(&["audio", "cd", "second"]),
+            exists(&["ab"]) || exists(&["cd"]),
+            exists(&["zz", "kept"]) && exists(&["cache.db"]),
+        );
+        fs::remove_dir_all(&data).unwrap();
+
+        assert_eq!(moved.unwrap(), 2);
+        assert_eq!(found, (true, true, true, false, true));
+    }
+}
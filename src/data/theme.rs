@@ -0,0 +1,96 @@
+use crate::environment;
+use anyhow::{anyhow, Result};
+use chrono::NaiveTime;
+use iced::{theme::Palette, Color, Theme};
+use serde::Deserialize;
+use std::{fs, path::PathBuf};
+
+const DIRECTORY: &str = "themes";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Light,
+    Dark,
+}
+
+/// Colors of `themes/<name>.toml` in the config directory, as `#rrggbb`.
+#[derive(Debug, Clone, Deserialize)]
+struct File {
+    background: String,
+    text: String,
+    primary: String,
+    success: String,
+    danger: String,
+}
+
+/// Builds the custom theme `name`, read again on every call so config reloads pick up edits.
+pub fn load(name: &str) -> Result<Theme> {
+    let path = path(name);
+
+    let content = fs::read_to_string(&path).map_err(|_| {
+        anyhow!(
+            "Unknown theme \"{name}\", it is not built-in and {} does not exist",
+            path.display()
+        )
+    })?;
+    let file: File = toml::from_str(&content)
+        .map_err(|err| anyhow!("Cannot read the theme {}: {err}", path.display()))?;
+
+    let color = |key: &str, value: &str| {
+        Color::parse(value).ok_or(anyhow!(
+            "Invalid {key} color \"{value}\" in {}",
+            path.display()
+        ))
+    };
+
+    let palette = Palette {
+        background: color("background", &file.background)?,
+        text: color("text", &file.text)?,
+        primary: color("primary", &file.primary)?,
+        success: color("success", &file.success)?,
+        danger: color("danger", &file.danger)?,
+    };
+
+    Ok(Theme::custom(name.to_string(), palette))
+}
+
+/// Names of the custom themes in the config directory.
+pub fn custom() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(environment::config_dir().join(DIRECTORY)) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+
+    names
+}
+
+/// Light from `day_start` until `night_start`, which may be past midnight.
+pub fn scheduled(day_start: NaiveTime, night_start: NaiveTime, now: NaiveTime) -> Scheme {
+    let day = if day_start <= night_start {
+        day_start <= now && now < night_start
+    } else {
+        now >= day_start || now < night_start
+    };
+
+    if day {
+        Scheme::Light
+    } else {
+        Scheme::Dark
+    }
+}
+
+fn path(name: &str) -> PathBuf {
+    environment::config_dir()
+        .join(DIRECTORY)
+        .join(format!("{name}.toml"))
+}
@@ -0,0 +1,50 @@
+use std::collections::VecDeque;
+
+/// Number of spectrum bars, spaced logarithmically from 30 Hz to 16 kHz.
+pub const BARS: usize = 32;
+/// Mono samples drawn by the oscilloscope.
+pub const SCOPE_LENGTH: usize = 512;
+/// Peaks kept by the scrolling waveform.
+pub const WAVEFORM_LENGTH: usize = 128;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, strum::Display, strum::EnumString, strum::EnumIter,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Style {
+    #[default]
+    Bars,
+    Oscilloscope,
+    Waveform,
+}
+
+impl Style {
+    pub fn cycle(self) -> Self {
+        match self {
+            Style::Bars => Style::Oscilloscope,
+            Style::Oscilloscope => Style::Waveform,
+            Style::Waveform => Style::Bars,
+        }
+    }
+}
+
+/// Latest analysis of the audio being played, every value is normalized.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// From 0.0 to 1.0, low frequencies first.
+    pub bars: [f32; BARS],
+    /// From -1.0 to 1.0.
+    pub scope: Vec<f32>,
+    /// From 0.0 to 1.0, oldest first.
+    pub waveform: VecDeque<f32>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            bars: [0.0; BARS],
+            scope: vec![0.0; SCOPE_LENGTH],
+            waveform: VecDeque::from(vec![0.0; WAVEFORM_LENGTH]),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+/// Scrolling by pixels, as touchpads do, counts as one line every this many pixels.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Mixer volume, from 0 to `u16::MAX`, as a percentage.
+pub fn percent(volume: u16) -> f32 {
+    volume as f32 * 100.0 / u16::MAX as f32
+}
+
+pub fn from_percent(percent: f32) -> u16 {
+    (percent.clamp(0.0, 100.0) / 100.0 * u16::MAX as f32).round() as u16
+}
+
+/// Moves `volume` by `percent`, which may be negative.
+pub fn step(volume: u16, percent: f32) -> u16 {
+    from_percent(self::percent(volume) + percent)
+}
+
+pub fn scroll_lines(delta: iced::mouse::ScrollDelta) -> f32 {
+    match delta {
+        iced::mouse::ScrollDelta::Lines { y, .. } => y,
+        iced::mouse::ScrollDelta::Pixels { y, .. } => y / PIXELS_PER_LINE,
+    }
+}
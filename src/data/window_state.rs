@@ -0,0 +1,163 @@
+use crate::environment;
+use iced::{Point, Size};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+const FILE_NAME: &str = "window_state.json";
+
+/// Geometry of the windows, to open them again the way they were left.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub size: (f32, f32),
+    pub position: Option<(f32, f32)>,
+    pub maximized: bool,
+    /// Position of the mini player, which has a fixed size.
+    #[serde(default)]
+    pub mini_player: Option<(f32, f32)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Change {
+    Resized(Size),
+    Moved(Point),
+}
+
+impl WindowState {
+    /// The first run opens the window at the configured size, where the system places it.
+    pub fn new(size: (f32, f32)) -> Self {
+        Self {
+            size,
+            position: None,
+            maximized: false,
+            mini_player: None,
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(path()).ok()?;
+
+        serde_json::from_str(&content)
+            .inspect_err(|err| log::warn!("Cannot read the window state: {err}"))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let parent = environment::data_dir();
+
+        if !parent.exists() {
+            fs::create_dir_all(&parent)?;
+        }
+
+        fs::write(path(), serde_json::to_string(self)?)
+    }
+
+    pub fn size(&self) -> Size {
+        self.size.into()
+    }
+
+    pub fn position(&self) -> Option<Point> {
+        self.position.map(|(x, y)| Point::new(x, y))
+    }
+
+    pub fn mini_player(&self) -> Option<Point> {
+        self.mini_player.map(|(x, y)| Point::new(x, y))
+    }
+
+    /// Size and position are only tracked unmaximized, so unmaximizing restores them.
+    pub fn apply(&mut self, change: Change, maximized: bool) {
+        self.maximized = maximized;
+
+        if maximized {
+            return;
+        }
+
+        match change {
+            Change::Resized(size) => self.size = (size.width, size.height),
+            Change::Moved(position) => self.position = Some((position.x, position.y)),
+        }
+    }
+
+    /// At least `min` large and, once the `monitor` the window opened on is known, within it.
+    pub fn clamp(self, min: Size, monitor: Option<Size>) -> Self {
+        let (width, height) = self.size;
+        let max = monitor.unwrap_or(Size::INFINITY);
+        let size = Size::new(
+            width.min(max.width).max(min.width),
+            height.min(max.height).max(min.height),
+        );
+
+        let position = match monitor {
+            Some(monitor) => self
+                .position()
+                .map(|position| fit(position, size, monitor))
+                .map(|position| (position.x, position.y)),
+            None => self.position,
+        };
+
+        Self {
+            size: (size.width, size.height),
+            position,
+            ..self
+        }
+    }
+}
+
+/// Moves a window `size` large at `position` so all of it is on the `monitor`.
+///
+/// Only the size of the monitor is known, so it is taken to be at the origin, which
+/// brings back a window left on a display unplugged since.
+pub fn fit(position: Point, size: Size, monitor: Size) -> Point {
+    Point::new(
+        position.x.min(monitor.width - size.width).max(0.0),
+        position.y.min(monitor.height - size.height).max(0.0),
+    )
+}
+
+fn path() -> PathBuf {
+    environment::data_dir().join(FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: Size = Size::new(400.0, 300.0);
+    const MONITOR: Size = Size::new(1920.0, 1080.0);
+
+    fn state(size: (f32, f32), position: (f32, f32)) -> WindowState {
+        WindowState {
+            position: Some(position),
+            ..WindowState::new(size)
+        }
+    }
+
+    #[test]
+    fn the_position_is_kept_until_the_monitor_is_known() {
+        let clamped = state((100.0, 100.0), (3000.0, 200.0)).clamp(MIN, None);
+
+        assert_eq!(clamped.size, (400.0, 300.0));
+        assert_eq!(clamped.position, Some((3000.0, 200.0)));
+    }
+
+    #[test]
+    fn windows_left_on_an_unplugged_display_come_back() {
+        let clamped = state((800.0, 600.0), (3000.0, -50.0)).clamp(MIN, Some(MONITOR));
+
+        assert_eq!(clamped.position, Some((1120.0, 0.0)));
+    }
+
+    #[test]
+    fn windows_larger_than_the_monitor_shrink_to_it() {
+        let clamped = state((2560.0, 1440.0), (100.0, 100.0)).clamp(MIN, Some(MONITOR));
+
+        assert_eq!(clamped.size, (1920.0, 1080.0));
+        assert_eq!(clamped.position, Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn visible_windows_stay_where_they_are() {
+        let position = Point::new(200.0, 150.0);
+
+        assert_eq!(fit(position, Size::new(360.0, 88.0), MONITOR), position);
+    }
+}
@@ -5,24 +5,34 @@ mod logger;
 mod oxify;
 mod screen;
 mod spotify;
+mod widget;
 
-use crate::data::font;
-use anyhow::Result;
+use crate::data::{
+    cache::Cache,
+    font,
+    playback::Modes,
+    storage::{self, Kind, Pins},
+};
+use anyhow::{anyhow, Result};
 use context::{config::Config, environment};
 use oxify::Oxify;
-use spotify::Setup;
-use std::env;
+use spotify::{service, setup::parse_file_size, Setup};
+use std::{env, str::FromStr};
+use strum::IntoEnumIterator;
 use tokio::runtime::Runtime;
 
 fn main() -> Result<()> {
     let mut args = env::args();
     args.next();
 
-    let version = args.next().is_some_and(|s| s == "--version" || s == "-v");
-
-    if version {
-        println!("Oxify {}", env!("CARGO_PKG_VERSION"));
-        return Ok(());
+    match args.next().as_deref() {
+        Some("--version" | "-v") => {
+            println!("Oxify {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Some(key @ ("shuffle" | "repeat")) => return playback_mode(key, args.next()),
+        Some("cache") => return cache(args),
+        _ => {}
     }
 
     let is_debug = cfg!(debug_assertions);
@@ -32,8 +42,6 @@ fn main() -> Result<()> {
     log::info!("config dir: {:?}", environment::config_dir());
     log::info!("data dir: {:?}", environment::data_dir());
 
-    font::set();
-
     let (config, setup) = (|| -> Result<(Config, Setup)> {
         let rt = Runtime::new()?;
 
@@ -45,19 +53,126 @@ fn main() -> Result<()> {
         })
     })()?;
 
+    // Until the config font files are loaded, the families already installed are used
+    font::set(&config.appaerance, false);
+
     let settings = iced::Settings {
-        default_font: font::MONO.clone().into(),
+        default_font: font::BODY.get(),
         default_text_size: config.appaerance.font_size.into(),
         id: None,
         antialiasing: false,
-        fonts: font::load(),
+        fonts: vec![appaerance::icon::load()],
     };
 
     iced::daemon("Oxify", Oxify::update, Oxify::view)
         .theme(Oxify::theme)
+        .scale_factor(Oxify::scale_factor)
+        .subscription(Oxify::subscription)
         .settings(settings)
         .run_with(move || Oxify::new(log_stream, config, setup))
         .inspect_err(|err| log::error!("{}", err))?;
 
     Ok(())
 }
+
+/// `oxify shuffle [off|on|smart]` and `oxify repeat [off|context|track]`.
+///
+/// Prints the current mode when no value is given. A running Oxify picks a new mode up
+/// within a few seconds.
+fn playback_mode(key: &str, value: Option<String>) -> Result<()> {
+    let mut modes = Modes::load();
+
+    if let Some(value) = value {
+        modes
+            .set(key, &value)
+            .map_err(|_| anyhow!("Invalid {key} mode: {value}"))?;
+        modes.save()?;
+    }
+
+    match key {
+        "shuffle" => println!("shuffle: {}", modes.shuffle),
+        _ => println!("repeat: {}", modes.repeat),
+    }
+
+    Ok(())
+}
+
+/// `oxify cache` prints the usage of every cache, the audio cache limit and the pinned albums.
+///
+/// `oxify cache clear [audio|metadata|credentials]`, `oxify cache limit [SIZE]`,
+/// `oxify cache pin [ALBUM_URI]` and `oxify cache unpin [ALBUM_URI]` manage them.
+fn cache(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let rt = Runtime::new()?;
+    let mut config = rt.block_on(Config::load());
+    let metadata = Cache::open();
+    let mut pins = Pins::load();
+
+    match (args.next().as_deref(), args.next()) {
+        (None, _) => {
+            for kind in Kind::iter() {
+                let usage = kind.usage(&metadata);
+                println!(
+                    "{kind}: {} in {} items, {}",
+                    storage::format_size(usage.size),
+                    usage.items,
+                    usage.location.display()
+                );
+            }
+
+            println!("limit: {}", config.audio.cache_limit_size);
+            for pin in &pins.albums {
+                println!("pinned: {} ({})", pin.name, pin.uri);
+            }
+        }
+        (Some("clear"), Some(kind)) => {
+            let kind = Kind::from_str(&kind).map_err(|_| {
+                anyhow!("Invalid cache: {kind}, options are: audio, metadata, credentials")
+            })?;
+
+            kind.clear(&metadata, &pins)?;
+            println!("{kind} cache cleared");
+        }
+        (Some("limit"), None) => println!("limit: {}", config.audio.cache_limit_size),
+        (Some("limit"), Some(limit)) => {
+            let bytes = parse_file_size(&limit)
+                .map_err(|err| anyhow!("Invalid cache limit {limit}: {err}"))?;
+
+            let previous = config.clone();
+            config.audio.cache_limit_size = limit;
+            rt.block_on(config.save(&previous))?;
+
+            let freed = storage::evict(bytes, &pins)?;
+            println!(
+                "limit: {}, {} evicted",
+                config.audio.cache_limit_size,
+                storage::format_size(freed)
+            );
+        }
+        (Some("pin"), Some(uri)) => {
+            // Only metadata is needed, no player nor audio device
+            let pin = rt.block_on(async {
+                let session = service::session(&Setup::load(config, None).await?).await?;
+                service::pin(&session, &uri).await
+            })?;
+
+            println!("pinned: {}", pin.name);
+            pins.add(pin);
+            pins.save()?;
+        }
+        (Some("unpin"), Some(uri)) => {
+            if !pins.remove(&uri) {
+                return Err(anyhow!("{uri} is not pinned"));
+            }
+
+            pins.save()?;
+            println!("unpinned: {uri}");
+        }
+        _ => {
+            return Err(anyhow!(
+                "Usage: oxify cache [clear <audio|metadata|credentials> | limit [SIZE] | pin <ALBUM_URI> | unpin <ALBUM_URI>]"
+            ))
+        }
+    }
+
+    Ok(())
+}
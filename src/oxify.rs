@@ -1,27 +1,127 @@
 use crate::{
-    context::config::Config,
+    context::{
+        config::{Config, Equalizer, AUTO_THEME},
+        environment::{self, WEBSITE_URL},
+        portal,
+    },
     data::{
+        cache::Cache,
+        cover::{self, Cover},
+        detail::Failure,
+        font,
+        keybinding::{Action, Keymap},
+        library::{parse_uri, Section, Selection, Track},
+        local,
         log::Record,
-        messages::{Message, OxifyMessage},
+        messages::{
+            AlbumMessage, ArtistMessage, LibraryMessage, Message, OxifyMessage, PlaylistMessage,
+            SearchMessage, SettingsMessage, SidebarMessage,
+        },
+        palette::Command,
+        playback::{Modes, Shuffle},
+        queue::Queue,
+        search::{Group, Navigation},
+        session::{Position, Session},
+        storage::{self, Kind, Pins},
+        theme::{self, Scheme},
+        visualizer::{self, Frame},
+        volume,
+        window_state::{self, Change, WindowState},
+    },
+    screen::{
+        library, mini_player, palette, player_bar, playlist, search, sidebar, Album, AlbumEvent,
+        Artist, ArtistEvent, Home, HomeEvent, Library, LibraryEvent, Logs, Palette, PaletteEvent,
+        Playlist, PlaylistEvent, QueueEvent, QueuePanel, Router, Screen, Search, SearchEvent,
+        Settings, SettingsEvent, Welcome, WelcomeEvent,
     },
-    screen::{Screen, Welcome, WelcomeEvent},
-    spotify::{Service, Setup},
+    spotify::{local::LocalEvent, setup::parse_file_size, sync, Api, Service, Setup},
 };
 use iced::{
-    widget::container,
+    event, keyboard, mouse,
+    widget::{
+        column, container, row,
+        scrollable::{self, AbsoluteOffset, RelativeOffset},
+        stack, text_input,
+    },
     window::{self, Id},
-    Element, Size, Task, Theme,
+    Element, Event, Length, Point, Size, Subscription, Task, Theme,
+};
+use librespot::playback::player::PlayerEvent;
+use std::{
+    ops::RangeInclusive,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
 };
-use tokio_stream::wrappers::ReceiverStream;
+use strum::IntoEnumIterator;
+use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
+
+use crate::{appaerance as ui, widget::text};
 
 const MIN_SIZE: Size = Size::new(400.0, 300.0);
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+const MODES_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const THEME_SCHEDULE_INTERVAL: Duration = Duration::from_secs(60);
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const ZOOM_STEP: f64 = 0.1;
+const ZOOM_RANGE: RangeInclusive<f64> = 0.5..=3.0;
+const SEEK_STEP_MS: i64 = 5_000;
 
 #[derive(Clone)]
 pub struct Oxify {
-    pub screen: Screen,
+    pub router: Router,
+    pub welcome: Welcome,
+    pub home: Home,
+    pub library: Library,
+    pub search: Search,
+    pub album: Album,
+    pub artist: Artist,
+    pub playlist: Playlist,
+    pub settings: Settings,
+    pub logs: Logs,
     pub config: Config,
+    /// The config as last loaded or saved, saving only writes what changed since.
+    pub saved_config: Config,
     pub setup: Setup,
     pub service: Option<Service>,
+    /// Shared by the web api and the covers, so their connections are pooled.
+    pub client: reqwest::Client,
+    /// A reconnection is in flight, no other one is started meanwhile.
+    pub reconnecting: bool,
+    /// Last walk of the audio cache, tracks ending do not walk it more than once per interval.
+    pub evicted_at: Option<Instant>,
+    /// Last change of the modes file this instance knows of, newer ones came from elsewhere.
+    pub modes_modified: Option<SystemTime>,
+    /// The session as last written, unchanged ones are not written again.
+    pub saved_session: Option<Session>,
+    pub queue: Queue,
+    pub queue_panel: QueuePanel,
+    pub show_queue: bool,
+    pub position: Position,
+    pub visualizer: Frame,
+    /// Tracks of the local files index.
+    pub local: Vec<Track>,
+    pub cache: Cache,
+    /// Last cover loaded, only shown while it belongs to the current track.
+    pub cover: Option<Cover>,
+    pub theme: Theme,
+    /// Why the configured theme could not be used, shown until the config is fixed.
+    pub theme_warning: Option<String>,
+    /// Why the configured fonts could not be loaded, shown until the config is fixed.
+    pub font_warning: Option<String>,
+    /// Scheme preferred by the desktop, as reported by the portal.
+    pub desktop_scheme: Option<Scheme>,
+    /// Scheme `theme` was picked for, while it is auto.
+    pub theme_scheme: Option<Scheme>,
+    pub main_window: window::Id,
+    pub window_state: WindowState,
+    pub mini_player: Option<window::Id>,
+    pub keymap: Keymap,
+    /// Invalid and conflicting keybindings, shown until the config is fixed.
+    pub keymap_warnings: Vec<String>,
+    pub palette: Option<Palette>,
 }
 
 impl Oxify {
@@ -30,23 +130,71 @@ impl Oxify {
         config: Config,
         setup: Setup,
     ) -> (Self, Task<Message>) {
-        let (_, open_main_window) = window::open(window::Settings {
-            size: config.appaerance.window_size.into(),
-            position: window::Position::Default,
+        let window_state = WindowState::load()
+            .unwrap_or_else(|| WindowState::new(config.appaerance.window_size))
+            .clamp(MIN_SIZE, None);
+
+        let (main_window, open_main_window) = window::open(window::Settings {
+            size: window_state.size(),
+            position: window_state
+                .position()
+                .map_or(window::Position::Default, window::Position::Specific),
             min_size: Some(MIN_SIZE),
             exit_on_close_request: true,
             ..Default::default()
         });
 
-        let oxify = Self {
-            screen: Screen::Welcome(Welcome::new()),
+        let local_paths = config.library.local_paths.clone();
+        let settings = Settings::new(&config);
+        let (keymap, keymap_warnings) = Keymap::new(&config.keybindings);
+
+        let mut oxify = Self {
+            router: Router::new(Screen::Welcome),
+            welcome: Welcome::new(),
+            home: Home::new(),
+            library: Library::new(),
+            search: Search::new(),
+            album: Album::new(),
+            artist: Artist::new(),
+            playlist: Playlist::new(),
+            settings,
+            logs: Logs::new(),
+            saved_config: config.clone(),
             config,
             setup,
             service: None,
+            client: reqwest::Client::new(),
+            reconnecting: false,
+            evicted_at: None,
+            modes_modified: Modes::modified(),
+            saved_session: None,
+            queue: Queue::new(Modes::load()),
+            queue_panel: QueuePanel::new(),
+            show_queue: false,
+            position: Position::default(),
+            visualizer: Frame::default(),
+            local: local::Index::load().tracks(),
+            cache: Cache::open(),
+            cover: None,
+            theme: Theme::default(),
+            theme_warning: None,
+            font_warning: None,
+            desktop_scheme: None,
+            theme_scheme: None,
+            main_window,
+            window_state,
+            mini_player: None,
+            keymap,
+            keymap_warnings,
+            palette: None,
         };
+        oxify.apply_theme();
 
         let commands = vec![
-            open_main_window.then(|_| Task::none()),
+            open_main_window.then(move |id| restore_window(id, window_state)),
+            scan_local_files(local_paths),
+            oxify.evict_audio_cache(),
+            oxify.load_fonts(),
             Task::stream(log_stream).map(|ls| Message::OxifyMessage(OxifyMessage::Logging(ls))),
         ];
 
@@ -56,40 +204,1862 @@ impl Oxify {
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::OxifyMessage(oxify_message) => match oxify_message {
-                OxifyMessage::Logging(records) => Task::none(),
+                OxifyMessage::Logging(records) => {
+                    self.logs.push(records);
+                    Task::none()
+                }
                 OxifyMessage::ConfigReloaded(config) => {
+                    let fonts_changed = {
+                        let (old, new) = (&self.config.appaerance, &config.appaerance);
+
+                        old.font_family != new.font_family
+                            || old.heading_font_family != new.heading_font_family
+                            || old.font_files != new.font_files
+                    };
+
+                    (self.keymap, self.keymap_warnings) = Keymap::new(&config.keybindings);
+                    self.saved_config = config.clone();
                     self.config = config;
+                    self.apply_theme();
+
+                    if fonts_changed {
+                        self.load_fonts()
+                    } else {
+                        Task::none()
+                    }
+                }
+                OxifyMessage::FontsLoaded(warning) => {
+                    if let Some(warning) = &warning {
+                        log::warn!("{warning}");
+                    }
+
+                    font::set(&self.config.appaerance, warning.is_some());
+                    self.font_warning = warning;
+                    Task::none()
+                }
+                OxifyMessage::DesktopScheme(scheme) => {
+                    self.desktop_scheme = scheme;
+                    self.follow_scheme();
+                    Task::none()
+                }
+                OxifyMessage::ThemeTick => {
+                    self.follow_scheme();
+                    Task::none()
+                }
+                OxifyMessage::ServiceConnected(service) => {
+                    let events = self.attach(service);
+
+                    if self.config.playback.restore_session {
+                        self.restore_session();
+                    }
+
+                    Task::batch(vec![
+                        self.queue
+                            .current()
+                            .map_or(Task::none(), |track| self.fetch_cover(track)),
+                        events,
+                        self.open_shell(),
+                    ])
+                }
+                OxifyMessage::ConnectionTick => self.check_connection(),
+                OxifyMessage::Reconnected(service) => {
+                    log::info!("Reconnected to Spotify");
+                    self.reconnecting = false;
+                    let events = self.attach(service);
+
+                    if self.queue.current().is_none() && self.config.playback.restore_session {
+                        // Offline since the start, nothing was restored yet
+                        self.restore_session();
+                    } else if let Some(track) = self.queue.current() {
+                        // The new player starts empty, the current track waits where it was
+                        self.cue(track, self.position.current_ms());
+                        self.preload_next();
+                    }
+
+                    Task::batch(vec![events, self.fetch_playlists()])
+                }
+                OxifyMessage::ReconnectFailed => {
+                    self.reconnecting = false;
+                    Task::none()
+                }
+                OxifyMessage::Offline => {
+                    let cached = self
+                        .cache
+                        .is_empty()
+                        .inspect_err(|err| log::warn!("Cannot read the metadata cache: {err}"))
+                        .is_ok_and(|empty| !empty);
+
+                    if !cached {
+                        return Task::none();
+                    }
+
+                    log::info!("Browsing the cached library while offline");
+                    self.open_shell()
+                }
+                OxifyMessage::Player(event) => self.handle_player_event(event),
+                OxifyMessage::ModesTick => self.check_modes(),
+                OxifyMessage::ModesChanged(modified, modes) => {
+                    // A check started before this instance saved its own modes is outdated
+                    if self.modes_modified.is_some_and(|seen| seen >= modified) {
+                        return Task::none();
+                    }
+                    self.modes_modified = Some(modified);
+
+                    if modes != self.queue.modes() {
+                        log::info!(
+                            "Playback modes changed to {} shuffle and {} repeat",
+                            modes.shuffle,
+                            modes.repeat
+                        );
+                        self.queue.set_modes(modes);
+                        self.preload_next();
+                    }
+                    Task::none()
+                }
+                OxifyMessage::SaveSession => {
+                    self.save_window_state();
+                    self.save_session()
+                }
+                OxifyMessage::WindowClosed(id) => {
+                    // Only closing the main window quits
+                    if id != self.main_window {
+                        if self.mini_player == Some(id) {
+                            self.mini_player = None;
+                        }
+                        return Task::none();
+                    }
+
+                    // Saved in place, a background write would not finish before the exit
+                    if let Some(session) = self.session() {
+                        if let Err(err) = session.save() {
+                            log::warn!("Cannot save the session: {err}");
+                        }
+                    }
+                    self.save_window_state();
+                    iced::exit()
+                }
+                OxifyMessage::WindowChanged(id, change) => {
+                    if self.mini_player == Some(id) {
+                        if let Change::Moved(position) = change {
+                            self.window_state.mini_player = Some((position.x, position.y));
+                        }
+                        return Task::none();
+                    }
+                    if id != self.main_window {
+                        return Task::none();
+                    }
+
+                    window::get_maximized(id).map(move |maximized| {
+                        Message::OxifyMessage(OxifyMessage::MainWindowChanged(change, maximized))
+                    })
+                }
+                OxifyMessage::MainWindowChanged(change, maximized) => {
+                    self.window_state.apply(change, maximized);
+                    Task::none()
+                }
+                OxifyMessage::Local(event) => match event {
+                    LocalEvent::Playing { position_ms } => {
+                        self.position = Position::playing(position_ms);
+                        Task::none()
+                    }
+                    LocalEvent::Paused { position_ms } => {
+                        self.position = Position::paused(position_ms);
+                        Task::none()
+                    }
+                    LocalEvent::EndOfTrack => self.end_of_track(),
+                    // Same as librespot, a track that cannot be played is skipped
+                    LocalEvent::Unavailable => self.skip_next(),
+                },
+                OxifyMessage::LocalScanned(tracks) => {
+                    self.local = tracks.clone();
+                    Task::done(Message::LibraryMessage(LibraryMessage::SectionFetched(
+                        Section::Local,
+                        tracks,
+                    )))
+                }
+                OxifyMessage::CoverLoaded(cover) => {
+                    self.cover = Some(cover);
                     Task::none()
                 }
+                OxifyMessage::ProgressTick => Task::none(),
+                OxifyMessage::VisualizerTick => {
+                    if let Some(service) = &self.service {
+                        self.visualizer = service.visualizer.frame();
+                    }
+                    Task::none()
+                }
+                OxifyMessage::KeyPressed(key, modifiers) => {
+                    match self.keymap.action(&key, modifiers) {
+                        // Only the palette itself works while it is open, it handles its own keys
+                        Some(Action::CommandPalette) => self.toggle_palette(),
+                        Some(_) if self.palette.is_some() => Task::none(),
+                        Some(action) => self.perform(action),
+                        None => Task::none(),
+                    }
+                }
             },
             Message::WelcomeMessage(welcome_message) => {
-                let Screen::Welcome(welcome) = &mut self.screen else {
-                    return Task::none();
-                };
+                match self.welcome.update(welcome_message) {
+                    Some(event) => match event {
+                        WelcomeEvent::LoginAttempt => {
+                            let setup = self.setup.clone();
+                            Task::future(async move { Service::connect(setup).await }).then(
+                                |result| match result {
+                                    Ok(service) => Task::done(Message::OxifyMessage(
+                                        OxifyMessage::ServiceConnected(service),
+                                    )),
+                                    Err(err) => {
+                                        log::error!("Cannot connect to Spotify: {err}");
+                                        Task::done(Message::OxifyMessage(OxifyMessage::Offline))
+                                    }
+                                },
+                            )
+                        }
+                        WelcomeEvent::ReloadConfigAttempt => self.reload_config(),
+                    },
+                    None => Task::none(),
+                }
+            }
+            Message::SidebarMessage(sidebar_message) => match sidebar_message {
+                SidebarMessage::Open(screen) => self.open(screen),
+                SidebarMessage::Back => self.back(),
+                SidebarMessage::Forward => self.forward(),
+                SidebarMessage::ToggleQueue => {
+                    self.show_queue = !self.show_queue;
+                    Task::none()
+                }
+            },
+            Message::HomeMessage(home_message) => match self.home.update(home_message) {
+                Some(event) => match event {
+                    HomeEvent::Replay(index) => match self.queue.history().get(index).cloned() {
+                        Some(track) => self.play(track),
+                        None => Task::none(),
+                    },
+                    HomeEvent::Open(selection) => self.open_selection(selection),
+                    HomeEvent::OpenUri(uri) => self.open_uri(&uri),
+                },
+                None => Task::none(),
+            },
+            Message::LibraryMessage(library_message) => {
+                match self.library.update(library_message) {
+                    Some(event) => match event {
+                        LibraryEvent::Fetch(selection) => self.fetch_library(selection),
+                        LibraryEvent::Play(uri, tracks, index) => {
+                            self.play_context(uri, tracks, index)
+                        }
+                        LibraryEvent::Enqueue(track) => {
+                            self.queue.enqueue(track);
+                            self.preload_next();
+                            Task::none()
+                        }
+                        LibraryEvent::OpenUri(uri) => self.open_uri(&uri),
+                        LibraryEvent::ScrollTo(y) => scrollable::scroll_to(
+                            scrollable::Id::new(library::TABLE_ID),
+                            AbsoluteOffset { x: 0.0, y },
+                        ),
+                    },
+                    None => Task::none(),
+                }
+            }
+            Message::SearchMessage(search_message) => match self.search.update(search_message) {
+                Some(event) => match event {
+                    SearchEvent::Debounce(generation) => Task::future(async move {
+                        tokio::time::sleep(SEARCH_DEBOUNCE).await;
+                        Message::SearchMessage(SearchMessage::Debounced(generation))
+                    }),
+                    SearchEvent::Search(query) => self.search(query, Group::iter().collect(), 0),
+                    SearchEvent::LoadMore(query, group, offset) => {
+                        self.search(query, vec![group], offset)
+                    }
+                    SearchEvent::Play(track) => self.play(track),
+                    SearchEvent::PlayNext(track) => {
+                        self.queue.play_next(track);
+                        self.preload_next();
+                        Task::none()
+                    }
+                    SearchEvent::Queue(track) => {
+                        self.queue.enqueue(track);
+                        self.preload_next();
+                        Task::none()
+                    }
+                    SearchEvent::Open(uri) => self.open_uri(&uri),
+                },
+                None => Task::none(),
+            },
+            Message::AlbumMessage(album_message) => match self.album.update(album_message) {
+                Some(event) => match event {
+                    AlbumEvent::Play(uri, tracks, index) => self.play_context(uri, tracks, index),
+                    AlbumEvent::OpenUri(uri) => self.open_uri(&uri),
+                    AlbumEvent::Fetch(id) => self.fetch_album(id),
+                },
+                None => Task::none(),
+            },
+            Message::ArtistMessage(artist_message) => match self.artist.update(artist_message) {
+                Some(event) => match event {
+                    ArtistEvent::Play(uri, tracks, index) => self.play_context(uri, tracks, index),
+                    ArtistEvent::Follow(id, follow) => self.follow_artist(id, follow),
+                    ArtistEvent::OpenUri(uri) => self.open_uri(&uri),
+                    ArtistEvent::Fetch(id) => self.fetch_artist(id),
+                },
+                None => Task::none(),
+            },
+            Message::PlaylistMessage(playlist_message) => {
+                match self.playlist.update(playlist_message) {
+                    Some(event) => match event {
+                        PlaylistEvent::Play(uri, tracks, index) => {
+                            self.play_context(uri, tracks, index)
+                        }
+                        PlaylistEvent::OpenUri(uri) => self.open_uri(&uri),
+                        PlaylistEvent::Fetch(id) => self.fetch_playlist(id),
+                    },
+                    None => Task::none(),
+                }
+            }
+            Message::SettingsMessage(settings_message) => {
+                match self.settings.update(settings_message) {
+                    Some(event) => match event {
+                        SettingsEvent::SetNormalisation(enabled) => {
+                            // The player reads it once, it applies on the next start
+                            self.config.audio.normalisation.enabled = enabled;
+                            self.save_config()
+                        }
+                        SettingsEvent::SetVisualizer(enabled) => {
+                            self.config.appaerance.visualizer.enabled = enabled;
+                            if let Some(service) = &self.service {
+                                service.visualizer.set_enabled(enabled);
+                            }
+                            self.visualizer = Frame::default();
+                            self.save_config()
+                        }
+                        SettingsEvent::PreviewEqualizer(equalizer) => {
+                            self.set_equalizer(equalizer);
+                            Task::none()
+                        }
+                        SettingsEvent::SetEqualizer(equalizer) => {
+                            self.set_equalizer(equalizer);
+                            self.save_config()
+                        }
+                        SettingsEvent::SaveEqualizer => self.save_config(),
+                        SettingsEvent::ClearCache(kind) => {
+                            let cache = self.cache.clone();
+
+                            Task::future(tokio::task::spawn_blocking(move || {
+                                kind.clear(&cache, &Pins::load())
+                            }))
+                            .then(move |result| {
+                                match result {
+                                    Ok(Ok(())) => log::info!("Cleared the {kind} cache"),
+                                    Ok(Err(err)) => {
+                                        log::error!("Cannot clear the {kind} cache: {err}")
+                                    }
+                                    Err(err) => log::error!("Cannot clear the {kind} cache: {err}"),
+                                }
+
+                                Task::none()
+                            })
+                            .chain(self.fetch_cache_usage())
+                        }
+                        SettingsEvent::SetCacheLimit(limit) => match parse_file_size(&limit) {
+                            Ok(bytes) => {
+                                self.config.audio.cache_limit_size = limit;
+                                self.setup.cache_limit = bytes;
+
+                                Task::batch(vec![
+                                    self.save_config(),
+                                    self.evict_audio_cache().chain(self.fetch_cache_usage()),
+                                ])
+                            }
+                            Err(err) => {
+                                log::error!("Invalid cache limit {limit}: {err}");
+                                Task::none()
+                            }
+                        },
+                        SettingsEvent::Pin(uri) => {
+                            let Some(service) = self.service.clone() else {
+                                log::error!("Pinning an album requires a connection");
+                                return Task::none();
+                            };
+
+                            Task::future(async move {
+                                let pin = service.pin(&uri).await?;
+                                let mut pins = Pins::load();
+                                pins.add(pin);
+                                pins.save()?;
+
+                                anyhow::Ok(pins)
+                            })
+                            .then(|result| match result {
+                                Ok(pins) => Task::done(Message::SettingsMessage(
+                                    SettingsMessage::PinsChanged(pins),
+                                )),
+                                Err(err) => {
+                                    log::error!("Cannot pin the album: {err}");
+                                    Task::none()
+                                }
+                            })
+                        }
+                        SettingsEvent::Unpin(uri) => {
+                            let mut pins = Pins::load();
+                            pins.remove(&uri);
+
+                            if let Err(err) = pins.save() {
+                                log::error!("Cannot unpin the album: {err}");
+                            }
 
-                match welcome.update(welcome_message) {
+                            Task::done(Message::SettingsMessage(SettingsMessage::PinsChanged(pins)))
+                        }
+                        SettingsEvent::Back => self.back(),
+                    },
+                    None => Task::none(),
+                }
+            }
+            Message::QueueMessage(queue_message) => {
+                match self.queue_panel.update(queue_message, &mut self.queue) {
                     Some(event) => match event {
-                        WelcomeEvent::LoginAttempt => todo!(),
-                        WelcomeEvent::ReloadConfigAttempt => {
-                            let config = self.config.clone();
-                            Task::future(async move { config.reload().await })
+                        QueueEvent::SkipTo(index) => match self.queue.skip_to(index) {
+                            Some(track) => self.load(&track),
+                            None => Task::none(),
+                        },
+                        QueueEvent::Replay(track) => self.play(track),
+                        QueueEvent::TogglePlayback => self.toggle_playback(),
+                        QueueEvent::ToggleMiniPlayer => self.toggle_mini_player(),
+                        QueueEvent::Next => self.skip_next(),
+                        QueueEvent::Previous => self.skip_previous(),
+                        QueueEvent::Changed => {
+                            self.preload_next();
+                            Task::none()
+                        }
+                        QueueEvent::ModesChanged => {
+                            self.save_modes();
+                            self.preload_next();
+                            Task::none()
+                        }
+                        QueueEvent::SetVolume(volume) => {
+                            if let Some(service) = self.volume_control() {
+                                service.mixer.set_volume(volume);
+                            }
+                            Task::none()
+                        }
+                        QueueEvent::ScrollVolume(lines) => {
+                            self.step_volume(lines * self.config.audio.scroll_volume_step as f32);
+                            Task::none()
+                        }
+                        QueueEvent::CycleVisualizer => {
+                            self.config.appaerance.visualizer.style =
+                                self.visualizer_style().cycle().to_string();
+                            self.save_config()
                         }
                     },
                     None => Task::none(),
                 }
             }
+            Message::PaletteMessage(palette_message) => {
+                let Some(palette) = &mut self.palette else {
+                    return Task::none();
+                };
+
+                match palette.update(palette_message) {
+                    Some(PaletteEvent::Run(command)) => {
+                        self.palette = None;
+                        self.run(command)
+                    }
+                    Some(PaletteEvent::Close) => {
+                        self.palette = None;
+                        Task::none()
+                    }
+                    Some(PaletteEvent::KeyPressed(key, modifiers)) => Task::done(
+                        Message::OxifyMessage(OxifyMessage::KeyPressed(key, modifiers)),
+                    ),
+                    None => Task::none(),
+                }
+            }
+            Message::LogsMessage(logs_message) => {
+                self.logs.update(logs_message);
+                Task::none()
+            }
         }
     }
 
-    pub fn view(&self, _: window::Id) -> Element<Message> {
-        let content = match &self.screen {
-            Screen::Welcome(welcome) => welcome.view().map(Message::WelcomeMessage),
+    pub fn view(&self, id: window::Id) -> Element<Message> {
+        if self.mini_player == Some(id) {
+            return mini_player::view(
+                &self.queue,
+                self.position.is_playing(),
+                self.current_cover(),
+            )
+            .map(Message::QueueMessage);
+        }
+
+        let screen = self.router.current();
+
+        let content = match screen {
+            Screen::Welcome => self
+                .welcome
+                .view(self.config.appaerance.font_size)
+                .map(Message::WelcomeMessage),
+            Screen::Home => self
+                .home
+                .view(self.queue.history(), self.library.playlists())
+                .map(Message::HomeMessage),
+            Screen::Library => self.library.view().map(Message::LibraryMessage),
+            Screen::Search => self.search.view().map(Message::SearchMessage),
+            Screen::Settings => self
+                .settings
+                .view(&self.config, self.setup.player_config.normalisation)
+                .map(Message::SettingsMessage),
+            Screen::Logs => self.logs.view().map(Message::LogsMessage),
+            Screen::Album(id) => self.album.view(id).map(Message::AlbumMessage),
+            Screen::Artist(id) => self.artist.view(id).map(Message::ArtistMessage),
+            Screen::Playlist(id) => self.playlist.view(id).map(Message::PlaylistMessage),
+        };
+
+        // Every screen but the welcome one sits in the shell
+        let content = if *screen == Screen::Welcome {
+            content
+        } else {
+            let queue_panel = self.show_queue.then(|| {
+                self.queue_panel
+                    .view(&self.queue, self.current_cover())
+                    .map(Message::QueueMessage)
+            });
+
+            let player_bar = player_bar::view(
+                &self.queue,
+                self.position.is_playing(),
+                self.position.current_ms(),
+                self.volume_control().map(|service| service.mixer.volume()),
+                self.config
+                    .appaerance
+                    .visualizer
+                    .enabled
+                    .then(|| (&self.visualizer, self.visualizer_style())),
+                self.current_cover(),
+            )
+            .map(Message::QueueMessage);
+
+            column![
+                row![
+                    sidebar::view(&self.router, self.show_queue).map(Message::SidebarMessage),
+                    content
+                ]
+                .push_maybe(queue_panel)
+                .height(Length::Fill),
+                player_bar,
+            ]
+            .into()
         };
 
-        container(content).into()
+        let warnings = [&self.theme_warning, &self.font_warning]
+            .into_iter()
+            .flatten()
+            .chain(&self.keymap_warnings)
+            .map(|warning| {
+                container(text(warning).style(ui::text::error))
+                    .padding([4, 10])
+                    .width(Length::Fill)
+                    .into()
+            });
+
+        let content = container(column![].extend(warnings).push(content));
+
+        match &self.palette {
+            Some(palette) => stack![content, palette.view().map(Message::PaletteMessage)].into(),
+            None => content.into(),
+        }
     }
 
     pub fn theme(&self, _window: window::Id) -> Theme {
-        self.config.appaerance.get_theme()
+        self.theme.clone()
+    }
+
+    pub fn scale_factor(&self, _window: window::Id) -> f64 {
+        self.config
+            .appaerance
+            .scale_factor
+            .clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end())
+    }
+
+    /// Sets the scale factor and saves it, rounded to a tenth so repeated steps do not drift.
+    fn zoom(&mut self, scale_factor: f64) -> Task<Message> {
+        let scale_factor =
+            ((scale_factor * 10.0).round() / 10.0).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+
+        if scale_factor == self.config.appaerance.scale_factor {
+            return Task::none();
+        }
+
+        self.config.appaerance.scale_factor = scale_factor;
+        self.save_config()
+    }
+
+    /// Loads the configured theme, falling back to the default one with a warning.
+    fn apply_theme(&mut self) {
+        let appaerance = &self.config.appaerance;
+
+        (self.theme, self.theme_warning) = match appaerance.get_theme(self.desktop_scheme) {
+            Ok(theme) => (theme, None),
+            Err(err) => {
+                log::warn!("{err}");
+                (
+                    Theme::GruvboxDark,
+                    Some(format!("{err}, using Gruvbox Dark")),
+                )
+            }
+        };
+        self.theme_scheme = appaerance
+            .is_auto_theme()
+            .then(|| appaerance.scheme(self.desktop_scheme).ok())
+            .flatten();
+    }
+
+    /// Loads the configured font files, then switches to their families or falls back.
+    fn load_fonts(&self) -> Task<Message> {
+        let files = self.config.appaerance.font_files.clone();
+
+        Task::future(font::read(files)).then(|result| match result {
+            Ok(fonts) => Task::batch(fonts.into_iter().map(iced::font::load))
+                .collect()
+                .map(|_| Message::OxifyMessage(OxifyMessage::FontsLoaded(None))),
+            Err(err) => {
+                let warning = format!("{err}, using the system monospace font");
+                Task::done(Message::OxifyMessage(OxifyMessage::FontsLoaded(Some(
+                    warning,
+                ))))
+            }
+        })
+    }
+
+    /// Switches between the light and dark themes when auto and the scheme changed.
+    fn follow_scheme(&mut self) {
+        let appaerance = &self.config.appaerance;
+
+        if appaerance.is_auto_theme()
+            && appaerance.scheme(self.desktop_scheme).ok() != self.theme_scheme
+        {
+            self.apply_theme();
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        // The palette takes the keys over from the screen below it
+        let screen = match (&self.palette, self.router.current()) {
+            (Some(palette), _) => palette.subscription().map(Message::PaletteMessage),
+            (None, Screen::Search) => self.search.subscription().map(Message::SearchMessage),
+            (None, _) => Subscription::none(),
+        };
+
+        let progress = if self.position.is_playing() {
+            iced::time::every(PROGRESS_INTERVAL)
+                .map(|_| Message::OxifyMessage(OxifyMessage::ProgressTick))
+        } else {
+            Subscription::none()
+        };
+
+        let queue = if self.show_queue {
+            self.queue_panel.subscription().map(Message::QueueMessage)
+        } else {
+            Subscription::none()
+        };
+
+        let connection = if self.logged_in() && !self.reconnecting {
+            iced::time::every(CONNECTION_CHECK_INTERVAL)
+                .map(|_| Message::OxifyMessage(OxifyMessage::ConnectionTick))
+        } else {
+            Subscription::none()
+        };
+
+        let history = if self.logged_in() {
+            event::listen_with(history_buttons)
+        } else {
+            Subscription::none()
+        };
+
+        // Only redraw while there is something to see
+        let visualizer = &self.config.appaerance.visualizer;
+        let visualizer = if visualizer.enabled && self.position.is_playing() {
+            iced::time::every(Duration::from_secs(1) / visualizer.fps.clamp(1, 60))
+                .map(|_| Message::OxifyMessage(OxifyMessage::VisualizerTick))
+        } else {
+            Subscription::none()
+        };
+
+        // The schedule is only needed when the desktop has no preference
+        let scheme = if self.config.appaerance.is_auto_theme() {
+            let schedule = match self.desktop_scheme {
+                Some(_) => Subscription::none(),
+                None => iced::time::every(THEME_SCHEDULE_INTERVAL)
+                    .map(|_| Message::OxifyMessage(OxifyMessage::ThemeTick)),
+            };
+
+            Subscription::batch(vec![
+                portal::color_scheme()
+                    .map(|scheme| Message::OxifyMessage(OxifyMessage::DesktopScheme(scheme))),
+                schedule,
+            ])
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![
+            screen,
+            visualizer,
+            progress,
+            queue,
+            connection,
+            history,
+            scheme,
+            keyboard::on_key_press(|key, modifiers| {
+                Some(Message::OxifyMessage(OxifyMessage::KeyPressed(
+                    key, modifiers,
+                )))
+            }),
+            iced::time::every(SESSION_SAVE_INTERVAL)
+                .map(|_| Message::OxifyMessage(OxifyMessage::SaveSession)),
+            iced::time::every(MODES_CHECK_INTERVAL)
+                .map(|_| Message::OxifyMessage(OxifyMessage::ModesTick)),
+            window::close_events().map(|id| Message::OxifyMessage(OxifyMessage::WindowClosed(id))),
+            event::listen_with(window_events),
+        ])
+    }
+
+    /// The service, unless the volume is fixed.
+    fn volume_control(&self) -> Option<&Service> {
+        self.service
+            .as_ref()
+            .filter(|_| !self.setup.connect_config.disable_volume)
+    }
+
+    fn step_volume(&self, percent: f32) {
+        if let Some(service) = self.volume_control() {
+            let mixer = &service.mixer;
+            mixer.set_volume(volume::step(mixer.volume(), percent));
+        }
+    }
+
+    /// Starts using `service`, the returned task forwards the events of its players.
+    fn attach(&mut self, service: Service) -> Task<Message> {
+        let player_events = UnboundedReceiverStream::new(service.player.get_player_event_channel());
+        let local_events = UnboundedReceiverStream::new(service.local.get_event_channel());
+        service
+            .visualizer
+            .set_enabled(self.config.appaerance.visualizer.enabled);
+        self.service = Some(service);
+        self.restore_volume();
+
+        Task::batch(vec![
+            Task::stream(player_events)
+                .map(|event| Message::OxifyMessage(OxifyMessage::Player(event))),
+            Task::stream(local_events)
+                .map(|event| Message::OxifyMessage(OxifyMessage::Local(event))),
+        ])
+    }
+
+    /// Falls back to the cached library once the session drops, and keeps trying
+    /// to connect again while offline.
+    fn check_connection(&mut self) -> Task<Message> {
+        match &self.service {
+            Some(service) if service.session.is_invalid() => {
+                log::warn!("Lost the connection to Spotify, browsing the cached library");
+
+                service.pause();
+                self.service = None;
+                self.position = Position::paused(self.position.current_ms());
+
+                Task::none()
+            }
+            Some(_) => Task::none(),
+            None => {
+                self.reconnecting = true;
+                let setup = self.setup.clone();
+
+                Task::future(async move { Service::connect(setup).await }).then(|result| {
+                    match result {
+                        Ok(service) => {
+                            Task::done(Message::OxifyMessage(OxifyMessage::Reconnected(service)))
+                        }
+                        Err(err) => {
+                            log::debug!("Cannot reconnect to Spotify: {err}");
+                            Task::done(Message::OxifyMessage(OxifyMessage::ReconnectFailed))
+                        }
+                    }
+                })
+            }
+        }
+    }
+
+    fn restore_volume(&self) {
+        let Some(service) = self.volume_control() else {
+            return;
+        };
+
+        let volume = Session::load()
+            .filter(|_| self.config.audio.remember_volume)
+            .map_or(self.setup.connect_config.initial_volume, |session| {
+                session.volume
+            });
+
+        service.mixer.set_volume(volume);
+    }
+
+    fn visualizer_style(&self) -> visualizer::Style {
+        visualizer::Style::from_str(&self.config.appaerance.visualizer.style).unwrap_or_default()
+    }
+
+    fn logged_in(&self) -> bool {
+        *self.router.current() != Screen::Welcome
     }
+
+    /// Leaves the welcome screen for the home one, loading the library behind it.
+    fn open_shell(&mut self) -> Task<Message> {
+        self.router = Router::new(Screen::Home);
+        self.library = Library::new();
+
+        Task::batch(vec![
+            self.fetch_playlists(),
+            self.fetch_library(Selection::Section(Section::LikedSongs)),
+        ])
+    }
+
+    /// Goes to `screen`, remembering the current one to come back to.
+    fn open(&mut self, screen: Screen) -> Task<Message> {
+        if !self.logged_in() || screen == Screen::Welcome {
+            return Task::none();
+        }
+
+        if self.router.push(screen.clone()) {
+            self.entered(&screen)
+        } else {
+            Task::none()
+        }
+    }
+
+    fn back(&mut self) -> Task<Message> {
+        match self.router.back() {
+            Some(screen) => self.entered(&screen),
+            None => Task::none(),
+        }
+    }
+
+    fn forward(&mut self) -> Task<Message> {
+        match self.router.forward() {
+            Some(screen) => self.entered(&screen),
+            None => Task::none(),
+        }
+    }
+
+    /// Refreshes what `screen` shows that may have changed while away from it.
+    fn entered(&self, screen: &Screen) -> Task<Message> {
+        match screen {
+            Screen::Search => text_input::focus(text_input::Id::new(search::INPUT_ID)),
+            Screen::Settings => self.fetch_cache_usage(),
+            Screen::Album(id) if !self.album.contains(id) => self.fetch_album(id.clone()),
+            Screen::Artist(id) if !self.artist.contains(id) => self.fetch_artist(id.clone()),
+            // The table is shared by every playlist, it would keep the offset of the last one
+            Screen::Playlist(id) => Task::batch(vec![
+                scrollable::snap_to(
+                    scrollable::Id::new(playlist::TABLE_ID),
+                    RelativeOffset::START,
+                ),
+                if self.playlist.contains(id) {
+                    Task::none()
+                } else {
+                    self.fetch_playlist(id.clone())
+                },
+            ]),
+            Screen::Welcome
+            | Screen::Home
+            | Screen::Library
+            | Screen::Logs
+            | Screen::Album(_)
+            | Screen::Artist(_) => Task::none(),
+        }
+    }
+
+    /// Shows the page of the album, artist or playlist `uri` names.
+    fn open_uri(&mut self, uri: &str) -> Task<Message> {
+        let screen = match parse_uri(uri) {
+            Some(("album", id)) => Screen::Album(id.to_string()),
+            Some(("artist", id)) => Screen::Artist(id.to_string()),
+            Some(("playlist", id)) => Screen::Playlist(id.to_string()),
+            _ => {
+                log::warn!("There is no page for {uri}");
+                return Task::none();
+            }
+        };
+
+        self.open(screen)
+    }
+
+    fn play(&mut self, track: Track) -> Task<Message> {
+        self.queue.play_now(track.clone());
+
+        self.load(&track)
+    }
+
+    /// Replaces the queue context with `tracks` and plays the one at `index`.
+    fn play_context(
+        &mut self,
+        uri: Option<String>,
+        tracks: Vec<Track>,
+        index: usize,
+    ) -> Task<Message> {
+        match self.queue.play_context(uri, tracks, index) {
+            Some(track) => {
+                let task = self.load(&track);
+                self.preload_next();
+                task
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Loads `track`, which must already be the current track of the queue.
+    fn load(&self, track: &Track) -> Task<Message> {
+        if let Some(service) = &self.service {
+            service.crossfade.begin_track(track.duration_ms);
+        }
+
+        self.start(track)
+    }
+
+    /// Loads `track` once the previous one ended, it fades in the end of the previous
+    /// one when the crossfade was told it comes next.
+    fn continue_with(&self, track: &Track) -> Task<Message> {
+        if let Some(service) = &self.service {
+            if !service.crossfade.advance(track.duration_ms) {
+                service.crossfade.begin_track(track.duration_ms);
+            }
+        }
+
+        self.start(track)
+    }
+
+    fn start(&self, track: &Track) -> Task<Message> {
+        if let Some(service) = &self.service {
+            self.announce_next();
+
+            if let Err(err) = service.play(&track.uri) {
+                log::warn!("Cannot play {}: {err}", track.name);
+            }
+        }
+
+        self.fetch_cover(track)
+    }
+
+    fn perform(&mut self, action: Action) -> Task<Message> {
+        let logged_in = self.logged_in();
+
+        match action {
+            Action::PlayPause => self.toggle_playback(),
+            Action::Next => self.skip_next(),
+            Action::Prev => self.skip_previous(),
+            Action::SeekForward => self.seek(SEEK_STEP_MS),
+            Action::SeekBackward => self.seek(-SEEK_STEP_MS),
+            Action::VolumeUp => {
+                self.step_volume(self.config.audio.volume_step as f32);
+                Task::none()
+            }
+            Action::VolumeDown => {
+                self.step_volume(-(self.config.audio.volume_step as f32));
+                Task::none()
+            }
+            Action::FocusSearch if logged_in => self.open_search(),
+            Action::GoToLibrary => self.open(Screen::Library),
+            Action::ToggleQueue if logged_in => {
+                self.show_queue = !self.show_queue;
+                Task::none()
+            }
+            Action::Back => self.back(),
+            Action::Forward => self.forward(),
+            Action::ReloadConfig => self.reload_config(),
+            Action::ZoomIn => self.zoom(self.config.appaerance.scale_factor + ZOOM_STEP),
+            Action::ZoomOut => self.zoom(self.config.appaerance.scale_factor - ZOOM_STEP),
+            Action::ResetZoom => self.zoom(1.0),
+            Action::CommandPalette => self.toggle_palette(),
+            Action::Up => self.navigate(Navigation::Up),
+            Action::Down => self.navigate(Navigation::Down),
+            Action::First => self.navigate(Navigation::First),
+            Action::Last => self.navigate(Navigation::Last),
+            Action::Activate => self.navigate(Navigation::Play),
+            Action::FocusSearch | Action::ToggleQueue => Task::none(),
+        }
+    }
+
+    /// Moves the cursor of the list on screen, or plays the track under it.
+    fn navigate(&self, navigation: Navigation) -> Task<Message> {
+        match self.router.current() {
+            Screen::Library => Task::done(Message::LibraryMessage(LibraryMessage::Navigate(
+                navigation,
+            ))),
+            Screen::Search => {
+                Task::done(Message::SearchMessage(SearchMessage::Navigate(navigation)))
+            }
+            Screen::Welcome
+            | Screen::Home
+            | Screen::Settings
+            | Screen::Logs
+            | Screen::Album(_)
+            | Screen::Artist(_)
+            | Screen::Playlist(_) => Task::none(),
+        }
+    }
+
+    /// Opens the command palette with every command available right now, or closes it.
+    fn toggle_palette(&mut self) -> Task<Message> {
+        if self.palette.take().is_some() {
+            return Task::none();
+        }
+
+        self.palette = Some(Palette::new(self.commands()));
+
+        text_input::focus(text_input::Id::new(palette::INPUT_ID))
+    }
+
+    fn commands(&self) -> Vec<Command> {
+        let logged_in = self.logged_in();
+
+        // Moving a cursor and the palette itself make no sense from the palette
+        let actions = Action::iter()
+            .filter(|action| {
+                !matches!(
+                    action,
+                    Action::CommandPalette
+                        | Action::Up
+                        | Action::Down
+                        | Action::First
+                        | Action::Last
+                        | Action::Activate
+                )
+            })
+            .filter(|action| {
+                logged_in
+                    || !matches!(
+                        action,
+                        Action::FocusSearch
+                            | Action::GoToLibrary
+                            | Action::ToggleQueue
+                            | Action::Back
+                            | Action::Forward
+                    )
+            })
+            .map(Command::Action);
+
+        let themes = std::iter::once(AUTO_THEME.to_string())
+            .chain(Theme::ALL.iter().map(Theme::to_string))
+            .chain(theme::custom())
+            .map(Command::Theme);
+
+        let mut commands: Vec<Command> = actions.collect();
+        if logged_in {
+            commands.extend(
+                [Screen::Home, Screen::Settings, Screen::Logs]
+                    .into_iter()
+                    .map(Command::Open),
+            );
+        }
+        commands.extend([
+            Command::ToggleMiniPlayer,
+            Command::OpenConfigDir,
+            Command::OpenWebsite,
+        ]);
+        commands.extend(themes);
+
+        if logged_in {
+            // The library keeps the playlists up to date with the cache, no need to read it again
+            commands.extend(Section::iter().map(Command::Section));
+            commands.extend(
+                self.library
+                    .playlists()
+                    .iter()
+                    .map(|playlist| Command::Playlist {
+                        id: playlist.id.clone(),
+                        name: playlist.name.clone(),
+                    }),
+            );
+        }
+
+        commands
+    }
+
+    fn run(&mut self, command: Command) -> Task<Message> {
+        match command {
+            Command::Action(action) => self.perform(action),
+            Command::Open(screen) => self.open(screen),
+            Command::ToggleMiniPlayer => self.toggle_mini_player(),
+            Command::OpenConfigDir => {
+                let _ = open::that_detached(environment::config_dir());
+                Task::none()
+            }
+            Command::OpenWebsite => {
+                let _ = open::that_detached(WEBSITE_URL);
+                Task::none()
+            }
+            Command::Theme(name) => {
+                self.config.appaerance.theme = name;
+                self.apply_theme();
+                self.save_config()
+            }
+            Command::Section(section) => self.open_selection(Selection::Section(section)),
+            Command::Playlist { id, .. } => self.open_selection(Selection::Playlist(id)),
+        }
+    }
+
+    /// Shows `selection` in the library, opening it first when on another screen.
+    fn open_selection(&mut self, selection: Selection) -> Task<Message> {
+        Task::batch(vec![
+            self.open(Screen::Library),
+            Task::done(Message::LibraryMessage(LibraryMessage::Select(selection))),
+        ])
+    }
+
+    /// Focuses the query input even when already searching.
+    fn open_search(&mut self) -> Task<Message> {
+        if *self.router.current() == Screen::Search {
+            text_input::focus(text_input::Id::new(search::INPUT_ID))
+        } else {
+            self.open(Screen::Search)
+        }
+    }
+
+    fn reload_config(&self) -> Task<Message> {
+        let config = self.config.clone();
+
+        Task::future(async move { config.reload().await })
+    }
+
+    fn skip_next(&mut self) -> Task<Message> {
+        match self.queue.next() {
+            Some(track) => self.load(&track),
+            None => {
+                // Skipping past the last track stops like reaching its end
+                if let Some(service) = &self.service {
+                    service.pause();
+                }
+                self.position = Position::paused(0);
+                Task::none()
+            }
+        }
+    }
+
+    fn skip_previous(&mut self) -> Task<Message> {
+        match self.queue.previous() {
+            Some(track) => self.load(&track),
+            None => Task::none(),
+        }
+    }
+
+    /// Moves the position of the current track by `delta_ms`.
+    fn seek(&self, delta_ms: i64) -> Task<Message> {
+        let (Some(service), Some(track)) = (&self.service, self.queue.current()) else {
+            return Task::none();
+        };
+
+        let position_ms = (self.position.current_ms() as i64 + delta_ms)
+            .clamp(0, track.duration_ms as i64) as u32;
+
+        service
+            .crossfade
+            .begin_track(track.duration_ms.saturating_sub(position_ms));
+        self.announce_next();
+        service.seek(position_ms);
+
+        Task::none()
+    }
+
+    /// Opens the always on top mini player where it was last, or closes it.
+    fn toggle_mini_player(&mut self) -> Task<Message> {
+        if let Some(id) = self.mini_player.take() {
+            return window::close(id);
+        }
+
+        let (id, open) = window::open(window::Settings {
+            size: mini_player::SIZE,
+            position: self
+                .window_state
+                .mini_player()
+                .map_or(window::Position::Default, window::Position::Specific),
+            resizable: false,
+            level: window::Level::AlwaysOnTop,
+            exit_on_close_request: true,
+            ..Default::default()
+        });
+        self.mini_player = Some(id);
+
+        let position = self.window_state.mini_player();
+        open.then(move |id| fit_mini_player(id, position))
+    }
+
+    /// A local track that already ended is opened again, from the start.
+    fn toggle_playback(&mut self) -> Task<Message> {
+        let (Some(service), Some(track)) = (&self.service, self.queue.current()) else {
+            return Task::none();
+        };
+
+        if self.position.is_playing() {
+            service.pause();
+            Task::none()
+        } else if track.is_local() && !service.local.is_loaded() {
+            self.load(&track.clone())
+        } else {
+            service.resume();
+            Task::none()
+        }
+    }
+
+    /// Loads `track` paused at `position_ms`, same as [`Self::load`] otherwise.
+    fn cue(&self, track: &Track, position_ms: u32) {
+        let Some(service) = &self.service else {
+            return;
+        };
+
+        service
+            .crossfade
+            .begin_track(track.duration_ms.saturating_sub(position_ms));
+        self.announce_next();
+        if let Err(err) = service.cue(&track.uri, position_ms) {
+            log::warn!("Cannot resume {}: {err}", track.name);
+        }
+    }
+
+    /// Crossfade between `track` and the one queued after it, consecutive tracks
+    /// of the same album are played gapless instead.
+    fn crossfade_after(&self, track: &Track) -> Duration {
+        let same_album = self
+            .queue
+            .peek()
+            .is_some_and(|next| !track.album.is_empty() && next.album == track.album);
+
+        if self.config.audio.gapless && same_album && self.queue.shuffle() == Shuffle::Off {
+            Duration::ZERO
+        } else {
+            self.setup.crossfade
+        }
+    }
+
+    /// Tells the crossfade which track follows the current one, its end is held back
+    /// from then on to be mixed into the start of the next.
+    fn announce_next(&self) {
+        let (Some(service), Some(track)) = (&self.service, self.queue.current()) else {
+            return;
+        };
+
+        service.crossfade.set_next(
+            self.queue.peek().map(|next| next.duration_ms),
+            self.crossfade_after(track),
+        );
+    }
+
+    fn preload_next(&self) {
+        self.announce_next();
+
+        let (Some(service), Some(next)) = (&self.service, self.queue.peek()) else {
+            return;
+        };
+
+        if let Err(err) = service.preload(&next.uri) {
+            log::warn!("Cannot preload {}: {err}", next.name);
+        }
+    }
+
+    /// The dsp stage lives in the player thread, equalizer changes don't need a new player.
+    fn set_equalizer(&mut self, equalizer: Equalizer) {
+        self.config.audio.equalizer = equalizer.clone();
+        self.setup.equalizer = equalizer;
+
+        if let Some(service) = &self.service {
+            service.dsp.set(&self.setup.equalizer);
+        }
+    }
+
+    fn save_config(&mut self) -> Task<Message> {
+        let config = self.config.clone();
+        let previous = std::mem::replace(&mut self.saved_config, config.clone());
+
+        Task::future(async move { config.save(&previous).await }).then(|result| {
+            if let Err(err) = result {
+                log::error!("Cannot save the config: {err}");
+            }
+
+            Task::none()
+        })
+    }
+
+    fn restore_session(&mut self) {
+        // Nothing can be cued without a player
+        if self.service.is_none() {
+            return;
+        }
+        let Some(session) = Session::load() else {
+            return;
+        };
+
+        // The modes file is the source of truth, it may have been changed from the
+        // command line since the session was saved
+        let modes = self.queue.modes();
+        self.queue = session.queue;
+        self.queue.set_modes(modes);
+        self.position = Position::paused(session.position_ms);
+
+        if let Some(track) = self.queue.current() {
+            log::info!("Restoring {} at {} ms", track.name, session.position_ms);
+
+            self.cue(track, session.position_ms);
+        }
+
+        self.preload_next();
+    }
+
+    /// The session to resume from, `None` until something could have been played.
+    fn session(&self) -> Option<Session> {
+        // Without a connection nothing has been played, keep the previous session intact
+        let service = self.service.as_ref()?;
+
+        Some(Session {
+            queue: self.queue.clone(),
+            position_ms: self.position.current_ms(),
+            volume: service.mixer.volume(),
+        })
+    }
+
+    /// Writes the session in the background, unless nothing changed since the last save.
+    fn save_session(&mut self) -> Task<Message> {
+        let Some(session) = self.session() else {
+            return Task::none();
+        };
+
+        if self.saved_session.as_ref() == Some(&session) {
+            return Task::none();
+        }
+        self.saved_session = Some(session.clone());
+
+        Task::future(tokio::task::spawn_blocking(move || session.save())).then(|result| {
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => log::warn!("Cannot save the session: {err}"),
+                Err(err) => log::warn!("Cannot save the session: {err}"),
+            }
+            Task::none()
+        })
+    }
+
+    fn save_window_state(&self) {
+        if let Err(err) = self.window_state.save() {
+            log::warn!("Cannot save the window state: {err}");
+        }
+    }
+
+    fn save_modes(&mut self) {
+        if let Err(err) = self.queue.modes().save() {
+            log::warn!("Cannot save playback modes: {err}");
+        }
+        self.modes_modified = Modes::modified();
+    }
+
+    /// Reads the modes file again once it is newer than what this instance last saw.
+    fn check_modes(&self) -> Task<Message> {
+        let seen = self.modes_modified;
+
+        Task::future(tokio::task::spawn_blocking(move || {
+            let modified = Modes::modified().filter(|modified| Some(*modified) > seen)?;
+
+            Some((modified, Modes::load()))
+        }))
+        .then(|result| match result {
+            Ok(Some((modified, modes))) => Task::done(Message::OxifyMessage(
+                OxifyMessage::ModesChanged(modified, modes),
+            )),
+            Ok(None) => Task::none(),
+            Err(err) => {
+                log::warn!("Cannot check the playback modes: {err}");
+                Task::none()
+            }
+        })
+    }
+
+    fn handle_player_event(&mut self, event: PlayerEvent) -> Task<Message> {
+        match event {
+            PlayerEvent::Playing { position_ms, .. } => {
+                self.position = Position::playing(position_ms);
+                Task::none()
+            }
+            PlayerEvent::Paused { position_ms, .. } => {
+                self.position = Position::paused(position_ms);
+                Task::none()
+            }
+            PlayerEvent::Seeked { position_ms, .. }
+            | PlayerEvent::PositionCorrection { position_ms, .. } => {
+                self.position = if self.position.is_playing() {
+                    Position::playing(position_ms)
+                } else {
+                    Position::paused(position_ms)
+                };
+                Task::none()
+            }
+            PlayerEvent::Stopped { .. } => {
+                self.position = Position::paused(0);
+                Task::none()
+            }
+            PlayerEvent::TimeToPreloadNextTrack { .. } => {
+                self.preload_next();
+                Task::none()
+            }
+            PlayerEvent::EndOfTrack { .. } => {
+                Task::batch(vec![self.end_of_track(), self.evict_after_track()])
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn end_of_track(&mut self) -> Task<Message> {
+        let modes = self.queue.modes();
+        let next = self.queue.advance();
+
+        // Wrapping a shuffled context around picks a new seed
+        if self.queue.modes() != modes {
+            self.save_modes();
+        }
+
+        match next {
+            Some(next) => self.continue_with(&next),
+            None => Task::none(),
+        }
+    }
+
+    /// Loads the cover of the track in every size, unless it is the one already shown.
+    fn fetch_cover(&self, track: &Track) -> Task<Message> {
+        let Some(url) = cover::url(&track.images, cover::Size::Large) else {
+            return Task::none();
+        };
+        if self.cover.as_ref().is_some_and(|cover| cover.url == url) {
+            return Task::none();
+        }
+
+        Task::future(cover::load(self.client.clone(), track.images.clone())).then(|result| {
+            match result {
+                Ok(cover) => Task::done(Message::OxifyMessage(OxifyMessage::CoverLoaded(cover))),
+                Err(err) => {
+                    log::warn!("Cannot load the cover: {err}");
+                    Task::none()
+                }
+            }
+        })
+    }
+
+    fn current_cover(&self) -> Option<&Cover> {
+        let url = cover::url(&self.queue.current()?.images, cover::Size::Large)?;
+
+        self.cover.as_ref().filter(|cover| cover.url == url)
+    }
+
+    fn evict_after_track(&mut self) -> Task<Message> {
+        if !storage::eviction_due(self.evicted_at, Instant::now()) {
+            return Task::none();
+        }
+
+        self.evict_audio_cache()
+    }
+
+    /// Keeps the audio cache under its limit, librespot only ever adds files to it.
+    fn evict_audio_cache(&mut self) -> Task<Message> {
+        self.evicted_at = Some(Instant::now());
+        let limit = self.setup.cache_limit;
+
+        Task::future(tokio::task::spawn_blocking(move || {
+            storage::evict(limit, &Pins::load())
+        }))
+        .then(|result| {
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(freed)) => log::info!("Evicted {} of audio", storage::format_size(freed)),
+                Ok(Err(err)) => log::warn!("Cannot evict the audio cache: {err}"),
+                Err(err) => log::warn!("Cannot evict the audio cache: {err}"),
+            }
+
+            Task::none()
+        })
+    }
+
+    fn fetch_cache_usage(&self) -> Task<Message> {
+        let cache = self.cache.clone();
+
+        Task::future(tokio::task::spawn_blocking(move || {
+            Kind::iter()
+                .map(|kind| (kind, kind.usage(&cache)))
+                .collect()
+        }))
+        .then(|result| match result {
+            Ok(usage) => Task::done(Message::SettingsMessage(
+                SettingsMessage::CacheUsageFetched(usage),
+            )),
+            Err(err) => {
+                log::error!("Cannot measure the cache: {err}");
+                Task::none()
+            }
+        })
+    }
+
+    fn search(&self, query: String, groups: Vec<Group>, offset: u32) -> Task<Message> {
+        let Some(service) = self.service.clone() else {
+            return Task::done(Message::SearchMessage(SearchMessage::Failed(query)));
+        };
+        let client = self.client.clone();
+        let searched = query.clone();
+
+        let local: Vec<Track> = if offset == 0 {
+            local::search(&self.local, &query).cloned().collect()
+        } else {
+            Vec::new()
+        };
+
+        Task::future(async move {
+            let api = Api::new(client, &service).await?;
+            let mut results = api.search(&query, &groups, offset).await?;
+
+            let message = match results.pop() {
+                Some((group, more)) if offset > 0 => SearchMessage::MoreFetched(query, group, more),
+                last => {
+                    let mut results: Vec<_> = results.into_iter().chain(last).collect();
+
+                    // Local matches go first, spotify has plenty of results already
+                    if let Some((_, tracks)) = results
+                        .iter_mut()
+                        .find(|(group, _)| *group == Group::Tracks)
+                    {
+                        tracks.total += local.len() as u32;
+                        tracks.items.splice(0..0, local);
+                    }
+
+                    SearchMessage::ResultsFetched(query, results)
+                }
+            };
+
+            anyhow::Ok(message)
+        })
+        .then(|result| match result {
+            Ok(message) => Task::done(Message::SearchMessage(message)),
+            Err(err) => {
+                log::error!("Cannot search: {err}");
+                Task::done(Message::SearchMessage(SearchMessage::Failed(
+                    searched.clone(),
+                )))
+            }
+        })
+    }
+
+    /// Renders the cached playlists right away, then syncs them.
+    fn fetch_playlists(&self) -> Task<Message> {
+        let cache = self.cache.clone();
+        let cached =
+            Task::future(tokio::task::spawn_blocking(move || cache.playlists())).then(|result| {
+                match result {
+                    Ok(Ok(playlists)) if !playlists.is_empty() => Task::done(
+                        Message::LibraryMessage(LibraryMessage::PlaylistsFetched(playlists)),
+                    ),
+                    Ok(Ok(_)) => Task::none(),
+                    Ok(Err(err)) => {
+                        log::warn!("Cannot read the cached playlists: {err}");
+                        Task::none()
+                    }
+                    Err(err) => {
+                        log::warn!("Cannot read the cached playlists: {err}");
+                        Task::none()
+                    }
+                }
+            });
+
+        let Some(service) = self.service.clone() else {
+            return cached;
+        };
+        let client = self.client.clone();
+        let cache = self.cache.clone();
+
+        let synced = Task::future(async move {
+            let api = Api::new(client, &service).await?;
+            sync::playlists(&api, &cache).await
+        })
+        .then(|result| match result {
+            Ok((playlists, updated)) => {
+                let messages = std::iter::once(LibraryMessage::PlaylistsFetched(playlists)).chain(
+                    updated
+                        .into_iter()
+                        .map(|(id, tracks)| LibraryMessage::PlaylistFetched(id, tracks)),
+                );
+
+                Task::batch(messages.map(|message| Task::done(Message::LibraryMessage(message))))
+            }
+            Err(err) => {
+                log::error!("Cannot fetch playlists: {err}");
+                Task::none()
+            }
+        });
+
+        // The stored ones come first, a late read would hide the synced ones
+        cached.chain(synced)
+    }
+
+    /// Renders the cached tracks right away, then fetches them unless a playlist did not change.
+    fn fetch_library(&self, selection: Selection) -> Task<Message> {
+        if selection == Selection::Section(Section::Local) {
+            return Task::done(Message::LibraryMessage(LibraryMessage::SectionFetched(
+                Section::Local,
+                self.local.clone(),
+            )));
+        }
+
+        let cache = self.cache.clone();
+        let read = selection.clone();
+        let cached = Task::future(tokio::task::spawn_blocking(move || {
+            cache
+                .tracks(&read)
+                .map(|tracks| tracks.map(|tracks| fetched(read, tracks)))
+        }))
+        .then(|result| match result {
+            Ok(Ok(Some(message))) => Task::done(Message::LibraryMessage(message)),
+            Ok(Ok(None)) => Task::none(),
+            Ok(Err(err)) => {
+                log::warn!("Cannot read the cached library: {err}");
+                Task::none()
+            }
+            Err(err) => {
+                log::warn!("Cannot read the cached library: {err}");
+                Task::none()
+            }
+        });
+
+        let Some(service) = self.service.clone() else {
+            return cached;
+        };
+        let client = self.client.clone();
+        let cache = self.cache.clone();
+
+        let synced = Task::future(async move {
+            let api = Api::new(client, &service).await?;
+            let tracks = sync::tracks(&api, &cache, &selection).await?;
+
+            anyhow::Ok(tracks.map(|tracks| fetched(selection, tracks)))
+        })
+        .then(|result| match result {
+            Ok(Some(message)) => Task::done(Message::LibraryMessage(message)),
+            Ok(None) => Task::none(),
+            Err(err) => {
+                log::error!("Cannot fetch library: {err}");
+                Task::none()
+            }
+        });
+
+        // The stored ones come first, a late read would hide the synced ones
+        cached.chain(synced)
+    }
+
+    fn fetch_album(&self, id: String) -> Task<Message> {
+        let Some(service) = self.service.clone() else {
+            return Task::done(Message::AlbumMessage(AlbumMessage::Failed(
+                id,
+                Failure::Offline,
+            )));
+        };
+        let client = self.client.clone();
+        let failed = id.clone();
+
+        Task::future(async move {
+            let album = Api::new(client, &service).await?.album(&id).await?;
+
+            anyhow::Ok(AlbumMessage::Fetched(id, album))
+        })
+        .then(move |result| match result {
+            Ok(message) => Task::done(Message::AlbumMessage(message)),
+            Err(err) => {
+                log::error!("Cannot fetch the album: {err}");
+                Task::done(Message::AlbumMessage(AlbumMessage::Failed(
+                    failed.clone(),
+                    Failure::Error,
+                )))
+            }
+        })
+    }
+
+    fn fetch_artist(&self, id: String) -> Task<Message> {
+        let Some(service) = self.service.clone() else {
+            return Task::done(Message::ArtistMessage(ArtistMessage::Failed(
+                id,
+                Failure::Offline,
+            )));
+        };
+        let client = self.client.clone();
+        let failed = id.clone();
+
+        Task::future(async move {
+            let artist = Api::new(client, &service).await?.artist(&id).await?;
+
+            anyhow::Ok(ArtistMessage::Fetched(id, artist))
+        })
+        .then(move |result| match result {
+            Ok(message) => Task::done(Message::ArtistMessage(message)),
+            Err(err) => {
+                log::error!("Cannot fetch the artist: {err}");
+                Task::done(Message::ArtistMessage(ArtistMessage::Failed(
+                    failed.clone(),
+                    Failure::Error,
+                )))
+            }
+        })
+    }
+
+    fn follow_artist(&self, id: String, follow: bool) -> Task<Message> {
+        let Some(service) = self.service.clone() else {
+            return Task::none();
+        };
+        let client = self.client.clone();
+
+        Task::future(async move {
+            Api::new(client, &service)
+                .await?
+                .follow_artist(&id, follow)
+                .await?;
+
+            anyhow::Ok(ArtistMessage::Followed(id, follow))
+        })
+        .then(|result| match result {
+            Ok(message) => Task::done(Message::ArtistMessage(message)),
+            Err(err) => {
+                log::error!("Cannot change whether the artist is followed: {err}");
+                Task::none()
+            }
+        })
+    }
+
+    fn fetch_playlist(&self, id: String) -> Task<Message> {
+        let Some(service) = self.service.clone() else {
+            return Task::done(Message::PlaylistMessage(PlaylistMessage::Failed(
+                id,
+                Failure::Offline,
+            )));
+        };
+        let client = self.client.clone();
+        let failed = id.clone();
+
+        Task::future(async move {
+            let playlist = Api::new(client, &service).await?.playlist(&id).await?;
+
+            anyhow::Ok(PlaylistMessage::Fetched(id, playlist))
+        })
+        .then(move |result| match result {
+            Ok(message) => Task::done(Message::PlaylistMessage(message)),
+            Err(err) => {
+                log::error!("Cannot fetch the playlist: {err}");
+                Task::done(Message::PlaylistMessage(PlaylistMessage::Failed(
+                    failed.clone(),
+                    Failure::Error,
+                )))
+            }
+        })
+    }
+}
+
+fn fetched(selection: Selection, tracks: Vec<Track>) -> LibraryMessage {
+    match selection {
+        Selection::Section(section) => LibraryMessage::SectionFetched(section, tracks),
+        Selection::Playlist(id) => LibraryMessage::PlaylistFetched(id, tracks),
+    }
+}
+
+fn window_events(event: Event, _status: event::Status, id: window::Id) -> Option<Message> {
+    let change = match event {
+        Event::Window(window::Event::Resized(size)) => Change::Resized(size),
+        Event::Window(window::Event::Moved(position)) => Change::Moved(position),
+        _ => return None,
+    };
+
+    Some(Message::OxifyMessage(OxifyMessage::WindowChanged(
+        id, change,
+    )))
+}
+
+/// The back and forward buttons of the mouse go through the history of the router.
+fn history_buttons(event: Event, _status: event::Status, _id: window::Id) -> Option<Message> {
+    let message = match event {
+        Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Back)) => SidebarMessage::Back,
+        Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Forward)) => {
+            SidebarMessage::Forward
+        }
+        _ => return None,
+    };
+
+    Some(Message::SidebarMessage(message))
+}
+
+/// Fits the restored window to the monitor it opened on, then maximizes it again.
+fn restore_window(id: window::Id, state: WindowState) -> Task<Message> {
+    let maximize = if state.maximized {
+        window::maximize(id, true)
+    } else {
+        Task::none()
+    };
+
+    window::monitor_size(id)
+        .then(move |monitor| {
+            let Some(monitor) = monitor else {
+                // The window ended up on no monitor at all
+                return window::move_to(id, Point::ORIGIN);
+            };
+
+            let fitted = state.clamp(MIN_SIZE, Some(monitor));
+            let resize = if fitted.size != state.size {
+                window::resize(id, fitted.size())
+            } else {
+                Task::none()
+            };
+            let reposition = if fitted.position != state.position {
+                window::move_to(id, fitted.position().unwrap_or(Point::ORIGIN))
+            } else {
+                Task::none()
+            };
+
+            Task::batch([resize, reposition])
+        })
+        .chain(maximize)
+}
+
+/// Brings the mini player back on the monitor it opened on, it may have been left on another.
+fn fit_mini_player(id: window::Id, position: Option<Point>) -> Task<Message> {
+    let Some(position) = position else {
+        return Task::none();
+    };
+
+    window::monitor_size(id).then(move |monitor| {
+        let fitted = monitor.map_or(Point::ORIGIN, |monitor| {
+            window_state::fit(position, mini_player::SIZE, monitor)
+        });
+
+        if fitted != position {
+            window::move_to(id, fitted)
+        } else {
+            Task::none()
+        }
+    })
+}
+
+/// Refreshes the local files index in the background, unchanged files are not read again.
+fn scan_local_files(directories: Vec<PathBuf>) -> Task<Message> {
+    if directories.is_empty() {
+        return Task::none();
+    }
+
+    Task::future(tokio::task::spawn_blocking(move || {
+        let mut index = local::Index::load();
+        index.scan(&directories);
+
+        if let Err(err) = index.save() {
+            log::warn!("Cannot save the local files index: {err}");
+        }
+
+        index.tracks()
+    }))
+    .then(|result| match result {
+        Ok(tracks) => Task::done(Message::OxifyMessage(OxifyMessage::LocalScanned(tracks))),
+        Err(err) => {
+            log::error!("Cannot scan the local files: {err}");
+            Task::none()
+        }
+    })
 }
@@ -1,9 +1,91 @@
+pub mod album;
+pub mod artist;
+pub mod home;
+pub mod library;
+pub mod logs;
+pub mod mini_player;
+pub mod palette;
+pub mod player_bar;
+pub mod playlist;
+pub mod queue;
+pub mod router;
+pub mod search;
+pub mod settings;
+pub mod sidebar;
+pub mod track_list;
 pub mod welcome;
 
+pub use album::Album;
+pub use album::AlbumEvent;
+pub use artist::Artist;
+pub use artist::ArtistEvent;
+pub use home::Home;
+pub use home::HomeEvent;
+pub use library::Library;
+pub use library::LibraryEvent;
+pub use logs::Logs;
+pub use palette::Palette;
+pub use palette::PaletteEvent;
+pub use playlist::Playlist;
+pub use playlist::PlaylistEvent;
+pub use queue::QueueEvent;
+pub use queue::QueuePanel;
+pub use router::Router;
+pub use search::Search;
+pub use search::SearchEvent;
+pub use settings::Settings;
+pub use settings::SettingsEvent;
 pub use welcome::Welcome;
 pub use welcome::WelcomeEvent;
 
-#[derive(Clone)]
+use crate::{appaerance as ui, data::detail::Failure, widget::text};
+use iced::{
+    alignment,
+    widget::{button, column, container},
+    Element, Length,
+};
+
+/// Screens of the main window. Their state lives in `Oxify`, so it is kept while away.
+#[derive(Debug, Clone, PartialEq, Eq, strum::Display)]
 pub enum Screen {
-    Welcome(welcome::Welcome),
+    Welcome,
+    Home,
+    Search,
+    Library,
+    Settings,
+    Logs,
+    /// Detail pages, by the id of what they show.
+    Album(String),
+    Artist(String),
+    Playlist(String),
+}
+
+/// Placeholder of a screen whose content could not be fetched, until `retry` is pressed.
+pub fn failed<'a, Message: Clone + 'a>(failure: Failure, retry: Message) -> Element<'a, Message> {
+    let content = column![
+        text(failure.describe()).style(ui::text::secondary),
+        button(text("Retry"))
+            .padding([4, 10])
+            .style(|theme, status| ui::button::secondary(theme, status, false))
+            .on_press(retry),
+    ]
+    .spacing(8)
+    .align_x(alignment::Horizontal::Center);
+
+    container(content)
+        .align_x(alignment::Horizontal::Center)
+        .align_y(alignment::Vertical::Center)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+/// Placeholder of a screen whose content is still being fetched.
+pub fn loading<'a, Message: 'a>() -> Element<'a, Message> {
+    container(text("Loading...").style(ui::text::secondary))
+        .align_x(alignment::Horizontal::Center)
+        .align_y(alignment::Vertical::Center)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
 }
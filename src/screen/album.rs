@@ -0,0 +1,133 @@
+use crate::data::{
+    detail::{AlbumDetails, Failure},
+    font,
+    library::{Column, Track},
+    messages::AlbumMessage,
+};
+use iced::{
+    widget::{column, container, horizontal_rule, mouse_area, scrollable, vertical_space},
+    Element, Length,
+};
+use std::collections::HashMap;
+
+use super::track_list;
+use crate::{
+    appaerance::{self as ui, icon::Icon},
+    widget::{self, text},
+};
+
+const COLUMNS: [Column; 3] = [Column::Title, Column::Artist, Column::Duration];
+
+/// Albums opened so far, by id, so going back to one does not fetch it again.
+#[derive(Debug, Default, Clone)]
+pub struct Album {
+    albums: HashMap<String, AlbumDetails>,
+    /// Albums that could not be fetched, until they are retried.
+    failures: HashMap<String, Failure>,
+}
+
+pub enum AlbumEvent {
+    Play(Option<String>, Vec<Track>, usize),
+    OpenUri(String),
+    Fetch(String),
+}
+
+impl Album {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.albums.contains_key(id)
+    }
+
+    pub fn update(&mut self, message: AlbumMessage) -> Option<AlbumEvent> {
+        match message {
+            AlbumMessage::Fetched(id, album) => {
+                self.failures.remove(&id);
+                self.albums.insert(id, album);
+
+                None
+            }
+            AlbumMessage::Failed(id, failure) => {
+                self.failures.insert(id, failure);
+
+                None
+            }
+            AlbumMessage::Retry(id) => {
+                self.failures.remove(&id);
+
+                Some(AlbumEvent::Fetch(id))
+            }
+            AlbumMessage::Play(id, index) => {
+                let album = self.albums.get(&id)?;
+
+                Some(AlbumEvent::Play(
+                    Some(album.uri.clone()),
+                    album.tracks.clone(),
+                    index,
+                ))
+            }
+            AlbumMessage::OpenUri(uri) => Some(AlbumEvent::OpenUri(uri)),
+        }
+    }
+
+    pub fn view(&self, id: &str) -> Element<AlbumMessage> {
+        let Some(album) = self.albums.get(id) else {
+            return match self.failures.get(id) {
+                Some(failure) => super::failed(*failure, AlbumMessage::Retry(id.to_string())),
+                None => super::loading(),
+            };
+        };
+
+        let facts = [
+            album.release_date.clone(),
+            album.label.clone(),
+            format!("{} tracks", album.tracks.len()),
+            album.duration(),
+        ]
+        .into_iter()
+        .filter(|fact| !fact.is_empty())
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+        let header = column![
+            text(album.name.clone()).font(font::HEADING.get()),
+            widget::link(
+                album.artist.clone(),
+                (!album.artist_uri.is_empty())
+                    .then(|| AlbumMessage::OpenUri(album.artist_uri.clone())),
+            ),
+            text(facts).style(ui::text::secondary),
+            widget::labeled_button(Icon::Play, "Play all").on_press_maybe(
+                (!album.tracks.is_empty()).then(|| AlbumMessage::Play(id.to_string(), 0))
+            ),
+        ]
+        .spacing(6);
+
+        let rows = album
+            .tracks
+            .iter()
+            .enumerate()
+            .fold(column![], |column, (index, track)| {
+                column.push(
+                    mouse_area(track_list::cells(track, &COLUMNS, AlbumMessage::OpenUri))
+                        .on_double_click(AlbumMessage::Play(id.to_string(), index)),
+                )
+            });
+
+        let content = column![
+            header,
+            vertical_space().height(10),
+            track_list::header(&COLUMNS, None, None),
+            horizontal_rule(1),
+            scrollable(rows).height(Length::Fill),
+        ];
+
+        container(content)
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
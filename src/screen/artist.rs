@@ -0,0 +1,195 @@
+use crate::data::{
+    detail::{ArtistDetails, Failure},
+    font,
+    library::{Column, Track},
+    messages::ArtistMessage,
+};
+use iced::{
+    alignment,
+    widget::{button, column, container, mouse_area, row, scrollable, vertical_space},
+    Element, Length,
+};
+use std::collections::HashMap;
+
+use super::track_list;
+use crate::{
+    appaerance::{self as ui, icon::Icon},
+    widget::{self, text},
+};
+
+const COLUMNS: [Column; 3] = [Column::Title, Column::Album, Column::Duration];
+
+/// Artists opened so far, by id, so going back to one does not fetch it again.
+#[derive(Debug, Default, Clone)]
+pub struct Artist {
+    artists: HashMap<String, ArtistDetails>,
+    /// Artists that could not be fetched, until they are retried.
+    failures: HashMap<String, Failure>,
+}
+
+pub enum ArtistEvent {
+    Play(Option<String>, Vec<Track>, usize),
+    Follow(String, bool),
+    OpenUri(String),
+    Fetch(String),
+}
+
+impl Artist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.artists.contains_key(id)
+    }
+
+    pub fn update(&mut self, message: ArtistMessage) -> Option<ArtistEvent> {
+        match message {
+            ArtistMessage::Fetched(id, artist) => {
+                self.failures.remove(&id);
+                self.artists.insert(id, artist);
+
+                None
+            }
+            ArtistMessage::Failed(id, failure) => {
+                self.failures.insert(id, failure);
+
+                None
+            }
+            ArtistMessage::Retry(id) => {
+                self.failures.remove(&id);
+
+                Some(ArtistEvent::Fetch(id))
+            }
+            ArtistMessage::Play(id, index) => {
+                let artist = self.artists.get(&id)?;
+
+                Some(ArtistEvent::Play(
+                    Some(artist.uri.clone()),
+                    artist.top_tracks.clone(),
+                    index,
+                ))
+            }
+            ArtistMessage::ToggleFollow(id) => {
+                let following = self.artists.get(&id)?.following?;
+
+                Some(ArtistEvent::Follow(id, !following))
+            }
+            ArtistMessage::Followed(id, following) => {
+                if let Some(artist) = self.artists.get_mut(&id) {
+                    artist.following = Some(following);
+                }
+
+                None
+            }
+            ArtistMessage::OpenUri(uri) => Some(ArtistEvent::OpenUri(uri)),
+        }
+    }
+
+    pub fn view(&self, id: &str) -> Element<ArtistMessage> {
+        let Some(artist) = self.artists.get(id) else {
+            return match self.failures.get(id) {
+                Some(failure) => super::failed(*failure, ArtistMessage::Retry(id.to_string())),
+                None => super::loading(),
+            };
+        };
+
+        let facts = std::iter::once(format!("{} followers", artist.followers))
+            .chain((!artist.genres.is_empty()).then(|| artist.genres.join(", ")))
+            .collect::<Vec<_>>()
+            .join(" · ");
+
+        let follow_button = artist.following.map(|following| {
+            button(text(if following { "Following" } else { "Follow" }))
+                .padding([4, 10])
+                .style(move |theme, status| ui::button::secondary(theme, status, following))
+                .on_press(ArtistMessage::ToggleFollow(id.to_string()))
+        });
+
+        let header = column![
+            text(artist.name.clone()).font(font::HEADING.get()),
+            text(facts).style(ui::text::secondary),
+            row![
+                widget::labeled_button(Icon::Play, "Play all").on_press_maybe(
+                    (!artist.top_tracks.is_empty()).then(|| ArtistMessage::Play(id.to_string(), 0))
+                ),
+            ]
+            .push_maybe(follow_button)
+            .spacing(8),
+        ]
+        .spacing(6);
+
+        let top_tracks = artist.top_tracks.iter().enumerate().fold(
+            column![track_list::header(&COLUMNS, None, None)],
+            |column, (index, track)| {
+                column.push(
+                    mouse_area(track_list::cells(track, &COLUMNS, ArtistMessage::OpenUri))
+                        .on_double_click(ArtistMessage::Play(id.to_string(), index)),
+                )
+            },
+        );
+
+        let discography =
+            artist
+                .discography
+                .iter()
+                .fold(column![].spacing(10), |column, (group, releases)| {
+                    let releases = releases
+                        .iter()
+                        .fold(column![].spacing(2), |column, release| {
+                            column.push(
+                                row![
+                                    text(release.year().to_string())
+                                        .style(ui::text::secondary)
+                                        .width(50),
+                                    widget::link(
+                                        release.name.clone(),
+                                        Some(ArtistMessage::OpenUri(release.uri.clone())),
+                                    ),
+                                ]
+                                .spacing(8)
+                                .align_y(alignment::Vertical::Center),
+                            )
+                        });
+
+                    column.push(
+                        column![text(group.to_string()).font(font::HEADING.get()), releases]
+                            .spacing(4),
+                    )
+                });
+
+        let mut content = column![
+            header,
+            vertical_space().height(10),
+            text("Popular").font(font::HEADING.get()),
+            top_tracks,
+            vertical_space().height(10),
+            text("Discography").font(font::HEADING.get()),
+            discography,
+        ]
+        .spacing(6);
+
+        if !artist.related.is_empty() {
+            let related = artist
+                .related
+                .iter()
+                .fold(column![].spacing(2), |column, related| {
+                    column.push(widget::link(
+                        related.name.clone(),
+                        Some(ArtistMessage::OpenUri(related.uri.clone())),
+                    ))
+                });
+
+            content = content
+                .push(vertical_space().height(10))
+                .push(text("Fans Also Like").font(font::HEADING.get()))
+                .push(related);
+        }
+
+        container(scrollable(content).height(Length::Fill))
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
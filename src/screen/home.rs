@@ -0,0 +1,123 @@
+use crate::data::{
+    font,
+    library::{Playlist, Section, Selection, Track},
+    messages::HomeMessage,
+};
+use iced::{
+    alignment,
+    widget::{button, column, container, row, scrollable, text::Wrapping},
+    Element, Length,
+};
+use strum::IntoEnumIterator;
+
+use super::track_list;
+use crate::{appaerance as ui, widget::text};
+
+const RECENT_TRACKS: usize = 10;
+
+#[derive(Debug, Default, Clone)]
+pub struct Home;
+
+pub enum HomeEvent {
+    Replay(usize),
+    Open(Selection),
+    OpenUri(String),
+}
+
+impl Home {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn update(&mut self, message: HomeMessage) -> Option<HomeEvent> {
+        match message {
+            HomeMessage::Replay(index) => Some(HomeEvent::Replay(index)),
+            HomeMessage::Open(selection) => Some(HomeEvent::Open(selection)),
+            HomeMessage::OpenUri(uri) => Some(HomeEvent::OpenUri(uri)),
+        }
+    }
+
+    /// The tracks played last from the queue `history`, and shortcuts to the library.
+    pub fn view<'a>(
+        &self,
+        history: &'a [Track],
+        playlists: &'a [Playlist],
+    ) -> Element<'a, HomeMessage> {
+        let recent: Element<HomeMessage> = if history.is_empty() {
+            text("Nothing played yet.")
+                .style(ui::text::secondary)
+                .into()
+        } else {
+            history
+                .iter()
+                .enumerate()
+                .rev()
+                .take(RECENT_TRACKS)
+                .fold(column![].spacing(2), |column, (index, track)| {
+                    column.push(track_row(index, track))
+                })
+                .into()
+        };
+
+        let shortcuts = Section::iter()
+            .map(|section| (section.to_string(), Selection::Section(section)))
+            .chain(playlists.iter().map(|playlist| {
+                (
+                    playlist.name.clone(),
+                    Selection::Playlist(playlist.id.clone()),
+                )
+            }))
+            .fold(column![].spacing(2), |column, (label, selection)| {
+                column.push(
+                    button(text(label).wrapping(Wrapping::None))
+                        .padding([4, 10])
+                        .width(Length::Fill)
+                        .style(|theme, status| ui::button::secondary(theme, status, false))
+                        .on_press(HomeMessage::Open(selection)),
+                )
+            });
+
+        let content = row![
+            column![
+                text("Recently Played").font(font::HEADING.get()),
+                scrollable(recent).height(Length::Fill),
+            ]
+            .spacing(8)
+            .width(Length::FillPortion(2)),
+            column![
+                text("Your Library").font(font::HEADING.get()),
+                scrollable(shortcuts).height(Length::Fill),
+            ]
+            .spacing(8)
+            .width(Length::FillPortion(1)),
+        ]
+        .spacing(20);
+
+        container(content)
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+fn track_row(index: usize, track: &Track) -> Element<'_, HomeMessage> {
+    let content = row![
+        container(column![
+            text(track.name.clone()).wrapping(Wrapping::None),
+            track_list::byline(track, HomeMessage::OpenUri),
+        ])
+        .width(Length::Fill)
+        .clip(true),
+        text(track.duration()),
+    ]
+    .spacing(10)
+    .align_y(alignment::Vertical::Center);
+
+    button(content)
+        .padding([4, 8])
+        .width(Length::Fill)
+        .style(|theme, status| ui::button::secondary(theme, status, false))
+        .on_press(HomeMessage::Replay(index))
+        .into()
+}
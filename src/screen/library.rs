@@ -0,0 +1,334 @@
+use crate::data::{
+    font,
+    library::{Column, Playlist, Section, Selection, Sort, Track},
+    messages::LibraryMessage,
+    search::Navigation,
+};
+use iced::{
+    alignment,
+    widget::{
+        button, column, container, horizontal_rule, mouse_area, row, scrollable, text::Wrapping,
+        vertical_space,
+    },
+    Element, Length,
+};
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+
+use super::track_list::{self, ROW_HEIGHT};
+use crate::{
+    appaerance::{
+        self as ui,
+        icon::{icon, Icon},
+    },
+    widget::text,
+};
+
+const COLUMNS: [Column; 5] = [
+    Column::Title,
+    Column::Artist,
+    Column::Album,
+    Column::Duration,
+    Column::DateAdded,
+];
+const SIDEBAR_WIDTH: f32 = 220.0;
+pub const TABLE_ID: &str = "library-table";
+
+#[derive(Debug, Clone)]
+pub struct Library {
+    playlists: Vec<Playlist>,
+    tracks: HashMap<Selection, Vec<Track>>,
+    selected: Selection,
+    sort: Sort,
+    offset: f32,
+    viewport_height: f32,
+    /// Row moved to with the keyboard, in the sorted tracks of the selection.
+    cursor: Option<usize>,
+}
+
+/// Followed artists are listed as tracks, but their rows open the artist instead of playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Track,
+    Artist,
+}
+
+pub enum LibraryEvent {
+    Fetch(Selection),
+    Play(Option<String>, Vec<Track>, usize),
+    Enqueue(Track),
+    OpenUri(String),
+    /// Vertical offset the table has to scroll to for the cursor to be visible.
+    ScrollTo(f32),
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        Self {
+            playlists: Vec::new(),
+            tracks: HashMap::new(),
+            selected: Selection::Section(Section::LikedSongs),
+            sort: Sort::default(),
+            offset: 0.0,
+            viewport_height: 0.0,
+            cursor: None,
+        }
+    }
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, message: LibraryMessage) -> Option<LibraryEvent> {
+        use LibraryMessage as LM;
+
+        match message {
+            LM::PlaylistsFetched(playlists) => {
+                self.playlists = playlists;
+
+                None
+            }
+            LM::SectionFetched(section, tracks) => {
+                self.insert(Selection::Section(section), tracks);
+
+                None
+            }
+            LM::PlaylistFetched(id, tracks) => {
+                self.insert(Selection::Playlist(id), tracks);
+
+                None
+            }
+            LM::Select(selection) => {
+                self.selected = selection.clone();
+                self.offset = 0.0;
+                self.cursor = None;
+
+                (!self.tracks.contains_key(&selection)).then_some(LibraryEvent::Fetch(selection))
+            }
+            LM::SortBy(column) => {
+                self.sort = if self.sort.column == column {
+                    Sort {
+                        column,
+                        ascending: !self.sort.ascending,
+                    }
+                } else {
+                    Sort {
+                        column,
+                        ascending: true,
+                    }
+                };
+
+                let sort = self.sort;
+                self.cursor = None;
+                self.tracks
+                    .values_mut()
+                    .for_each(|tracks| sort.apply(tracks));
+
+                None
+            }
+            LM::Scrolled(viewport) => {
+                self.offset = viewport.absolute_offset().y;
+                self.viewport_height = viewport.bounds().height;
+
+                None
+            }
+            LM::Play(index) if self.row() == Row::Artist => self.update(LM::Open(index)),
+            LM::Play(index) => {
+                let tracks = self.tracks.get(&self.selected)?;
+                let uri = match &self.selected {
+                    Selection::Playlist(id) => Some(format!("spotify:playlist:{id}")),
+                    Selection::Section(_) => None,
+                };
+
+                Some(LibraryEvent::Play(uri, tracks.clone(), index))
+            }
+            LM::Enqueue(_) if self.row() == Row::Artist => None,
+            LM::Enqueue(index) => self
+                .tracks
+                .get(&self.selected)
+                .and_then(|tracks| tracks.get(index))
+                .cloned()
+                .map(LibraryEvent::Enqueue),
+            LM::Open(index) => self
+                .tracks
+                .get(&self.selected)
+                .and_then(|artists| artists.get(index))
+                .map(|artist| LibraryEvent::OpenUri(artist.uri.clone())),
+            LM::OpenUri(uri) => Some(LibraryEvent::OpenUri(uri)),
+            LM::Navigate(navigation) => self.navigate(navigation),
+        }
+    }
+
+    fn navigate(&mut self, navigation: Navigation) -> Option<LibraryEvent> {
+        let last = self.tracks.get(&self.selected)?.len().checked_sub(1)?;
+
+        let cursor = match (navigation, self.cursor) {
+            (Navigation::Up, Some(cursor)) => cursor.saturating_sub(1),
+            (Navigation::Down, Some(cursor)) => (cursor + 1).min(last),
+            (Navigation::Up | Navigation::Down | Navigation::First, _) => 0,
+            (Navigation::Last, _) => last,
+            (Navigation::Play, Some(cursor)) => return self.update(LibraryMessage::Play(cursor)),
+            (Navigation::Queue, Some(cursor)) => {
+                return self.update(LibraryMessage::Enqueue(cursor))
+            }
+            _ => return None,
+        };
+        self.cursor = Some(cursor);
+
+        // Scrolls just enough for the whole row to be visible
+        let top = cursor as f32 * ROW_HEIGHT;
+
+        if top < self.offset {
+            Some(LibraryEvent::ScrollTo(top))
+        } else if top + ROW_HEIGHT > self.offset + self.viewport_height {
+            Some(LibraryEvent::ScrollTo(
+                top + ROW_HEIGHT - self.viewport_height,
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn playlists(&self) -> &[Playlist] {
+        &self.playlists
+    }
+
+    pub fn view(&self) -> Element<LibraryMessage> {
+        container(row![self.sidebar(), self.table()])
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn row(&self) -> Row {
+        match self.selected {
+            Selection::Section(Section::Artists) => Row::Artist,
+            _ => Row::Track,
+        }
+    }
+
+    fn insert(&mut self, selection: Selection, mut tracks: Vec<Track>) {
+        self.sort.apply(&mut tracks);
+        self.tracks.insert(selection, tracks);
+    }
+
+    fn sidebar(&self) -> Element<LibraryMessage> {
+        let entry = |label: String, selection: Selection| {
+            let selected = self.selected == selection;
+            let heart = (selection == Selection::Section(Section::LikedSongs))
+                .then(|| icon(Icon::Heart).style(ui::text::action));
+
+            button(row![].push_maybe(heart).push(text(label)).spacing(6))
+                .padding([4, 10])
+                .width(Length::Fill)
+                .style(move |theme, status| ui::button::secondary(theme, status, selected))
+                .on_press(LibraryMessage::Select(selection))
+        };
+
+        let sections = Section::iter().fold(column![].spacing(2), |column, section| {
+            column.push(entry(section.to_string(), Selection::Section(section)))
+        });
+
+        let playlists = self
+            .playlists
+            .iter()
+            .fold(column![].spacing(2), |column, playlist| {
+                column.push(entry(
+                    playlist.name.clone(),
+                    Selection::Playlist(playlist.id.clone()),
+                ))
+            });
+
+        let content = column![]
+            .spacing(4)
+            .push(text("Library").font(font::HEADING.get()))
+            .push(sections)
+            .push(vertical_space().height(10))
+            .push(text("Playlists").font(font::HEADING.get()))
+            .push(scrollable(playlists).height(Length::Fill));
+
+        container(content)
+            .padding(10)
+            .width(SIDEBAR_WIDTH)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn table(&self) -> Element<LibraryMessage> {
+        let Some(tracks) = self.tracks.get(&self.selected) else {
+            return super::loading();
+        };
+
+        let kind = self.row();
+        let header = match kind {
+            Row::Track => {
+                track_list::header(&COLUMNS, Some(self.sort), Some(&LibraryMessage::SortBy))
+            }
+            Row::Artist => container(text("Artist").font(font::HEADING.get()))
+                .padding([4, 0])
+                .into(),
+        };
+
+        let rows = track_list::rows(tracks.len(), self.offset, self.viewport_height, |index| {
+            let selected = self.cursor == Some(index);
+
+            match kind {
+                Row::Track => track_row(index, &tracks[index], selected),
+                Row::Artist => artist_row(index, &tracks[index], selected),
+            }
+        });
+
+        let content = column![].push(header).push(horizontal_rule(1)).push(
+            scrollable(rows)
+                .id(scrollable::Id::new(TABLE_ID))
+                .on_scroll(LibraryMessage::Scrolled)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        );
+
+        container(content)
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+fn track_row(index: usize, track: &Track, selected: bool) -> Element<LibraryMessage> {
+    let content = track_list::cells(track, &COLUMNS, LibraryMessage::OpenUri);
+
+    mouse_area(highlight(content, selected))
+        .on_double_click(LibraryMessage::Play(index))
+        .on_right_press(LibraryMessage::Enqueue(index))
+        .into()
+}
+
+/// Artists have no album or duration to show, only their name.
+fn artist_row(index: usize, artist: &Track, selected: bool) -> Element<LibraryMessage> {
+    let content = row![text(artist.name.clone()).wrapping(Wrapping::None)]
+        .height(ROW_HEIGHT)
+        .align_y(alignment::Vertical::Center);
+
+    mouse_area(highlight(content, selected))
+        .on_double_click(LibraryMessage::Open(index))
+        .into()
+}
+
+fn highlight<'a>(
+    content: impl Into<Element<'a, LibraryMessage>>,
+    selected: bool,
+) -> Element<'a, LibraryMessage> {
+    container(content)
+        .style(move |theme| {
+            if selected {
+                container::Style::default()
+                    .background(theme.extended_palette().secondary.weak.color)
+            } else {
+                container::Style::default()
+            }
+        })
+        .width(Length::Fill)
+        .into()
+}
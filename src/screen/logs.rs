@@ -0,0 +1,93 @@
+use crate::data::{
+    font,
+    log::{Level, Record},
+    messages::LogsMessage,
+};
+use iced::{
+    alignment,
+    widget::{button, column, container, horizontal_space, row, scrollable, text::Style},
+    Element, Length, Theme,
+};
+use std::collections::VecDeque;
+
+use crate::{appaerance as ui, widget::text};
+
+/// Records kept in memory, the log file has all of them.
+const LIMIT: usize = 1000;
+
+#[derive(Debug, Default, Clone)]
+pub struct Logs {
+    records: VecDeque<Record>,
+}
+
+impl Logs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, records: Vec<Record>) {
+        self.records.extend(records);
+
+        let overflow = self.records.len().saturating_sub(LIMIT);
+        self.records.drain(..overflow);
+    }
+
+    pub fn update(&mut self, message: LogsMessage) {
+        match message {
+            LogsMessage::Clear => self.records.clear(),
+        }
+    }
+
+    /// Newest records first.
+    pub fn view(&self) -> Element<LogsMessage> {
+        let clear_button = button(text("Clear"))
+            .padding([2, 10])
+            .style(|theme, status| ui::button::secondary(theme, status, false))
+            .on_press_maybe((!self.records.is_empty()).then_some(LogsMessage::Clear));
+
+        let header = row![
+            text("Logs").font(font::HEADING.get()),
+            horizontal_space(),
+            clear_button
+        ]
+        .align_y(alignment::Vertical::Center);
+
+        let records = self
+            .records
+            .iter()
+            .rev()
+            .fold(column![].spacing(2), |column, record| {
+                column.push(record_row(record))
+            });
+
+        container(column![header, scrollable(records).height(Length::Fill)].spacing(10))
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+fn record_row(record: &Record) -> Element<'_, LogsMessage> {
+    let style: fn(&Theme) -> Style = match record.level {
+        Level::Error => ui::text::error,
+        Level::Warn => ui::text::action,
+        Level::Info => ui::text::primary,
+        Level::Debug | Level::Trace => ui::text::secondary,
+    };
+
+    row![
+        text(
+            record
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S")
+                .to_string()
+        )
+        .style(ui::text::secondary),
+        text(record.level.to_string()).width(50).style(style),
+        text(record.message.clone()),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,49 @@
+use crate::data::{cover::Cover, messages::QueueMessage, queue::Queue};
+use iced::{
+    alignment,
+    widget::{column, container, row, text::Wrapping},
+    Element, Length, Size,
+};
+
+use super::queue::{cover_art, transport};
+use crate::{appaerance as ui, widget::text};
+
+pub const SIZE: Size = Size::new(360.0, 88.0);
+const COVER_SIZE: f32 = 64.0;
+
+/// Cover, title and transport of the current track, it sends the same messages as the queue panel.
+pub fn view<'a>(
+    queue: &'a Queue,
+    playing: bool,
+    cover: Option<&'a Cover>,
+) -> Element<'a, QueueMessage> {
+    let title = match queue.current() {
+        Some(track) => column![
+            text(track.name.clone())
+                .style(ui::text::action)
+                .wrapping(Wrapping::None),
+            text(track.artist.clone())
+                .style(ui::text::secondary)
+                .wrapping(Wrapping::None),
+        ],
+        None => column![text("Nothing playing").style(ui::text::secondary)],
+    };
+
+    let content = row![
+        cover_art(cover, COVER_SIZE),
+        container(column![title, transport(queue, playing)].spacing(6))
+            .width(Length::Fill)
+            .clip(true),
+    ]
+    .spacing(10)
+    .align_y(alignment::Vertical::Center);
+
+    let color = cover.and_then(|cover| cover.color);
+
+    container(content)
+        .padding(12)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(move |theme| ui::container::tinted(theme, color))
+        .into()
+}
@@ -0,0 +1,176 @@
+use crate::data::{
+    font,
+    messages::PaletteMessage,
+    palette::{self, Command, Recent},
+    search::Navigation,
+};
+use iced::{
+    alignment, event,
+    keyboard::{self, key::Named, Key},
+    widget::{button, column, container, mouse_area, opaque, row, text::Wrapping, text_input},
+    window, Element, Event, Length, Subscription,
+};
+
+use crate::{appaerance as ui, widget::text};
+
+pub const INPUT_ID: &str = "palette-input";
+const WIDTH: f32 = 480.0;
+const MAX_RESULTS: usize = 12;
+
+/// Overlay running any command by name, opened above the current screen.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    query: String,
+    commands: Vec<Command>,
+    recent: Recent,
+    /// Indices of the commands matching the query, best first.
+    matches: Vec<usize>,
+    cursor: usize,
+}
+
+pub enum PaletteEvent {
+    Run(Command),
+    Close,
+    KeyPressed(Key, keyboard::Modifiers),
+}
+
+impl Palette {
+    pub fn new(commands: Vec<Command>) -> Self {
+        let recent = Recent::load();
+        let matches = palette::rank(&commands, "", &recent);
+
+        Self {
+            query: String::new(),
+            commands,
+            recent,
+            matches,
+            cursor: 0,
+        }
+    }
+
+    pub fn update(&mut self, message: PaletteMessage) -> Option<PaletteEvent> {
+        use PaletteMessage as PM;
+
+        match message {
+            PM::QueryChanged(query) => {
+                self.matches = palette::rank(&self.commands, &query, &self.recent);
+                self.query = query;
+                self.cursor = 0;
+
+                None
+            }
+            PM::Navigate(navigation) => {
+                let last = self.matches.len().min(MAX_RESULTS).saturating_sub(1);
+
+                self.cursor = match navigation {
+                    Navigation::Up => self.cursor.saturating_sub(1),
+                    Navigation::Down => (self.cursor + 1).min(last),
+                    Navigation::First => 0,
+                    Navigation::Last => last,
+                    _ => self.cursor,
+                };
+
+                None
+            }
+            PM::Submit => self.run(self.cursor),
+            PM::Run(position) => self.run(position),
+            PM::Close => Some(PaletteEvent::Close),
+            PM::KeyPressed(key, modifiers) => Some(PaletteEvent::KeyPressed(key, modifiers)),
+        }
+    }
+
+    pub fn view(&self) -> Element<PaletteMessage> {
+        let input = text_input("Type a command", &self.query)
+            .font(font::BODY.get())
+            .id(text_input::Id::new(INPUT_ID))
+            .on_input(PaletteMessage::QueryChanged)
+            .on_submit(PaletteMessage::Submit)
+            .padding(8);
+
+        let results: Element<PaletteMessage> = if self.matches.is_empty() {
+            text("No matching command")
+                .style(ui::text::secondary)
+                .into()
+        } else {
+            self.matches
+                .iter()
+                .take(MAX_RESULTS)
+                .enumerate()
+                .fold(column![].spacing(2), |column, (position, index)| {
+                    column.push(self.command_row(position, &self.commands[*index]))
+                })
+                .into()
+        };
+
+        let panel = container(column![input, results].spacing(8))
+            .padding(10)
+            .width(WIDTH)
+            .style(ui::container::panel);
+
+        // Clicking anywhere around the panel closes it
+        let backdrop = container(opaque(panel))
+            .center_x(Length::Fill)
+            .height(Length::Fill)
+            .padding([60, 0])
+            .style(ui::container::backdrop);
+
+        opaque(mouse_area(backdrop).on_press(PaletteMessage::Close))
+    }
+
+    /// Escape and the arrows are listened to even while the query input has focus,
+    /// the other keys it takes are passed on for the keybindings.
+    pub fn subscription(&self) -> Subscription<PaletteMessage> {
+        event::listen_with(keys)
+    }
+
+    fn command_row<'a>(&self, position: usize, command: &Command) -> Element<'a, PaletteMessage> {
+        let selected = position == self.cursor;
+
+        let content = row![container(text(command.label()).wrapping(Wrapping::None))
+            .width(Length::Fill)
+            .clip(true)]
+        .push_maybe(
+            self.recent
+                .age(command)
+                .map(|_| text("recent").style(ui::text::secondary)),
+        )
+        .spacing(10)
+        .align_y(alignment::Vertical::Center);
+
+        button(content)
+            .padding([4, 8])
+            .width(Length::Fill)
+            .style(move |theme, status| ui::button::secondary(theme, status, selected))
+            .on_press(PaletteMessage::Run(position))
+            .into()
+    }
+
+    /// Remembers the command at `position` of the matches before running it.
+    fn run(&mut self, position: usize) -> Option<PaletteEvent> {
+        let command = self.commands.get(*self.matches.get(position)?)?.clone();
+
+        self.recent.push(&command);
+        if let Err(err) = self.recent.save() {
+            log::warn!("Cannot save the recent commands: {err}");
+        }
+
+        Some(PaletteEvent::Run(command))
+    }
+}
+
+fn keys(event: Event, status: event::Status, _window: window::Id) -> Option<PaletteMessage> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event else {
+        return None;
+    };
+
+    match key.as_ref() {
+        Key::Named(Named::Escape) => Some(PaletteMessage::Close),
+        Key::Named(Named::ArrowUp) => Some(PaletteMessage::Navigate(Navigation::Up)),
+        Key::Named(Named::ArrowDown) => Some(PaletteMessage::Navigate(Navigation::Down)),
+        // The keys nothing took already reach the keybindings
+        _ if status == event::Status::Captured => {
+            Some(PaletteMessage::KeyPressed(key.clone(), modifiers))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,94 @@
+use crate::data::{
+    cover::Cover,
+    library::format_ms,
+    messages::QueueMessage,
+    queue::Queue,
+    visualizer::{Frame, Style},
+};
+use iced::{
+    alignment,
+    widget::{column, container, mouse_area, progress_bar, row, text::Wrapping},
+    Element, Length,
+};
+
+use super::queue::{cover_art, modes, transport, volume_slider};
+use crate::{
+    appaerance as ui,
+    widget::{self, text},
+};
+
+const COVER_SIZE: f32 = 48.0;
+const VOLUME_WIDTH: f32 = 200.0;
+
+/// Current track, transport, progress and volume, shown below every screen. It sends
+/// the same messages as the queue panel.
+///
+/// `volume` is only given when it can be changed and `visualizer` while it is enabled,
+/// clicking it cycles its style.
+pub fn view<'a>(
+    queue: &'a Queue,
+    playing: bool,
+    position_ms: u32,
+    volume: Option<u16>,
+    visualizer: Option<(&'a Frame, Style)>,
+    cover: Option<&'a Cover>,
+) -> Element<'a, QueueMessage> {
+    let current = queue.current();
+
+    let title = match current {
+        Some(track) => column![
+            text(track.name.clone())
+                .style(ui::text::action)
+                .wrapping(Wrapping::None),
+            text(track.artist.clone())
+                .style(ui::text::secondary)
+                .wrapping(Wrapping::None),
+        ],
+        None => column![text("Nothing playing").style(ui::text::secondary)],
+    };
+
+    let duration_ms = current.map_or(0, |track| track.duration_ms);
+    let position_ms = position_ms.min(duration_ms);
+
+    let progress = row![
+        text(format_ms(position_ms)).size(12),
+        progress_bar(0.0..=duration_ms.max(1) as f32, position_ms as f32).height(4),
+        text(format_ms(duration_ms)).size(12),
+    ]
+    .spacing(8)
+    .align_y(alignment::Vertical::Center);
+
+    let controls = column![
+        row![transport(queue, playing), modes(queue)].spacing(12),
+        progress,
+    ]
+    .spacing(4)
+    .align_x(alignment::Horizontal::Center);
+
+    let content = row![
+        cover_art(cover, COVER_SIZE),
+        container(title).width(Length::FillPortion(1)).clip(true),
+        container(controls).width(Length::FillPortion(2)),
+    ]
+    .push_maybe(visualizer.map(|(frame, style)| {
+        container(
+            mouse_area(widget::visualizer(frame, style)).on_press(QueueMessage::CycleVisualizer),
+        )
+        .width(Length::FillPortion(1))
+    }))
+    .push(
+        container(row![].push_maybe(volume.map(volume_slider)))
+            .width(VOLUME_WIDTH)
+            .align_x(alignment::Horizontal::Right),
+    )
+    .spacing(12)
+    .align_y(alignment::Vertical::Center);
+
+    let color = cover.and_then(|cover| cover.color);
+
+    container(content)
+        .padding([8, 12])
+        .width(Length::Fill)
+        .style(move |theme| ui::container::tinted(theme, color))
+        .into()
+}
@@ -0,0 +1,240 @@
+use crate::data::{
+    detail::{Failure, PlaylistDetails},
+    font,
+    library::{Column, Sort, Track},
+    local,
+    messages::PlaylistMessage,
+};
+use iced::{
+    alignment,
+    widget::{
+        column, container, horizontal_rule, horizontal_space, mouse_area, row, scrollable,
+        text::Wrapping, text_input, vertical_space,
+    },
+    Element, Length,
+};
+use std::collections::HashMap;
+
+use super::track_list;
+use crate::{
+    appaerance::{self as ui, icon::Icon},
+    widget::{self, text},
+};
+
+const COLUMNS: [Column; 5] = [
+    Column::Title,
+    Column::Artist,
+    Column::Album,
+    Column::Duration,
+    Column::DateAdded,
+];
+const FILTER_WIDTH: f32 = 240.0;
+pub const TABLE_ID: &str = "playlist-table";
+
+/// Playlists opened so far, by id, each with its own filter and sort.
+#[derive(Debug, Default, Clone)]
+pub struct Playlist {
+    playlists: HashMap<String, Entry>,
+    /// Playlists that could not be fetched, until they are retried.
+    failures: HashMap<String, Failure>,
+    offset: f32,
+    viewport_height: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    details: PlaylistDetails,
+    filter: String,
+    /// In the order of the playlist without one.
+    sort: Option<Sort>,
+}
+
+pub enum PlaylistEvent {
+    Play(Option<String>, Vec<Track>, usize),
+    OpenUri(String),
+    Fetch(String),
+}
+
+impl Playlist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.playlists.contains_key(id)
+    }
+
+    pub fn update(&mut self, message: PlaylistMessage) -> Option<PlaylistEvent> {
+        use PlaylistMessage as PM;
+
+        match message {
+            PM::Fetched(id, details) => {
+                self.failures.remove(&id);
+
+                match self.playlists.get_mut(&id) {
+                    Some(entry) => entry.details = details,
+                    None => {
+                        self.playlists.insert(
+                            id,
+                            Entry {
+                                details,
+                                filter: String::new(),
+                                sort: None,
+                            },
+                        );
+                    }
+                }
+
+                None
+            }
+            PM::Failed(id, failure) => {
+                self.failures.insert(id, failure);
+
+                None
+            }
+            PM::Retry(id) => {
+                self.failures.remove(&id);
+
+                Some(PlaylistEvent::Fetch(id))
+            }
+            PM::FilterChanged(id, filter) => {
+                self.playlists.get_mut(&id)?.filter = filter;
+
+                None
+            }
+            PM::SortBy(id, column) => {
+                let entry = self.playlists.get_mut(&id)?;
+
+                // Sorting by the same column again goes descending, then back to the playlist order
+                entry.sort = match entry.sort {
+                    Some(sort) if sort.column == column && sort.ascending => Some(Sort {
+                        column,
+                        ascending: false,
+                    }),
+                    Some(sort) if sort.column == column => None,
+                    _ => Some(Sort {
+                        column,
+                        ascending: true,
+                    }),
+                };
+
+                None
+            }
+            PM::Scrolled(viewport) => {
+                self.offset = viewport.absolute_offset().y;
+                self.viewport_height = viewport.bounds().height;
+
+                None
+            }
+            PM::Play(id, index) => {
+                let entry = self.playlists.get(&id)?;
+                let tracks = entry.tracks().into_iter().cloned().collect();
+
+                Some(PlaylistEvent::Play(
+                    Some(entry.details.uri.clone()),
+                    tracks,
+                    index,
+                ))
+            }
+            PM::OpenUri(uri) => Some(PlaylistEvent::OpenUri(uri)),
+        }
+    }
+
+    pub fn view(&self, id: &str) -> Element<PlaylistMessage> {
+        let Some(entry) = self.playlists.get(id) else {
+            return match self.failures.get(id) {
+                Some(failure) => super::failed(*failure, PlaylistMessage::Retry(id.to_string())),
+                None => super::loading(),
+            };
+        };
+        let playlist = &entry.details;
+        let tracks = entry.tracks();
+
+        let mut facts = vec![
+            format!("By {}", playlist.owner),
+            format!("{} tracks", playlist.tracks.len()),
+            playlist.duration(),
+            format!("{} followers", playlist.followers),
+        ];
+        if playlist.collaborative {
+            facts.insert(1, "Collaborative".to_string());
+        }
+
+        let collaborators = (!playlist.collaborators.is_empty()).then(|| {
+            text(format!("With {}", playlist.collaborators.join(", "))).style(ui::text::secondary)
+        });
+        let description = (!playlist.description.is_empty())
+            .then(|| text(playlist.description.clone()).style(ui::text::secondary));
+
+        let filtered = id.to_string();
+        let filter = text_input("Filter", &entry.filter)
+            .font(font::BODY.get())
+            .on_input(move |filter| PlaylistMessage::FilterChanged(filtered.clone(), filter))
+            .padding(4)
+            .width(FILTER_WIDTH);
+
+        let actions = row![
+            widget::labeled_button(Icon::Play, "Play all").on_press_maybe(
+                (!tracks.is_empty()).then(|| PlaylistMessage::Play(id.to_string(), 0))
+            ),
+            horizontal_space(),
+            filter,
+        ]
+        .spacing(8)
+        .align_y(alignment::Vertical::Center);
+
+        let header = column![
+            text(playlist.name.clone()).font(font::HEADING.get()),
+            text(facts.join(" · "))
+                .style(ui::text::secondary)
+                .wrapping(Wrapping::None),
+        ]
+        .push_maybe(description)
+        .push_maybe(collaborators)
+        .push(actions)
+        .spacing(6);
+
+        let sort_by = |column| PlaylistMessage::SortBy(id.to_string(), column);
+
+        let rows = track_list::rows(tracks.len(), self.offset, self.viewport_height, |index| {
+            mouse_area(track_list::cells(
+                tracks[index],
+                &COLUMNS,
+                PlaylistMessage::OpenUri,
+            ))
+            .on_double_click(PlaylistMessage::Play(id.to_string(), index))
+            .into()
+        });
+
+        let content = column![
+            header,
+            vertical_space().height(10),
+            track_list::header(&COLUMNS, entry.sort, Some(&sort_by)),
+            horizontal_rule(1),
+            scrollable(rows)
+                .id(scrollable::Id::new(TABLE_ID))
+                .on_scroll(PlaylistMessage::Scrolled)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        ];
+
+        container(content)
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+impl Entry {
+    /// Tracks matching the filter, in the order they are shown.
+    fn tracks(&self) -> Vec<&Track> {
+        let mut tracks: Vec<&Track> = local::search(&self.details.tracks, &self.filter).collect();
+
+        if let Some(sort) = self.sort {
+            tracks.sort_by(|a, b| sort.compare(a, b));
+        }
+
+        tracks
+    }
+}
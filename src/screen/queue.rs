@@ -0,0 +1,374 @@
+use crate::data::{
+    cover::{self, Cover},
+    font,
+    library::Track,
+    messages::QueueMessage,
+    playback::{Repeat, Shuffle},
+    queue::Queue,
+    volume,
+};
+use iced::{
+    alignment, event, mouse,
+    widget::{
+        button, column, container, horizontal_space, image, mouse_area, row, scrollable, slider,
+        text::Wrapping, tooltip,
+    },
+    window, Element, Event, Length, Subscription,
+};
+
+use crate::{
+    appaerance::{
+        self as ui,
+        icon::{icon, Icon},
+    },
+    widget::{self, text},
+};
+
+const PANEL_WIDTH: f32 = 300.0;
+const COVER_SIZE: f32 = 96.0;
+
+#[derive(Debug, Default, Clone)]
+pub struct QueuePanel {
+    dragging: Option<usize>,
+    hovered: Option<usize>,
+    show_history: bool,
+}
+
+pub enum QueueEvent {
+    SkipTo(usize),
+    Replay(Track),
+    Changed,
+    TogglePlayback,
+    Next,
+    Previous,
+    ToggleMiniPlayer,
+    ModesChanged,
+    CycleVisualizer,
+    SetVolume(u16),
+    /// Scroll wheel lines, positive when scrolling up.
+    ScrollVolume(f32),
+}
+
+impl QueuePanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Listens for the mouse release that ends a drag, wherever it happens.
+    pub fn subscription(&self) -> Subscription<QueueMessage> {
+        if self.dragging.is_some() {
+            event::listen_with(drag_end)
+        } else {
+            Subscription::none()
+        }
+    }
+
+    pub fn update(&mut self, message: QueueMessage, queue: &mut Queue) -> Option<QueueEvent> {
+        use QueueMessage as QM;
+
+        match message {
+            QM::DragStart(index) => {
+                self.dragging = Some(index);
+                self.hovered = Some(index);
+
+                None
+            }
+            QM::DragOver(index) => {
+                self.hovered = self.dragging.map(|_| index);
+
+                None
+            }
+            QM::Drop(index) => {
+                let from = self.dragging.take()?;
+                self.hovered = None;
+
+                // Releasing on the same row is a click rather than a drag
+                if from == index {
+                    return Some(QueueEvent::SkipTo(index));
+                }
+
+                queue.reorder(from, index);
+
+                Some(QueueEvent::Changed)
+            }
+            QM::DragEnd => {
+                // A release on a row has already been handled as a drop
+                self.dragging = None;
+                self.hovered = None;
+
+                None
+            }
+            QM::Remove(index) => {
+                queue.remove(index);
+
+                Some(QueueEvent::Changed)
+            }
+            QM::Clear => {
+                queue.clear();
+
+                Some(QueueEvent::Changed)
+            }
+            QM::Replay(index) => queue.history().get(index).cloned().map(QueueEvent::Replay),
+            QM::ToggleHistory => {
+                self.show_history = !self.show_history;
+
+                None
+            }
+            QM::TogglePlayback => Some(QueueEvent::TogglePlayback),
+            QM::Next => Some(QueueEvent::Next),
+            QM::Previous => Some(QueueEvent::Previous),
+            QM::ToggleMiniPlayer => Some(QueueEvent::ToggleMiniPlayer),
+            QM::CycleShuffle => {
+                queue.set_shuffle(queue.shuffle().cycle());
+
+                Some(QueueEvent::ModesChanged)
+            }
+            QM::CycleRepeat => {
+                queue.set_repeat(queue.repeat().cycle());
+
+                Some(QueueEvent::ModesChanged)
+            }
+            QM::CycleVisualizer => Some(QueueEvent::CycleVisualizer),
+            QM::SetVolume(percent) => Some(QueueEvent::SetVolume(volume::from_percent(percent))),
+            QM::ScrollVolume(delta) => Some(QueueEvent::ScrollVolume(volume::scroll_lines(delta))),
+        }
+    }
+
+    /// `cover` is only given once the one of the current track is loaded. Playback is
+    /// controlled from the player bar.
+    pub fn view<'a>(
+        &'a self,
+        queue: &'a Queue,
+        cover: Option<&'a Cover>,
+    ) -> Element<'a, QueueMessage> {
+        let now_playing = match queue.current() {
+            Some(track) => row![]
+                .push(cover_art(cover, COVER_SIZE))
+                .push(column![
+                    text(track.name.clone()).style(ui::text::action),
+                    text(track.artist.clone()).style(ui::text::secondary),
+                ])
+                .spacing(8),
+            None => row![text("Nothing playing").style(ui::text::secondary)],
+        };
+        let color = cover.and_then(|cover| cover.color);
+        let now_playing = container(now_playing)
+            .padding(6)
+            .width(Length::Fill)
+            .style(move |theme| ui::container::tinted(theme, color));
+
+        let upcoming = queue
+            .upcoming()
+            .iter()
+            .enumerate()
+            .fold(column![].spacing(2), |column, (index, track)| {
+                column.push(self.upcoming_row(index, track))
+            });
+
+        let context = queue
+            .context_upcoming()
+            .fold(column![].spacing(2), |column, track| {
+                column.push(container(track_label(track)).padding([2, 6]))
+            });
+
+        let clear_button = button(text("Clear"))
+            .padding([2, 10])
+            .style(|theme, status| ui::button::secondary(theme, status, false))
+            .on_press_maybe((!queue.upcoming().is_empty()).then_some(QueueMessage::Clear));
+
+        let history_button = button(text(if self.show_history {
+            "Hide history"
+        } else {
+            "Show history"
+        }))
+        .padding([2, 10])
+        .style(|theme, status| ui::button::secondary(theme, status, self.show_history))
+        .on_press(QueueMessage::ToggleHistory);
+
+        let mut content = column![]
+            .spacing(8)
+            .push(
+                row![
+                    text("Now Playing").font(font::HEADING.get()),
+                    horizontal_space(),
+                    widget::icon_button(Icon::MiniPlayer, false)
+                        .on_press(QueueMessage::ToggleMiniPlayer),
+                ]
+                .align_y(alignment::Vertical::Center),
+            )
+            .push(now_playing)
+            .push(
+                row![
+                    text("Next Up").font(font::HEADING.get()),
+                    horizontal_space(),
+                    clear_button
+                ]
+                .align_y(alignment::Vertical::Center),
+            )
+            .push(scrollable(upcoming).height(Length::Fill))
+            .push(text("Next From Context").font(font::HEADING.get()))
+            .push(scrollable(context).height(Length::Fill))
+            .push(history_button);
+
+        if self.show_history {
+            let history = queue.history().iter().enumerate().rev().fold(
+                column![].spacing(2),
+                |column, (index, track)| {
+                    column.push(
+                        button(track_label(track))
+                            .padding([2, 6])
+                            .width(Length::Fill)
+                            .style(|theme, status| ui::button::secondary(theme, status, false))
+                            .on_press(QueueMessage::Replay(index)),
+                    )
+                },
+            );
+
+            content = content.push(scrollable(history).height(Length::FillPortion(1)));
+        }
+
+        container(content)
+            .padding(10)
+            .width(PANEL_WIDTH)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn upcoming_row<'a>(&self, index: usize, track: &'a Track) -> Element<'a, QueueMessage> {
+        let selected = self.dragging == Some(index) || self.hovered == Some(index);
+
+        let remove_button = widget::icon_button(Icon::Close, false)
+            .padding([0, 6])
+            .on_press(QueueMessage::Remove(index));
+
+        let content = container(
+            row![track_label(track), remove_button]
+                .spacing(4)
+                .align_y(alignment::Vertical::Center),
+        )
+        .padding([2, 6])
+        .style(move |theme| {
+            let palette = theme.extended_palette();
+
+            container::Style::default().background(if selected {
+                palette.secondary.strong.color
+            } else {
+                palette.secondary.weak.color
+            })
+        });
+
+        mouse_area(content)
+            .on_press(QueueMessage::DragStart(index))
+            .on_enter(QueueMessage::DragOver(index))
+            .on_release(QueueMessage::Drop(index))
+            .into()
+    }
+}
+
+/// Previous, play or pause and next buttons, shared with the player bar and the mini player.
+pub fn transport<'a>(queue: &Queue, playing: bool) -> Element<'a, QueueMessage> {
+    let has_current = queue.current().is_some();
+
+    row![
+        widget::icon_button(Icon::Previous, false)
+            .on_press_maybe((!queue.history().is_empty()).then_some(QueueMessage::Previous)),
+        widget::icon_button(if playing { Icon::Pause } else { Icon::Play }, false)
+            .on_press_maybe(has_current.then_some(QueueMessage::TogglePlayback)),
+        widget::icon_button(Icon::Next, false)
+            .on_press_maybe(has_current.then_some(QueueMessage::Next)),
+    ]
+    .spacing(4)
+    .into()
+}
+
+/// Shuffle and repeat buttons, highlighted while the mode is on.
+pub fn modes<'a>(queue: &Queue) -> Element<'a, QueueMessage> {
+    let shuffle = queue.shuffle();
+    let shuffle_button = mode_button(
+        Icon::Shuffle,
+        format!("Shuffle: {shuffle}"),
+        shuffle != Shuffle::Off,
+        QueueMessage::CycleShuffle,
+    );
+
+    let repeat = queue.repeat();
+    let repeat_button = mode_button(
+        if repeat == Repeat::Track {
+            Icon::RepeatOne
+        } else {
+            Icon::Repeat
+        },
+        format!("Repeat: {repeat}"),
+        repeat != Repeat::Off,
+        QueueMessage::CycleRepeat,
+    );
+
+    row![shuffle_button, repeat_button].spacing(4).into()
+}
+
+/// Slider of the `volume`, which the scroll wheel also changes.
+pub fn volume_slider<'a>(volume: u16) -> Element<'a, QueueMessage> {
+    let percent = volume::percent(volume);
+
+    mouse_area(
+        row![
+            icon(Icon::volume(percent)),
+            text(format!("{percent:>3.0}%")),
+            slider(0.0..=100.0, percent, QueueMessage::SetVolume)
+        ]
+        .spacing(8)
+        .align_y(alignment::Vertical::Center),
+    )
+    .on_scroll(QueueMessage::ScrollVolume)
+    .into()
+}
+
+/// The cover of the current track, or the logo until it is loaded.
+pub fn cover_art(cover: Option<&Cover>, size: f32) -> Element<'_, QueueMessage> {
+    match cover {
+        Some(cover) => image(cover.handle(cover::Size::fitting(size)).clone())
+            .width(size)
+            .height(size)
+            .into(),
+        None => container(icon(Icon::Logo).size(size / 2.0))
+            .center(size)
+            .into(),
+    }
+}
+
+/// Icon button of a playback mode, named by a tooltip as the icon alone does not tell it.
+fn mode_button<'a>(
+    icon: Icon,
+    label: String,
+    active: bool,
+    message: QueueMessage,
+) -> Element<'a, QueueMessage> {
+    tooltip(
+        widget::icon_button(icon, active).on_press(message),
+        container(text(label))
+            .padding([2, 6])
+            .style(container::rounded_box),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
+
+fn track_label(track: &Track) -> Element<'_, QueueMessage> {
+    column![
+        text(track.name.clone()).wrapping(Wrapping::None),
+        text(track.artist.clone())
+            .style(ui::text::secondary)
+            .wrapping(Wrapping::None),
+    ]
+    .width(Length::Fill)
+    .into()
+}
+
+fn drag_end(event: Event, _status: event::Status, _id: window::Id) -> Option<QueueMessage> {
+    match event {
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+            Some(QueueMessage::DragEnd)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,64 @@
+use super::Screen;
+
+/// Screens kept to go back to, the oldest are forgotten past it.
+const HISTORY_LIMIT: usize = 50;
+
+/// Screens visited in the main window, to go back and forward through them like a browser.
+#[derive(Debug, Clone)]
+pub struct Router {
+    history: Vec<Screen>,
+    position: usize,
+}
+
+impl Router {
+    pub fn new(screen: Screen) -> Self {
+        Self {
+            history: vec![screen],
+            position: 0,
+        }
+    }
+
+    pub fn current(&self) -> &Screen {
+        &self.history[self.position]
+    }
+
+    /// Opens `screen`, forgetting the ones ahead of the current one. Returns whether it changed.
+    pub fn push(&mut self, screen: Screen) -> bool {
+        if *self.current() == screen {
+            return false;
+        }
+
+        self.history.truncate(self.position + 1);
+        self.history.push(screen);
+
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.position = self.history.len() - 1;
+
+        true
+    }
+
+    pub fn back(&mut self) -> Option<Screen> {
+        self.position = self.position.checked_sub(1)?;
+
+        Some(self.current().clone())
+    }
+
+    pub fn forward(&mut self) -> Option<Screen> {
+        if !self.can_go_forward() {
+            return None;
+        }
+        self.position += 1;
+
+        Some(self.current().clone())
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.position + 1 < self.history.len()
+    }
+}
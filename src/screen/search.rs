@@ -0,0 +1,312 @@
+use crate::data::{
+    font,
+    library::Track,
+    messages::SearchMessage,
+    search::{Group, Navigation, Results},
+};
+use iced::{
+    alignment, event,
+    keyboard::{self, key::Named, Key},
+    widget::{
+        button, column, container, row, scrollable, text::Wrapping, text_input, vertical_space,
+    },
+    window, Element, Event, Length, Subscription,
+};
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+
+use super::track_list;
+use crate::{
+    appaerance::{self as ui, icon::Icon},
+    widget::{self, text},
+};
+
+pub const INPUT_ID: &str = "search-input";
+
+#[derive(Debug, Default, Clone)]
+pub struct Search {
+    query: String,
+    generation: u64,
+    results: HashMap<Group, Results>,
+    cursor: Option<(Group, usize)>,
+    loading: bool,
+}
+
+pub enum SearchEvent {
+    Debounce(u64),
+    Search(String),
+    LoadMore(String, Group, u32),
+    Play(Track),
+    PlayNext(Track),
+    Queue(Track),
+    /// Uri of the page to open.
+    Open(String),
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, message: SearchMessage) -> Option<SearchEvent> {
+        use SearchMessage as SM;
+
+        match message {
+            SM::QueryChanged(query) => {
+                // The search in flight, if any, is stale now and no other one is coming
+                if query.trim().is_empty() {
+                    self.loading = false;
+                }
+
+                self.query = query;
+                self.generation += 1;
+
+                Some(SearchEvent::Debounce(self.generation))
+            }
+            SM::Debounced(generation) => {
+                // A newer keystroke arrived while waiting, that one will trigger the search
+                if generation != self.generation || self.query.trim().is_empty() {
+                    return None;
+                }
+
+                self.loading = true;
+
+                Some(SearchEvent::Search(self.query.clone()))
+            }
+            SM::Failed(query) => {
+                if query == self.query {
+                    self.loading = false;
+                }
+
+                None
+            }
+            SM::ResultsFetched(query, results) => {
+                if query != self.query {
+                    return None;
+                }
+
+                self.loading = false;
+                self.results = results.into_iter().collect();
+                self.cursor = Group::iter()
+                    .find(|group| self.group_len(*group) > 0)
+                    .map(|group| (group, 0));
+
+                None
+            }
+            SM::MoreFetched(query, group, results) => {
+                if query != self.query {
+                    return None;
+                }
+
+                let entry = self.results.entry(group).or_default();
+                entry.items.extend(results.items);
+                entry.total = results.total;
+
+                None
+            }
+            SM::LoadMore(group) => {
+                // Local matches are not part of the spotify pages
+                let offset = self.results.get(&group).map_or(0, |results| {
+                    results.items.iter().filter(|item| !item.is_local()).count()
+                }) as u32;
+
+                Some(SearchEvent::LoadMore(self.query.clone(), group, offset))
+            }
+            SM::Select(group, index) => {
+                self.cursor = Some((group, index));
+
+                None
+            }
+            SM::Play(group, index) => self.item(group, index).map(SearchEvent::Play),
+            SM::PlayNext(group, index) => self.item(group, index).map(SearchEvent::PlayNext),
+            SM::Queue(group, index) => self.item(group, index).map(SearchEvent::Queue),
+            SM::Open(group, index) => self.open(group, index),
+            SM::OpenUri(uri) => Some(SearchEvent::Open(uri)),
+            SM::Navigate(navigation) => self.navigate(navigation),
+        }
+    }
+
+    pub fn view(&self) -> Element<SearchMessage> {
+        let input = text_input("What do you want to listen to?", &self.query)
+            .font(font::BODY.get())
+            .id(text_input::Id::new(INPUT_ID))
+            .on_input(SearchMessage::QueryChanged)
+            .padding(8);
+
+        let body: Element<SearchMessage> = if self.loading {
+            text("Searching...").style(ui::text::secondary).into()
+        } else if self.results.is_empty() {
+            text("Start typing to search tracks, albums, artists, playlists and podcasts.")
+                .style(ui::text::secondary)
+                .into()
+        } else {
+            let groups = Group::iter()
+                .filter(|group| self.group_len(*group) > 0)
+                .fold(column![].spacing(20), |column, group| {
+                    column.push(self.group(group))
+                });
+
+            scrollable(groups).height(Length::Fill).into()
+        };
+
+        container(column![input, vertical_space().height(10), body])
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// Listened to even while the query input has focus, it swallows Enter.
+    pub fn subscription(&self) -> Subscription<SearchMessage> {
+        event::listen_with(navigation)
+    }
+
+    fn group(&self, group: Group) -> Element<SearchMessage> {
+        let Some(results) = self.results.get(&group) else {
+            return column![].into();
+        };
+
+        let title = text(format!("{group} ({})", results.total)).font(font::HEADING.get());
+
+        let items =
+            results
+                .items
+                .iter()
+                .enumerate()
+                .fold(column![].spacing(2), |column, (index, item)| {
+                    let selected = self.cursor == Some((group, index));
+
+                    column.push(item_row(group, index, item, selected))
+                });
+
+        let mut content = column![title, items].spacing(6);
+
+        if results.has_more() {
+            content = content.push(
+                button(text("Load more"))
+                    .padding([4, 20])
+                    .style(|theme, status| ui::button::secondary(theme, status, false))
+                    .on_press(SearchMessage::LoadMore(group)),
+            );
+        }
+
+        content.into()
+    }
+
+    fn group_len(&self, group: Group) -> usize {
+        self.results
+            .get(&group)
+            .map_or(0, |results| results.items.len())
+    }
+
+    fn item(&self, group: Group, index: usize) -> Option<Track> {
+        self.results.get(&group)?.items.get(index).cloned()
+    }
+
+    fn open(&self, group: Group, index: usize) -> Option<SearchEvent> {
+        self.item(group, index)
+            .map(|item| SearchEvent::Open(item.uri))
+    }
+
+    fn navigate(&mut self, navigation: Navigation) -> Option<SearchEvent> {
+        let groups: Vec<Group> = Group::iter()
+            .filter(|group| self.group_len(*group) > 0)
+            .collect();
+        let (group, index) = self.cursor?;
+        let position = groups.iter().position(|g| *g == group)?;
+
+        match navigation {
+            Navigation::Up => {
+                self.cursor = Some((group, index.saturating_sub(1)));
+            }
+            Navigation::Down => {
+                let last = self.group_len(group).saturating_sub(1);
+                self.cursor = Some((group, (index + 1).min(last)));
+            }
+            Navigation::First => {
+                self.cursor = Some((group, 0));
+            }
+            Navigation::Last => {
+                self.cursor = Some((group, self.group_len(group).saturating_sub(1)));
+            }
+            Navigation::NextGroup => {
+                let next = groups[(position + 1) % groups.len()];
+                self.cursor = Some((next, 0));
+            }
+            Navigation::PreviousGroup => {
+                let previous = groups[(position + groups.len() - 1) % groups.len()];
+                self.cursor = Some((previous, 0));
+            }
+            Navigation::Play => return self.item(group, index).map(SearchEvent::Play),
+            Navigation::Queue => return self.item(group, index).map(SearchEvent::Queue),
+            Navigation::Open => return self.open(group, index),
+        }
+
+        None
+    }
+}
+
+fn navigation(event: Event, status: event::Status, _window: window::Id) -> Option<SearchMessage> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event else {
+        return None;
+    };
+
+    let navigation = match key.as_ref() {
+        Key::Named(Named::Tab) if modifiers.shift() => Navigation::PreviousGroup,
+        Key::Named(Named::Tab) => Navigation::NextGroup,
+        Key::Named(Named::Enter) if modifiers.shift() => Navigation::Queue,
+        Key::Named(Named::Enter) if modifiers.command() => Navigation::Open,
+        // Moving the cursor and playing are keybindings handled by `Oxify`, which never
+        // sees the Enter the focused input captured
+        Key::Named(Named::Enter) if status == event::Status::Captured => Navigation::Play,
+        _ => return None,
+    };
+
+    Some(SearchMessage::Navigate(navigation))
+}
+
+fn item_row(group: Group, index: usize, item: &Track, selected: bool) -> Element<SearchMessage> {
+    let action =
+        |icon: Icon, message: SearchMessage| widget::icon_button(icon, false).on_press(message);
+
+    let mut actions = row![].spacing(4);
+
+    if group.is_playable() {
+        actions = actions
+            .push(action(Icon::Play, SearchMessage::Play(group, index)))
+            .push(action(Icon::Next, SearchMessage::PlayNext(group, index)))
+            .push(action(Icon::Queue, SearchMessage::Queue(group, index)));
+    } else {
+        actions = actions.push(
+            button(text("Open"))
+                .padding([2, 10])
+                .style(|theme, status| ui::button::secondary(theme, status, false))
+                .on_press(SearchMessage::Open(group, index)),
+        );
+    }
+
+    let name = text(item.name.clone()).wrapping(Wrapping::None);
+    let label = column![
+        row![]
+            .push_maybe(item.is_local().then(|| widget::badge("local")))
+            .push(name)
+            .spacing(4)
+            .align_y(alignment::Vertical::Center),
+        track_list::byline(item, SearchMessage::OpenUri),
+    ];
+
+    let content = row![
+        container(label).width(Length::Fill).clip(true),
+        text(item.duration()),
+        actions,
+    ]
+    .spacing(10)
+    .align_y(alignment::Vertical::Center);
+
+    button(content)
+        .padding([4, 8])
+        .width(Length::Fill)
+        .style(move |theme, status| ui::button::secondary(theme, status, selected))
+        .on_press(SearchMessage::Select(group, index))
+        .into()
+}
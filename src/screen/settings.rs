@@ -0,0 +1,402 @@
+use crate::context::config::{Config, Equalizer};
+use crate::data::{
+    equalizer::{Preset, FREQUENCIES, GAIN_RANGE},
+    font,
+    messages::SettingsMessage,
+    storage::{self, Kind, Pins, Usage},
+};
+use iced::{
+    alignment,
+    widget::{
+        button, column, container, horizontal_space, pick_list, row, scrollable, slider,
+        text_input, toggler, vertical_slider,
+    },
+    Element, Length,
+};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+use crate::{appaerance as ui, widget::text};
+
+#[derive(Debug, Default, Clone)]
+pub struct Settings {
+    cache_usage: Vec<(Kind, Usage)>,
+    cache_limit: String,
+    pins: Pins,
+    pin_input: String,
+}
+
+pub enum SettingsEvent {
+    SetNormalisation(bool),
+    SetVisualizer(bool),
+    PreviewEqualizer(Equalizer),
+    SetEqualizer(Equalizer),
+    SaveEqualizer,
+    ClearCache(Kind),
+    SetCacheLimit(String),
+    Pin(String),
+    Unpin(String),
+    Back,
+}
+
+impl Settings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            cache_limit: config.audio.cache_limit_size.clone(),
+            pins: Pins::load(),
+            ..Self::default()
+        }
+    }
+
+    pub fn update(&mut self, message: SettingsMessage) -> Option<SettingsEvent> {
+        use SettingsMessage as SM;
+
+        match message {
+            SM::ToggleNormalisation(enabled) => Some(SettingsEvent::SetNormalisation(enabled)),
+            SM::ToggleVisualizer(enabled) => Some(SettingsEvent::SetVisualizer(enabled)),
+            SM::PreviewEqualizer(equalizer) => Some(SettingsEvent::PreviewEqualizer(equalizer)),
+            SM::SetEqualizer(equalizer) => Some(SettingsEvent::SetEqualizer(equalizer)),
+            SM::SaveEqualizer => Some(SettingsEvent::SaveEqualizer),
+            SM::CacheUsageFetched(usage) => {
+                self.cache_usage = usage;
+
+                None
+            }
+            SM::ClearCache(kind) => Some(SettingsEvent::ClearCache(kind)),
+            SM::CacheLimitChanged(limit) => {
+                self.cache_limit = limit;
+
+                None
+            }
+            SM::SetCacheLimit => Some(SettingsEvent::SetCacheLimit(
+                self.cache_limit.trim().to_string(),
+            )),
+            SM::PinInputChanged(uri) => {
+                self.pin_input = uri;
+
+                None
+            }
+            SM::Pin => {
+                let uri = std::mem::take(&mut self.pin_input);
+
+                (!uri.trim().is_empty()).then(|| SettingsEvent::Pin(uri.trim().to_string()))
+            }
+            SM::Unpin(uri) => Some(SettingsEvent::Unpin(uri)),
+            SM::PinsChanged(pins) => {
+                self.pins = pins;
+
+                None
+            }
+            SM::Back => Some(SettingsEvent::Back),
+        }
+    }
+
+    /// `normalising` is whether the running player normalises, it only changes on restart.
+    pub fn view<'a>(
+        &'a self,
+        config: &'a Config,
+        normalising: bool,
+    ) -> Element<'a, SettingsMessage> {
+        let back_button = button(text("Back"))
+            .padding([5, 20])
+            .style(|theme, status| ui::button::secondary(theme, status, false))
+            .on_press(SettingsMessage::Back);
+
+        let header = row![
+            text("Settings").font(font::HEADING.get()),
+            horizontal_space(),
+            back_button
+        ]
+        .align_y(alignment::Vertical::Center);
+
+        let normalisation = &config.audio.normalisation;
+        let normalisation_description = if normalisation.enabled == normalising {
+            format!(
+                "{} / {}, {:+.1} dB pregain",
+                normalisation.kind, normalisation.method, normalisation.pregain
+            )
+        } else {
+            String::from("Restart Oxify to apply")
+        };
+        let audio = column![
+            text("Audio").font(font::HEADING.get()),
+            setting(
+                "Volume normalisation",
+                normalisation_description,
+                toggler(normalisation.enabled).on_toggle(SettingsMessage::ToggleNormalisation),
+            ),
+        ]
+        .spacing(8);
+
+        let visualizer = &config.appaerance.visualizer;
+        let appaerance = column![
+            text("Appaerance").font(font::HEADING.get()),
+            setting(
+                "Visualizer",
+                format!(
+                    "{}, {} fps, click it in the queue to change the style",
+                    visualizer.style, visualizer.fps
+                ),
+                toggler(visualizer.enabled).on_toggle(SettingsMessage::ToggleVisualizer),
+            ),
+        ]
+        .spacing(8);
+
+        let content = column![
+            header,
+            scrollable(
+                column![
+                    appaerance,
+                    audio,
+                    equalizer(&config.audio.equalizer),
+                    self.cache()
+                ]
+                .spacing(20)
+            )
+        ]
+        .spacing(20);
+
+        container(content)
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+impl Settings {
+    fn cache(&self) -> Element<'_, SettingsMessage> {
+        use SettingsMessage as SM;
+
+        let action = |label: &'static str, message: SettingsMessage| {
+            button(text(label))
+                .padding([4, 20])
+                .style(|theme, status| ui::button::secondary(theme, status, false))
+                .on_press(message)
+        };
+
+        let usage = self.cache_usage.iter().map(|(kind, usage)| {
+            let (label, description) = match kind {
+                Kind::Audio => (
+                    "Audio cache",
+                    format!(
+                        "{} in {} files, {}",
+                        storage::format_size(usage.size),
+                        usage.items,
+                        usage.location.display()
+                    ),
+                ),
+                Kind::Metadata => (
+                    "Metadata cache",
+                    format!(
+                        "{} for {} items, {}",
+                        storage::format_size(usage.size),
+                        usage.items,
+                        usage.location.display()
+                    ),
+                ),
+                Kind::Credentials if usage.items > 0 => (
+                    "Credentials",
+                    format!(
+                        "Login is required after clearing, {}",
+                        usage.location.display()
+                    ),
+                ),
+                Kind::Credentials => ("Credentials", String::from("Not stored")),
+            };
+
+            setting(label, description, action("Clear", SM::ClearCache(*kind)))
+        });
+
+        let limit = setting(
+            "Audio cache limit",
+            String::from("Least recently played files are evicted above it, as 10G or 500MiB"),
+            row![
+                text_input("10G", &self.cache_limit)
+                    .font(font::BODY.get())
+                    .on_input(SM::CacheLimitChanged)
+                    .on_submit(SM::SetCacheLimit)
+                    .width(100),
+                action("Apply", SM::SetCacheLimit),
+            ]
+            .spacing(4),
+        );
+
+        let pin = setting(
+            "Pin an album",
+            String::from("Its tracks are never evicted once played"),
+            row![
+                text_input("spotify:album:...", &self.pin_input)
+                    .font(font::BODY.get())
+                    .on_input(SM::PinInputChanged)
+                    .on_submit(SM::Pin)
+                    .width(240),
+                action("Pin", SM::Pin),
+            ]
+            .spacing(4),
+        );
+
+        let pins = self.pins.albums.iter().map(|pin| {
+            setting(
+                &pin.name,
+                pin.uri.clone(),
+                action("Unpin", SM::Unpin(pin.uri.clone())),
+            )
+        });
+
+        column![text("Cache").font(font::HEADING.get())]
+            .extend(usage)
+            .push(limit)
+            .push(pin)
+            .extend(pins)
+            .spacing(8)
+            .into()
+    }
+}
+
+fn equalizer(equalizer: &Equalizer) -> Element<'_, SettingsMessage> {
+    use SettingsMessage as SM;
+
+    let preset = Preset::from_str(&equalizer.preset).ok();
+
+    let bands = row(FREQUENCIES.iter().enumerate().map(|(index, frequency)| {
+        let band = vertical_slider(
+            GAIN_RANGE,
+            equalizer.bands[index],
+            edit(equalizer, SM::PreviewEqualizer, move |equalizer, gain| {
+                equalizer.bands[index] = gain;
+                equalizer.preset = Preset::Custom.to_string();
+            }),
+        )
+        .step(0.5)
+        .height(120)
+        .on_release(SM::SaveEqualizer);
+
+        column![
+            band,
+            text(band_label(*frequency)).style(ui::text::secondary)
+        ]
+        .spacing(5)
+        .align_x(alignment::Horizontal::Center)
+        .width(Length::Fill)
+        .into()
+    }));
+
+    column![
+        text("Equalizer").font(font::HEADING.get()),
+        setting(
+            "Equalizer",
+            String::from("Everything below, applied while playing"),
+            toggler(equalizer.enabled).on_toggle(edit(
+                equalizer,
+                SM::SetEqualizer,
+                |equalizer, enabled| equalizer.enabled = enabled
+            )),
+        ),
+        setting(
+            "Preset",
+            String::from("Moving a band switches to custom"),
+            pick_list(
+                Preset::iter().collect::<Vec<_>>(),
+                preset,
+                edit(equalizer, SM::SetEqualizer, |equalizer, preset: Preset| {
+                    equalizer.preset = preset.to_string();
+                    if let Some(gains) = preset.gains() {
+                        equalizer.bands = gains;
+                    }
+                }),
+            ),
+        ),
+        setting(
+            "Preamp",
+            format!("{:+.1} dB", equalizer.preamp),
+            slider(
+                GAIN_RANGE,
+                equalizer.preamp,
+                edit(equalizer, SM::PreviewEqualizer, |equalizer, preamp| {
+                    equalizer.preamp = preamp
+                }),
+            )
+            .step(0.5)
+            .width(200)
+            .on_release(SM::SaveEqualizer),
+        ),
+        bands,
+        setting(
+            "Balance",
+            match equalizer.balance {
+                balance if balance < 0.0 => format!("{:.0}% left", -balance * 100.0),
+                balance if balance > 0.0 => format!("{:.0}% right", balance * 100.0),
+                _ => String::from("Center"),
+            },
+            slider(
+                -1.0..=1.0,
+                equalizer.balance,
+                edit(equalizer, SM::PreviewEqualizer, |equalizer, balance| {
+                    equalizer.balance = balance
+                }),
+            )
+            .step(0.05)
+            .width(200)
+            .on_release(SM::SaveEqualizer),
+        ),
+        setting(
+            "Mono",
+            String::from("Downmix both channels"),
+            toggler(equalizer.mono).on_toggle(edit(
+                equalizer,
+                SM::SetEqualizer,
+                |equalizer, mono| equalizer.mono = mono
+            )),
+        ),
+        setting(
+            "Limiter",
+            String::from("Prevents clipping when boosting"),
+            toggler(equalizer.limiter).on_toggle(edit(
+                equalizer,
+                SM::SetEqualizer,
+                |equalizer, limiter| equalizer.limiter = limiter
+            )),
+        ),
+    ]
+    .spacing(8)
+    .into()
+}
+
+/// Builds the message for a control, carrying a copy of `equalizer` with the new value applied.
+fn edit<T>(
+    equalizer: &Equalizer,
+    message: fn(Equalizer) -> SettingsMessage,
+    apply: impl Fn(&mut Equalizer, T),
+) -> impl Fn(T) -> SettingsMessage {
+    let equalizer = equalizer.clone();
+
+    move |value| {
+        let mut equalizer = equalizer.clone();
+        apply(&mut equalizer, value);
+
+        message(equalizer)
+    }
+}
+
+fn band_label(frequency: f64) -> String {
+    if frequency >= 1000.0 {
+        format!("{}k", frequency / 1000.0)
+    } else {
+        format!("{frequency}")
+    }
+}
+
+fn setting<'a>(
+    label: &'a str,
+    description: String,
+    control: impl Into<Element<'a, SettingsMessage>>,
+) -> Element<'a, SettingsMessage> {
+    row![
+        column![text(label), text(description).style(ui::text::secondary)].width(Length::Fill),
+        control.into(),
+    ]
+    .spacing(10)
+    .align_y(alignment::Vertical::Center)
+    .into()
+}
@@ -0,0 +1,58 @@
+use crate::data::messages::SidebarMessage;
+use iced::{
+    widget::{column, container, row, vertical_space},
+    Element, Length,
+};
+
+use super::{Router, Screen};
+use crate::{
+    appaerance::{self as ui, icon::Icon},
+    widget,
+};
+
+const WIDTH: f32 = 150.0;
+
+/// Screens to go to, and the history of the router to go back and forward through.
+pub fn view<'a>(router: &Router, show_queue: bool) -> Element<'a, SidebarMessage> {
+    let current = router.current();
+
+    let history = row![
+        widget::icon_button(Icon::Back, false)
+            .on_press_maybe(router.can_go_back().then_some(SidebarMessage::Back)),
+        widget::icon_button(Icon::Forward, false)
+            .on_press_maybe(router.can_go_forward().then_some(SidebarMessage::Forward)),
+    ]
+    .spacing(4);
+
+    let entry = |icon: Icon, label: &'static str, screen: Screen| {
+        let selected = *current == screen;
+
+        widget::labeled_button(icon, label)
+            .width(Length::Fill)
+            .style(move |theme, status| ui::button::secondary(theme, status, selected))
+            .on_press(SidebarMessage::Open(screen))
+    };
+
+    let queue_button = widget::labeled_button(Icon::Queue, "Queue")
+        .width(Length::Fill)
+        .style(move |theme, status| ui::button::secondary(theme, status, show_queue))
+        .on_press(SidebarMessage::ToggleQueue);
+
+    let content = column![
+        history,
+        vertical_space().height(6),
+        entry(Icon::Home, "Home", Screen::Home),
+        entry(Icon::Search, "Search", Screen::Search),
+        entry(Icon::Library, "Library", Screen::Library),
+        queue_button,
+        entry(Icon::Settings, "Settings", Screen::Settings),
+        entry(Icon::Logs, "Logs", Screen::Logs),
+    ]
+    .spacing(4);
+
+    container(content)
+        .padding(10)
+        .width(WIDTH)
+        .height(Length::Fill)
+        .into()
+}
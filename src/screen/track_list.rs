@@ -0,0 +1,136 @@
+use crate::data::{
+    font,
+    library::{Column, Sort, Track},
+};
+use iced::{
+    alignment,
+    widget::{button, container, row, text::Wrapping, vertical_space, Column as WidgetColumn, Row},
+    Element, Length,
+};
+
+use crate::{
+    appaerance as ui,
+    widget::{self, text},
+};
+
+pub const ROW_HEIGHT: f32 = 28.0;
+const OVERSCAN: usize = 10;
+
+/// Header of `columns`, sorting by the one pressed when `on_sort` is given.
+pub fn header<'a, Message: Clone + 'a>(
+    columns: &[Column],
+    sort: Option<Sort>,
+    on_sort: Option<&dyn Fn(Column) -> Message>,
+) -> Element<'a, Message> {
+    columns
+        .iter()
+        .fold(row![].spacing(8), |row, column| {
+            let label = match sort {
+                Some(sort) if sort.column == *column => {
+                    format!("{column} {}", if sort.ascending { "▲" } else { "▼" })
+                }
+                _ => column.to_string(),
+            };
+
+            row.push(
+                button(text(label).font(font::HEADING.get()))
+                    .padding([4, 0])
+                    .width(column_width(*column))
+                    .style(|theme, status| ui::button::secondary(theme, status, false))
+                    .on_press_maybe(on_sort.map(|on_sort| on_sort(*column))),
+            )
+        })
+        .into()
+}
+
+/// Cells of `track` in `columns`, its artist and album link to their page.
+pub fn cells<'a, Message: Clone + 'a>(
+    track: &Track,
+    columns: &[Column],
+    on_open: fn(String) -> Message,
+) -> Row<'a, Message> {
+    let cell = |content: Element<'a, Message>, column: Column| {
+        container(content).width(column_width(column)).clip(true)
+    };
+    let label =
+        |content: String| -> Element<'a, Message> { text(content).wrapping(Wrapping::None).into() };
+    let link = |label: String, uri: &str| -> Element<'a, Message> {
+        widget::link(label, (!uri.is_empty()).then(|| on_open(uri.to_string()))).into()
+    };
+
+    columns
+        .iter()
+        .fold(row![].spacing(8), |row, column| {
+            let content = match column {
+                Column::Title if track.is_local() => row![
+                    widget::badge("local"),
+                    text(track.name.clone()).wrapping(Wrapping::None)
+                ]
+                .spacing(4)
+                .align_y(alignment::Vertical::Center)
+                .into(),
+                Column::Title => label(track.name.clone()),
+                Column::Artist => link(track.artist.clone(), &track.artist_uri),
+                Column::Album => link(track.album.clone(), &track.album_uri),
+                Column::Duration => label(track.duration()),
+                Column::DateAdded => label(track.date_added()),
+            };
+
+            row.push(cell(content, *column))
+        })
+        .height(ROW_HEIGHT)
+        .align_y(alignment::Vertical::Center)
+}
+
+/// Artist and album under the title of `track`, each linking to its page.
+pub fn byline<'a, Message: Clone + 'a>(
+    track: &Track,
+    on_open: fn(String) -> Message,
+) -> Row<'a, Message> {
+    let link = |label: String, uri: &str| {
+        widget::link(label, (!uri.is_empty()).then(|| on_open(uri.to_string())))
+    };
+
+    // Albums are their own album, there is no point naming it twice
+    let album = (!track.album.is_empty() && track.album_uri != track.uri).then(|| {
+        row![
+            text("·").style(ui::text::secondary),
+            link(track.album.clone(), &track.album_uri)
+        ]
+        .spacing(6)
+    });
+
+    row![link(track.artist.clone(), &track.artist_uri)]
+        .push_maybe(album)
+        .spacing(6)
+}
+
+/// Only the rows inside the viewport (plus some overscan) are built, the rest of
+/// the list is replaced by spacers of the same height.
+pub fn rows<'a, Message: 'a>(
+    len: usize,
+    offset: f32,
+    viewport_height: f32,
+    row: impl Fn(usize) -> Element<'a, Message>,
+) -> WidgetColumn<'a, Message> {
+    let visible = (viewport_height / ROW_HEIGHT).ceil() as usize + OVERSCAN;
+    let first = ((offset / ROW_HEIGHT) as usize).saturating_sub(OVERSCAN / 2);
+    let last = (first + visible).min(len);
+    let first = first.min(last);
+
+    (first..last)
+        .fold(
+            WidgetColumn::new().push(vertical_space().height(first as f32 * ROW_HEIGHT)),
+            |column, index| column.push(row(index)),
+        )
+        .push(vertical_space().height((len - last) as f32 * ROW_HEIGHT))
+}
+
+pub fn column_width(column: Column) -> Length {
+    match column {
+        Column::Title => Length::FillPortion(4),
+        Column::Artist | Column::Album => Length::FillPortion(3),
+        Column::Duration => Length::Fixed(70.0),
+        Column::DateAdded => Length::Fixed(110.0),
+    }
+}
@@ -2,11 +2,16 @@ use crate::context::environment::{self, WEBSITE_URL};
 use crate::data::{font, messages::WelcomeMessage};
 use iced::{
     alignment,
-    widget::{button, column, container, image, row, text, vertical_space},
+    widget::{button, column, container, image, row, vertical_space},
     Element, Length,
 };
 
-use crate::appaerance as ui;
+use crate::{appaerance as ui, widget::text};
+
+/// Sizes at the default text size, they grow and shrink with the configured one.
+const TEXT_SIZE: f32 = 16.0;
+const LOGO_WIDTH: f32 = 150.0;
+const BUTTON_WIDTH: f32 = 220.0;
 
 #[derive(Debug, Default, Clone)]
 pub struct Welcome;
@@ -40,7 +45,9 @@ impl Welcome {
         }
     }
 
-    pub fn view(&self) -> Element<WelcomeMessage> {
+    /// The scale factor applies to every size, `text_size` keeps the labels fitting the buttons.
+    pub fn view(&self, text_size: f32) -> Element<WelcomeMessage> {
+        let em = |pixels: f32| pixels * text_size / TEXT_SIZE;
         let config_dir = String::from(environment::config_dir().to_string_lossy());
 
         let config_button = button(
@@ -64,7 +71,7 @@ impl Welcome {
         .on_press(WelcomeMessage::OpenWebsite);
 
         let login_button = button(
-            container(text("Login to Spotify").font(font::MONO_BOLD.clone()))
+            container(text("Login to Spotify").font(font::HEADING.get()))
                 .align_x(alignment::Horizontal::Center)
                 .width(Length::Fill),
         )
@@ -86,28 +93,28 @@ impl Welcome {
         let logo_bytes = include_bytes!("../../img/oxify-transparent.png").to_vec();
         let content = column![]
             .spacing(1)
-            .push(image(image::Handle::from_bytes(logo_bytes)).width(150))
-            .push(vertical_space().height(10))
-            .push(text("Welcome to Oxify!").font(font::MONO_BOLD.clone()))
-            .push(vertical_space().height(10))
-            .push(login_button.width(220))
-            .push(vertical_space().height(20))
+            .push(image(image::Handle::from_bytes(logo_bytes)).width(em(LOGO_WIDTH)))
+            .push(vertical_space().height(em(10.0)))
+            .push(text("Welcome to Oxify!").font(font::HEADING.get()))
+            .push(vertical_space().height(em(10.0)))
+            .push(login_button.width(em(BUTTON_WIDTH)))
+            .push(vertical_space().height(em(20.0)))
             .push(text("Oxify is configured through a config file."))
             .push(row![
                 text("You can find the "),
                 text("config.toml").style(ui::text::action),
                 text(" file at the following path:"),
             ])
-            .push(vertical_space().height(4))
+            .push(vertical_space().height(em(4.0)))
             .push(config_button)
-            .push(vertical_space().height(2))
-            .push(reload_button.width(220))
-            .push(vertical_space().height(10))
+            .push(vertical_space().height(em(2.0)))
+            .push(reload_button.width(em(BUTTON_WIDTH)))
+            .push(vertical_space().height(em(10.0)))
             .push(text(
                 "All available configuration options are at our website:",
             ))
-            .push(vertical_space().height(4))
-            .push(documentation_button.width(220))
+            .push(vertical_space().height(em(4.0)))
+            .push(documentation_button.width(em(BUTTON_WIDTH)))
             .align_x(iced::Alignment::Center);
 
         container(content)
@@ -1,5 +1,10 @@
+pub mod api;
+pub mod local;
+pub mod pipeline;
 pub mod service;
 pub mod setup;
+pub mod sync;
 
+pub use api::Api;
 pub use service::Service;
 pub use setup::Setup;
@@ -0,0 +1,718 @@
+use super::Service;
+use crate::data::{
+    detail::{AlbumDetails, AlbumGroup, ArtistDetails, PlaylistDetails, Release},
+    library::{Image, Playlist, Section, Track},
+    search::{Group, Results, PAGE_SIZE},
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Deserialize};
+use strum::IntoEnumIterator;
+
+const API_URL: &str = "https://api.spotify.com/v1";
+const PAGE_LIMIT: u32 = 50;
+
+#[derive(Clone)]
+pub struct Api {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl Api {
+    pub async fn new(client: reqwest::Client, service: &Service) -> Result<Self> {
+        Ok(Self {
+            client,
+            token: service.token().await?,
+        })
+    }
+
+    pub async fn section(&self, section: Section) -> Result<Vec<Track>> {
+        match section {
+            Section::LikedSongs => self.liked_songs().await,
+            Section::Albums => self.saved_albums().await,
+            Section::Artists => self.followed_artists().await,
+            Section::Podcasts => self.saved_episodes().await,
+            Section::Local => Err(anyhow!("Local files are not on Spotify")),
+        }
+    }
+
+    pub async fn playlists(&self) -> Result<Vec<Playlist>> {
+        let items: Vec<PlaylistObject> = self
+            .paginate(format!("{API_URL}/me/playlists?limit={PAGE_LIMIT}"))
+            .await?;
+
+        Ok(items.into_iter().map(Playlist::from).collect())
+    }
+
+    pub async fn playlist_tracks(&self, id: &str) -> Result<Vec<Track>> {
+        let items = self.playlist_items(id).await?;
+
+        Ok(items
+            .into_iter()
+            .filter_map(|item| Some(item.track?.into_track(item.added_at)))
+            .collect())
+    }
+
+    pub async fn playlist(&self, id: &str) -> Result<PlaylistDetails> {
+        let playlist: PlaylistObject = self.get(&format!("{API_URL}/playlists/{id}")).await?;
+        let items = self.playlist_items(id).await?;
+
+        let mut ids: Vec<&str> = Vec::new();
+        for user in items.iter().filter_map(|item| item.added_by.as_ref()) {
+            if user.id != playlist.owner.id && !ids.contains(&user.id.as_str()) {
+                ids.push(&user.id);
+            }
+        }
+
+        // Ids mean nothing to people, users without a name are left out
+        let mut collaborators = Vec::new();
+        for id in ids {
+            match self.get::<Owner>(&format!("{API_URL}/users/{id}")).await {
+                Ok(user) => collaborators.extend(user.display_name),
+                Err(err) => log::debug!("Cannot fetch the user {id}: {err}"),
+            }
+        }
+
+        let tracks = items
+            .into_iter()
+            .filter_map(|item| Some(item.track?.into_track(item.added_at)))
+            .collect();
+
+        Ok(PlaylistDetails {
+            uri: playlist.uri,
+            name: playlist.name,
+            description: plain_text(playlist.description.as_deref().unwrap_or_default()),
+            owner: playlist.owner.display_name.unwrap_or(playlist.owner.id),
+            collaborative: playlist.collaborative,
+            collaborators,
+            followers: playlist.followers.map_or(0, |followers| followers.total),
+            tracks,
+        })
+    }
+
+    pub async fn album(&self, id: &str) -> Result<AlbumDetails> {
+        let mut album: AlbumObject = self.get(&format!("{API_URL}/albums/{id}")).await?;
+
+        // Only the first page of the tracks comes with the album
+        let mut tracks = Vec::new();
+        if let Some(page) = album.tracks.take() {
+            tracks.extend(page.items);
+
+            if let Some(next) = page.next {
+                tracks.extend(self.paginate::<TrackObject>(next).await?);
+            }
+        }
+
+        Ok(AlbumDetails {
+            tracks: tracks
+                .into_iter()
+                .map(|track| Track {
+                    album: album.name.clone(),
+                    album_uri: album.uri.clone(),
+                    images: album.images.clone(),
+                    ..track.into_track(None)
+                })
+                .collect(),
+            artist_uri: first_uri(&album.artists),
+            artist: join_artists(album.artists),
+            uri: album.uri,
+            name: album.name,
+            release_date: album.release_date,
+            label: album.label,
+        })
+    }
+
+    pub async fn artist(&self, id: &str) -> Result<ArtistDetails> {
+        let artist: ArtistObject = self.get(&format!("{API_URL}/artists/{id}")).await?;
+        let top_tracks: TopTracks = self
+            .get(&format!(
+                "{API_URL}/artists/{id}/top-tracks?market=from_token"
+            ))
+            .await?;
+
+        let groups = AlbumGroup::iter()
+            .map(|group| group.api_type())
+            .collect::<Vec<_>>()
+            .join(",");
+        let albums: Vec<AlbumObject> = self
+            .paginate(format!(
+                "{API_URL}/artists/{id}/albums?include_groups={groups}&limit={PAGE_LIMIT}"
+            ))
+            .await?;
+
+        let discography = AlbumGroup::iter()
+            .map(|group| {
+                let releases: Vec<Release> = albums
+                    .iter()
+                    .filter(|album| album.album_group.as_deref() == Some(group.api_type()))
+                    .map(|album| Release {
+                        uri: album.uri.clone(),
+                        name: album.name.clone(),
+                        release_date: album.release_date.clone(),
+                    })
+                    .collect();
+
+                (group, releases)
+            })
+            .filter(|(_, releases)| !releases.is_empty())
+            .collect();
+
+        // Spotify no longer serves related artists to every application
+        let related = match self
+            .get::<RelatedArtists>(&format!("{API_URL}/artists/{id}/related-artists"))
+            .await
+        {
+            Ok(related) => related.artists.into_iter().map(Track::from).collect(),
+            Err(err) => {
+                log::debug!("Cannot fetch the artists related to {id}: {err}");
+                Vec::new()
+            }
+        };
+
+        let following = match self
+            .get::<Vec<bool>>(&format!(
+                "{API_URL}/me/following/contains?type=artist&ids={id}"
+            ))
+            .await
+        {
+            Ok(following) => following.first().copied(),
+            Err(err) => {
+                log::debug!("Cannot tell whether {id} is followed: {err}");
+                None
+            }
+        };
+
+        Ok(ArtistDetails {
+            uri: artist.uri,
+            name: artist.name,
+            followers: artist.followers.map_or(0, |followers| followers.total),
+            genres: artist.genres,
+            top_tracks: top_tracks
+                .tracks
+                .into_iter()
+                .map(|track| track.into_track(None))
+                .collect(),
+            discography,
+            related,
+            following,
+        })
+    }
+
+    pub async fn follow_artist(&self, id: &str, follow: bool) -> Result<()> {
+        let method = if follow { Method::PUT } else { Method::DELETE };
+
+        self.send(
+            method,
+            &format!("{API_URL}/me/following?type=artist&ids={id}"),
+        )
+        .await
+    }
+
+    pub async fn search(
+        &self,
+        query: &str,
+        groups: &[Group],
+        offset: u32,
+    ) -> Result<Vec<(Group, Results)>> {
+        let types = groups
+            .iter()
+            .map(Group::api_type)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let url = reqwest::Url::parse_with_params(
+            &format!("{API_URL}/search"),
+            &[
+                ("q", query),
+                ("type", &types),
+                ("limit", &PAGE_SIZE.to_string()),
+                ("offset", &offset.to_string()),
+            ],
+        )?;
+
+        let mut response: SearchResponse = self.get(url.as_str()).await?;
+
+        Ok(groups
+            .iter()
+            .map(|group| (*group, response.results(*group)))
+            .collect())
+    }
+
+    async fn liked_songs(&self) -> Result<Vec<Track>> {
+        let items: Vec<SavedTrack> = self
+            .paginate(format!("{API_URL}/me/tracks?limit={PAGE_LIMIT}"))
+            .await?;
+
+        Ok(items
+            .into_iter()
+            .map(|item| item.track.into_track(Some(item.added_at)))
+            .collect())
+    }
+
+    async fn saved_albums(&self) -> Result<Vec<Track>> {
+        let items: Vec<SavedAlbum> = self
+            .paginate(format!("{API_URL}/me/albums?limit={PAGE_LIMIT}"))
+            .await?;
+
+        Ok(items
+            .into_iter()
+            .flat_map(|item| {
+                let SavedAlbum { added_at, album } = item;
+                let tracks = album.tracks.map(|page| page.items).unwrap_or_default();
+
+                tracks.into_iter().map(move |track| Track {
+                    album: album.name.clone(),
+                    album_uri: album.uri.clone(),
+                    images: album.images.clone(),
+                    ..track.into_track(Some(added_at))
+                })
+            })
+            .collect())
+    }
+
+    async fn followed_artists(&self) -> Result<Vec<Track>> {
+        let mut artists = Vec::new();
+        let mut next = Some(format!(
+            "{API_URL}/me/following?type=artist&limit={PAGE_LIMIT}"
+        ));
+
+        while let Some(url) = next {
+            let page: FollowedArtists = self.get(&url).await?;
+
+            next = page.artists.next;
+            artists.extend(page.artists.items);
+        }
+
+        Ok(artists.into_iter().map(Track::from).collect())
+    }
+
+    async fn saved_episodes(&self) -> Result<Vec<Track>> {
+        let items: Vec<SavedEpisode> = self
+            .paginate(format!("{API_URL}/me/episodes?limit={PAGE_LIMIT}"))
+            .await?;
+
+        Ok(items
+            .into_iter()
+            .map(|item| Track {
+                added_at: Some(item.added_at),
+                ..Track::from(item.episode)
+            })
+            .collect())
+    }
+
+    async fn playlist_items(&self, id: &str) -> Result<Vec<PlaylistItem>> {
+        self.paginate(format!(
+            "{API_URL}/playlists/{id}/tracks?limit={}",
+            PAGE_LIMIT * 2
+        ))
+        .await
+    }
+
+    async fn paginate<T: DeserializeOwned>(&self, url: String) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut next = Some(url);
+
+        while let Some(url) = next {
+            let page: Page<T> = self.get(&url).await?;
+
+            next = page.next;
+            items.extend(page.items);
+        }
+
+        Ok(items)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        log::debug!("GET {url}");
+
+        Ok(self
+            .client
+            .get(url)
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Requests without a response body, for changes to the library.
+    async fn send(&self, method: Method, url: &str) -> Result<()> {
+        log::debug!("{method} {url}");
+
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Page<T> {
+    items: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ArtistObject {
+    uri: String,
+    name: String,
+    // Artists nested in tracks and albums are simplified and do not include the rest
+    #[serde(default)]
+    images: Vec<Image>,
+    #[serde(default)]
+    genres: Vec<String>,
+    #[serde(default)]
+    followers: Option<Followers>,
+}
+
+impl From<ArtistObject> for Track {
+    fn from(artist: ArtistObject) -> Self {
+        Self {
+            artist_uri: artist.uri.clone(),
+            uri: artist.uri,
+            name: artist.name,
+            artist: String::new(),
+            album: String::new(),
+            duration_ms: 0,
+            added_at: None,
+            album_uri: String::new(),
+            images: artist.images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AlbumObject {
+    #[serde(default)]
+    uri: String,
+    name: String,
+    #[serde(default)]
+    artists: Vec<ArtistObject>,
+    #[serde(default)]
+    tracks: Option<Page<TrackObject>>,
+    #[serde(default)]
+    images: Vec<Image>,
+    #[serde(default)]
+    release_date: String,
+    // Only full albums have a label, and only the albums of an artist have a group
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    album_group: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TrackObject {
+    uri: String,
+    name: String,
+    duration_ms: u32,
+    #[serde(default)]
+    artists: Vec<ArtistObject>,
+    #[serde(default)]
+    album: Option<AlbumObject>,
+}
+
+impl TrackObject {
+    fn into_track(self, added_at: Option<DateTime<Utc>>) -> Track {
+        let (album, album_uri, images) = self
+            .album
+            .map(|album| (album.name, album.uri, album.images))
+            .unwrap_or_default();
+
+        Track {
+            uri: self.uri,
+            name: self.name,
+            artist_uri: first_uri(&self.artists),
+            artist: join_artists(self.artists),
+            album,
+            duration_ms: self.duration_ms,
+            added_at,
+            album_uri,
+            images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SavedTrack {
+    added_at: DateTime<Utc>,
+    track: TrackObject,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SavedAlbum {
+    added_at: DateTime<Utc>,
+    album: AlbumObject,
+}
+
+impl From<AlbumObject> for Track {
+    fn from(album: AlbumObject) -> Self {
+        Self {
+            album_uri: album.uri.clone(),
+            uri: album.uri,
+            artist_uri: first_uri(&album.artists),
+            artist: join_artists(album.artists),
+            album: album.name.clone(),
+            name: album.name,
+            duration_ms: 0,
+            added_at: None,
+            images: album.images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ShowObject {
+    #[serde(default)]
+    uri: String,
+    name: String,
+    publisher: String,
+    #[serde(default)]
+    images: Vec<Image>,
+}
+
+impl From<ShowObject> for Track {
+    fn from(show: ShowObject) -> Self {
+        Self {
+            uri: show.uri,
+            name: show.name,
+            artist: show.publisher,
+            album: String::new(),
+            duration_ms: 0,
+            added_at: None,
+            album_uri: String::new(),
+            artist_uri: String::new(),
+            images: show.images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EpisodeObject {
+    uri: String,
+    name: String,
+    duration_ms: u32,
+    // Episodes coming from search results are simplified and do not include their show
+    #[serde(default)]
+    show: Option<ShowObject>,
+    #[serde(default)]
+    images: Vec<Image>,
+}
+
+impl From<EpisodeObject> for Track {
+    fn from(episode: EpisodeObject) -> Self {
+        let (artist, album) = episode
+            .show
+            .map(|show| (show.publisher, show.name))
+            .unwrap_or_default();
+
+        Self {
+            uri: episode.uri,
+            name: episode.name,
+            artist,
+            album,
+            duration_ms: episode.duration_ms,
+            added_at: None,
+            album_uri: String::new(),
+            artist_uri: String::new(),
+            images: episode.images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SavedEpisode {
+    added_at: DateTime<Utc>,
+    episode: EpisodeObject,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FollowedArtists {
+    artists: Page<ArtistObject>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Followers {
+    total: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TopTracks {
+    tracks: Vec<TrackObject>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RelatedArtists {
+    artists: Vec<ArtistObject>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct User {
+    id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Owner {
+    id: String,
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TracksRef {
+    total: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PlaylistObject {
+    id: String,
+    uri: String,
+    name: String,
+    owner: Owner,
+    snapshot_id: String,
+    tracks: TracksRef,
+    // `null` for playlists without any track
+    #[serde(default)]
+    images: Option<Vec<Image>>,
+    // Only full playlists have these
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    collaborative: bool,
+    #[serde(default)]
+    followers: Option<Followers>,
+}
+
+impl From<PlaylistObject> for Playlist {
+    fn from(playlist: PlaylistObject) -> Self {
+        Self {
+            id: playlist.id,
+            uri: playlist.uri,
+            name: playlist.name,
+            owner: playlist.owner.display_name.unwrap_or(playlist.owner.id),
+            snapshot_id: playlist.snapshot_id,
+            total: playlist.tracks.total,
+        }
+    }
+}
+
+impl From<PlaylistObject> for Track {
+    fn from(mut playlist: PlaylistObject) -> Self {
+        let images = playlist.images.take().unwrap_or_default();
+        let playlist = Playlist::from(playlist);
+
+        Self {
+            uri: playlist.uri,
+            name: playlist.name,
+            artist: playlist.owner,
+            album: String::new(),
+            duration_ms: 0,
+            added_at: None,
+            album_uri: String::new(),
+            artist_uri: String::new(),
+            images,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PlaylistItem {
+    added_at: Option<DateTime<Utc>>,
+    // `null` for playlists older than the tracking of who added what
+    #[serde(default)]
+    added_by: Option<User>,
+    track: Option<TrackObject>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SearchPage<T> {
+    // Search results may contain `null` entries for items that are no longer available
+    items: Vec<Option<T>>,
+    total: u32,
+}
+
+impl<T: Into<Track>> SearchPage<T> {
+    fn into_results(self) -> Results {
+        Results {
+            items: self.items.into_iter().flatten().map(Into::into).collect(),
+            total: self.total,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SearchResponse {
+    tracks: Option<SearchPage<TrackObject>>,
+    albums: Option<SearchPage<AlbumObject>>,
+    artists: Option<SearchPage<ArtistObject>>,
+    playlists: Option<SearchPage<PlaylistObject>>,
+    shows: Option<SearchPage<ShowObject>>,
+    episodes: Option<SearchPage<EpisodeObject>>,
+}
+
+impl SearchResponse {
+    fn results(&mut self, group: Group) -> Results {
+        match group {
+            Group::Tracks => self.tracks.take().map(SearchPage::into_results),
+            Group::Albums => self.albums.take().map(SearchPage::into_results),
+            Group::Artists => self.artists.take().map(SearchPage::into_results),
+            Group::Playlists => self.playlists.take().map(SearchPage::into_results),
+            Group::Shows => self.shows.take().map(SearchPage::into_results),
+            Group::Episodes => self.episodes.take().map(SearchPage::into_results),
+        }
+        .unwrap_or_default()
+    }
+}
+
+impl From<TrackObject> for Track {
+    fn from(track: TrackObject) -> Self {
+        track.into_track(None)
+    }
+}
+
+fn join_artists(artists: Vec<ArtistObject>) -> String {
+    artists
+        .into_iter()
+        .map(|artist| artist.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn first_uri(artists: &[ArtistObject]) -> String {
+    artists
+        .first()
+        .map(|artist| artist.uri.clone())
+        .unwrap_or_default()
+}
+
+/// Text of the html spotify serves descriptions as, links are kept as their label.
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    [
+        ("&quot;", "\""),
+        ("&#x27;", "'"),
+        ("&#39;", "'"),
+        ("&#x2F;", "/"),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&amp;", "&"),
+    ]
+    .into_iter()
+    .fold(text, |text, (entity, c)| text.replace(entity, c))
+}
@@ -0,0 +1,376 @@
+mod libopus;
+
+use anyhow::{anyhow, Result};
+use librespot::playback::{
+    audio_backend::Sink, convert::Converter, decoder::AudioPacket, mixer::VolumeGetter,
+    NUM_CHANNELS, SAMPLE_RATE,
+};
+use std::{
+    fs::File,
+    io,
+    path::Path,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{CodecRegistry, DecoderOptions},
+    errors::Error as DecodeError,
+    formats::{FormatOptions, FormatReader, SeekMode, SeekTo},
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+    units::Time,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+#[derive(Debug, Clone)]
+pub enum LocalEvent {
+    Playing {
+        position_ms: u32,
+    },
+    Paused {
+        position_ms: u32,
+    },
+    EndOfTrack,
+    /// The file cannot be opened or stopped decoding halfway.
+    Unavailable,
+}
+
+/// Plays local files through a sink of its own, the librespot player cannot open them.
+#[derive(Clone, Default)]
+pub struct LocalPlayer {
+    control: Arc<Mutex<Option<Arc<Control>>>>,
+    senders: Arc<Mutex<Vec<UnboundedSender<LocalEvent>>>>,
+}
+
+/// Commands for the thread decoding the open file.
+#[derive(Debug, Default)]
+struct Control {
+    commands: Mutex<Commands>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Commands {
+    /// Also set by the thread itself once the file is over.
+    stop: bool,
+    paused: bool,
+    seek: Option<u32>,
+}
+
+impl LocalPlayer {
+    /// Stops whatever is playing and opens `file` at `position_ms`, paused unless `start`.
+    ///
+    /// `volume` is applied the same way the librespot player applies its soft volume.
+    pub fn play(
+        &self,
+        file: &Path,
+        position_ms: u32,
+        start: bool,
+        volume: Box<dyn VolumeGetter + Send>,
+        sink: Box<dyn Sink>,
+    ) {
+        self.stop();
+
+        let control = Arc::new(Control {
+            commands: Mutex::new(Commands {
+                paused: !start,
+                ..Default::default()
+            }),
+            changed: Condvar::new(),
+        });
+
+        {
+            let file = file.to_path_buf();
+            let control = control.clone();
+            let senders = self.senders.clone();
+
+            thread::spawn(move || {
+                let send = |event: LocalEvent| {
+                    if let Ok(mut senders) = senders.lock() {
+                        senders.retain(|sender| sender.send(event.clone()).is_ok());
+                    }
+                };
+
+                if let Err(err) = decode(&file, position_ms, volume, sink, &control, &send) {
+                    log::error!("Cannot play {}: {err}", file.display());
+                    send(LocalEvent::Unavailable);
+                }
+                control.update(|commands| commands.stop = true);
+            });
+        }
+
+        if let Ok(mut current) = self.control.lock() {
+            *current = Some(control);
+        }
+    }
+
+    /// Tells the thread to release its sink and returns right away, the
+    /// thread finishes on its own.
+    pub fn stop(&self) {
+        if let Some(control) = self.control.lock().ok().and_then(|mut c| c.take()) {
+            control.update(|commands| commands.stop = true);
+        }
+    }
+
+    /// The decoder is kept, playback resumes from the same sample.
+    pub fn pause(&self) {
+        self.command(|commands| commands.paused = true);
+    }
+
+    pub fn resume(&self) {
+        self.command(|commands| commands.paused = false);
+    }
+
+    pub fn seek(&self, position_ms: u32) {
+        self.command(|commands| commands.seek = Some(position_ms));
+    }
+
+    /// Whether a file is open, playing or paused, rather than finished or stopped.
+    pub fn is_loaded(&self) -> bool {
+        self.current()
+            .is_some_and(|control| control.commands.lock().is_ok_and(|commands| !commands.stop))
+    }
+
+    pub fn get_event_channel(&self) -> UnboundedReceiver<LocalEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.push(sender);
+        }
+
+        receiver
+    }
+
+    fn current(&self) -> Option<Arc<Control>> {
+        self.control.lock().ok().and_then(|control| control.clone())
+    }
+
+    fn command(&self, apply: impl FnOnce(&mut Commands)) {
+        if let Some(control) = self.current() {
+            control.update(apply);
+        }
+    }
+}
+
+impl Control {
+    fn update(&self, apply: impl FnOnce(&mut Commands)) {
+        if let Ok(mut commands) = self.commands.lock() {
+            apply(&mut commands);
+            self.changed.notify_all();
+        }
+    }
+
+    /// Whether playback is paused and where to seek to, `None` once stopped.
+    fn poll(&self) -> Option<(bool, Option<u32>)> {
+        let mut commands = self.commands.lock().ok()?;
+
+        (!commands.stop).then(|| (commands.paused, commands.seek.take()))
+    }
+
+    /// Blocks while paused and nothing else is asked.
+    fn wait(&self) {
+        let Ok(commands) = self.commands.lock() else {
+            return;
+        };
+
+        let _commands = self.changed.wait_while(commands, |commands| {
+            commands.paused && !commands.stop && commands.seek.is_none()
+        });
+    }
+}
+
+fn decode(
+    file: &Path,
+    position_ms: u32,
+    volume: Box<dyn VolumeGetter + Send>,
+    mut sink: Box<dyn Sink>,
+    control: &Control,
+    send: impl Fn(LocalEvent),
+) -> Result<()> {
+    let source = MediaSourceStream::new(Box::new(File::open(file)?), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = file.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?
+        .format;
+
+    let track = format
+        .default_track()
+        .ok_or(anyhow!("No audio track found"))?;
+    let track_id = track.id;
+    let time_base = track.codec_params.time_base;
+    let mut decoder = codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut position_ms = position_ms;
+    if position_ms > 0 {
+        seek(format.as_mut(), track_id, position_ms)?;
+    }
+
+    let mut converter = Converter::new(None);
+    let mut resampler = Resampler::default();
+    // Whether the sink is started, nothing has been announced yet while `None`
+    let mut running = None;
+
+    while let Some((paused, seek_to)) = control.poll() {
+        if let Some(target) = seek_to {
+            seek(format.as_mut(), track_id, target)?;
+            decoder.reset();
+            resampler = Resampler::default();
+            position_ms = target;
+
+            send(match running {
+                Some(true) => LocalEvent::Playing { position_ms },
+                _ => LocalEvent::Paused { position_ms },
+            });
+            continue;
+        }
+
+        if paused {
+            if running != Some(false) {
+                if running == Some(true) {
+                    sink.stop()?;
+                }
+                running = Some(false);
+                send(LocalEvent::Paused { position_ms });
+            }
+
+            control.wait();
+            continue;
+        }
+
+        if running != Some(true) {
+            sink.start()?;
+            running = Some(true);
+            send(LocalEvent::Playing { position_ms });
+        }
+
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                send(LocalEvent::EndOfTrack);
+                break;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        if let Some(time_base) = time_base {
+            let time = time_base.calc_time(packet.ts() + packet.dur());
+            position_ms = (time.seconds * 1000 + (time.frac * 1000.0) as u64) as u32;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupted packet is skipped, as other players do
+            Err(DecodeError::DecodeError(err)) => {
+                log::warn!("Skipping a packet of {}: {err}", file.display());
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let spec = *decoded.spec();
+        let mut buffer = SampleBuffer::<f64>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        let mut samples = resampler.process(buffer.samples(), spec.channels.count(), spec.rate);
+        let attenuation = volume.attenuation_factor();
+        if attenuation < 1.0 {
+            samples.iter_mut().for_each(|sample| *sample *= attenuation);
+        }
+
+        sink.write(AudioPacket::Samples(samples), &mut converter)?;
+    }
+
+    if running == Some(true) {
+        sink.stop()?;
+    }
+
+    Ok(())
+}
+
+/// The codecs symphonia has, and Opus.
+fn codecs() -> CodecRegistry {
+    let mut codecs = CodecRegistry::new();
+    symphonia::default::register_enabled_codecs(&mut codecs);
+    codecs.register_all::<libopus::OpusDecoder>();
+
+    codecs
+}
+
+fn seek(format: &mut dyn FormatReader, track_id: u32, position_ms: u32) -> Result<()> {
+    format.seek(
+        SeekMode::Accurate,
+        SeekTo::Time {
+            time: Time::from(Duration::from_millis(position_ms as u64)),
+            track_id: Some(track_id),
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Turns any file into interleaved stereo at [`SAMPLE_RATE`], as the sink expects.
+///
+/// Linear interpolation is good enough for the few files not at 44.1 kHz.
+#[derive(Debug, Default)]
+struct Resampler {
+    /// Position of the next output frame, in input frames after `previous`.
+    position: f64,
+    /// Last input frame of the previous packet.
+    previous: [f64; 2],
+}
+
+impl Resampler {
+    fn process(&mut self, samples: &[f64], channels: usize, rate: u32) -> Vec<f64> {
+        let frames: Vec<[f64; 2]> = samples
+            .chunks_exact(channels.max(1))
+            .map(|frame| [frame[0], frame[frame.len().min(2) - 1]])
+            .collect();
+
+        if rate == SAMPLE_RATE {
+            return frames.into_iter().flatten().collect();
+        }
+
+        let step = rate as f64 / SAMPLE_RATE as f64;
+        let frame = |index: usize| match index {
+            0 => self.previous,
+            index => frames[index - 1],
+        };
+
+        let mut output = Vec::with_capacity((frames.len() as f64 / step) as usize * 2 + 2);
+        while self.position + 1.0 <= frames.len() as f64 {
+            let index = self.position as usize;
+            let fraction = self.position.fract();
+            let (from, to) = (frame(index), frame(index + 1));
+
+            for channel in 0..NUM_CHANNELS as usize {
+                output.push(from[channel] + (to[channel] - from[channel]) * fraction);
+            }
+
+            self.position += step;
+        }
+
+        self.position -= frames.len() as f64;
+        if let Some(last) = frames.last() {
+            self.previous = *last;
+        }
+
+        output
+    }
+}
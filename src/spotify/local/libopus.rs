@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+use symphonia::core::{
+    audio::{AsAudioBufferRef, AudioBuffer, AudioBufferRef, Signal, SignalSpec},
+    codecs::{
+        CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult, CODEC_TYPE_OPUS,
+    },
+    errors::{decode_error, unsupported_error, Result},
+    formats::Packet,
+    support_codec,
+};
+
+/// Opus always decodes at 48 kHz, whatever rate it was encoded at.
+const SAMPLE_RATE: u32 = 48_000;
+/// Frames of the longest packet, 120 ms.
+const MAX_FRAMES: usize = 5_760;
+
+/// Symphonia demuxes Ogg Opus files but cannot decode them, libopus does.
+pub struct OpusDecoder {
+    params: CodecParameters,
+    /// libopus decoders can be sent but not shared, symphonia wants both.
+    decoder: Mutex<opus::Decoder>,
+    channels: usize,
+    /// Frames the encoder added before the audio, only dropped from the start of the file.
+    skip: usize,
+    interleaved: Vec<f32>,
+    buffer: AudioBuffer<f32>,
+}
+
+impl Decoder for OpusDecoder {
+    fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+        let Some(layout) = params.channels else {
+            return unsupported_error("opus: the channels are unknown");
+        };
+
+        let channels = match layout.count() {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            _ => return unsupported_error("opus: only mono and stereo are supported"),
+        };
+
+        let Ok(decoder) = opus::Decoder::new(SAMPLE_RATE, channels) else {
+            return unsupported_error("opus: libopus cannot open the stream");
+        };
+
+        Ok(Self {
+            params: params.clone(),
+            decoder: Mutex::new(decoder),
+            channels: layout.count(),
+            skip: params.delay.unwrap_or_default() as usize,
+            interleaved: vec![0.0; MAX_FRAMES * layout.count()],
+            buffer: AudioBuffer::new(MAX_FRAMES as u64, SignalSpec::new(SAMPLE_RATE, layout)),
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+    }
+
+    fn reset(&mut self) {
+        // Seeking lands past the frames to skip
+        self.skip = 0;
+
+        if let Ok(decoder) = self.decoder.get_mut() {
+            let _ = decoder.reset_state();
+        }
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+        let Ok(decoder) = self.decoder.get_mut() else {
+            return decode_error("opus: the decoder is poisoned");
+        };
+        let Ok(frames) = decoder.decode_float(&packet.data, &mut self.interleaved, false) else {
+            return decode_error("opus: invalid packet");
+        };
+
+        let skipped = self.skip.min(frames);
+        self.skip -= skipped;
+
+        self.buffer.clear();
+        self.buffer.render_reserved(Some(frames - skipped));
+        for channel in 0..self.channels {
+            let samples = self.interleaved[skipped * self.channels..]
+                .iter()
+                .skip(channel)
+                .step_by(self.channels);
+
+            for (sample, decoded) in self.buffer.chan_mut(channel).iter_mut().zip(samples) {
+                *sample = *decoded;
+            }
+        }
+
+        Ok(self.buffer.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buffer.as_audio_buffer_ref()
+    }
+}
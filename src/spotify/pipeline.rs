@@ -0,0 +1,91 @@
+pub mod crossfade;
+pub mod dsp;
+pub mod visualizer;
+
+pub use crossfade::Crossfade;
+pub use dsp::Dsp;
+pub use visualizer::Visualizer;
+
+use librespot::playback::{
+    audio_backend::{Sink, SinkResult},
+    convert::Converter,
+    decoder::AudioPacket,
+};
+
+/// A processing step applied to the decoded PCM before it reaches the real sink.
+///
+/// Samples are interleaved stereo `f64` at [`librespot::playback::SAMPLE_RATE`].
+pub trait Stage: Send {
+    fn process(&mut self, samples: &mut Vec<f64>);
+
+    /// Called before the sink stops, stages holding samples back must return them here.
+    fn flush(&mut self) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+/// Wraps the sink built from `Setup::backend` and runs every packet through the stages.
+pub struct Pipeline {
+    sink: Box<dyn Sink>,
+    stages: Vec<Box<dyn Stage>>,
+    /// `stop` is not given the player's converter, flushed samples go through this one.
+    converter: Converter,
+}
+
+impl Pipeline {
+    pub fn new(sink: Box<dyn Sink>, stages: Vec<Box<dyn Stage>>) -> Self {
+        Self {
+            sink,
+            stages,
+            converter: Converter::new(None),
+        }
+    }
+
+    fn run(&mut self, mut samples: Vec<f64>, from: usize) -> Vec<f64> {
+        for stage in self.stages.iter_mut().skip(from) {
+            if samples.is_empty() {
+                break;
+            }
+
+            stage.process(&mut samples);
+        }
+
+        samples
+    }
+}
+
+impl Sink for Pipeline {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        // Samples held back by a stage still have to go through the ones after it
+        for index in 0..self.stages.len() {
+            let held = self.stages[index].flush();
+            let samples = self.run(held, index + 1);
+
+            if !samples.is_empty() {
+                self.sink
+                    .write(AudioPacket::Samples(samples), &mut self.converter)?;
+            }
+        }
+
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        match packet {
+            AudioPacket::Samples(samples) => {
+                let samples = self.run(samples, 0);
+
+                if samples.is_empty() {
+                    return Ok(());
+                }
+
+                self.sink.write(AudioPacket::Samples(samples), converter)
+            }
+            raw => self.sink.write(raw, converter),
+        }
+    }
+}
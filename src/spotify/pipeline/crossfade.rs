@@ -0,0 +1,448 @@
+use super::Stage;
+use librespot::playback::{NUM_CHANNELS, SAMPLE_RATE};
+use std::{
+    collections::VecDeque,
+    f64::consts::FRAC_PI_2,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+pub const MAX_CROSSFADE: Duration = Duration::from_secs(12);
+
+/// The tail starts being held back this many fades before the end, a little more
+/// of every packet, so the sink is never left waiting while it fills up.
+const HOLD_LEAD: u64 = 4;
+
+/// Handle shared between the service and the crossfade stage running in the player thread.
+#[derive(Debug, Clone, Default)]
+pub struct Crossfade(Arc<Mutex<State>>);
+
+#[derive(Debug, Default)]
+struct State {
+    /// The track the packets are counted against, then the ones announced after it.
+    planned: VecDeque<Planned>,
+    /// Generation of the track the app is playing, the next one is announced after it.
+    current: u64,
+    generations: u64,
+    /// A track was started out of turn, the next packet is its first.
+    cut: bool,
+    /// End of the playing track, held back until the next one starts.
+    holding: VecDeque<f64>,
+    /// End of the previous track, being mixed into the start of the playing one.
+    mixing: VecDeque<f64>,
+    mixing_len: usize,
+}
+
+#[derive(Debug)]
+struct Planned {
+    generation: u64,
+    length: u64,
+    /// Samples of the track not decoded yet.
+    remaining: u64,
+    /// Overlap with the track after it, in samples.
+    fade: u64,
+}
+
+impl State {
+    fn plan(&mut self, duration_ms: u32) -> Planned {
+        self.generations += 1;
+
+        Planned {
+            generation: self.generations,
+            length: samples(duration_ms as u64),
+            remaining: samples(duration_ms as u64),
+            fade: 0,
+        }
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.planned
+            .iter()
+            .position(|planned| planned.generation == self.current)
+    }
+
+    /// The playing track ran out and the next one is known, the next packet is its first.
+    fn ended(&self) -> bool {
+        self.planned.len() > 1 && self.planned[0].remaining == 0
+    }
+}
+
+impl Crossfade {
+    /// Announces the track about to be loaded out of turn, by a skip or a seek.
+    ///
+    /// Must be called before the player is told to load it. Whatever the previous
+    /// track held back is dropped, it does not fade into this one.
+    pub fn begin_track(&self, duration_ms: u32) {
+        if let Ok(mut state) = self.0.lock() {
+            let planned = state.plan(duration_ms);
+
+            state.current = planned.generation;
+            state.planned = VecDeque::from([planned]);
+            state.cut = true;
+        }
+    }
+
+    /// Announces the track following the current one, `None` when nothing does.
+    ///
+    /// Packets keep counting against the current track until it ran out, late ones
+    /// still end up in its tail. A `fade` of zero makes the transition gapless.
+    pub fn set_next(&self, duration_ms: Option<u32>, fade: Duration) {
+        let Ok(mut state) = self.0.lock() else {
+            return;
+        };
+        let Some(index) = state.current() else {
+            return;
+        };
+        let fade = samples(fade.min(MAX_CROSSFADE).as_millis() as u64);
+
+        state.planned.truncate(index + 1);
+        let next = duration_ms.map(|duration_ms| state.plan(duration_ms));
+
+        let current = &mut state.planned[index];
+        current.fade = match next {
+            Some(_) => fade.min(current.length / 2) & !1,
+            None => 0,
+        };
+        state.planned.extend(next);
+    }
+
+    /// Moves on to the announced next track once the current one ended.
+    ///
+    /// Returns false when the track of `duration_ms` is not the one announced,
+    /// it then has to begin on its own.
+    pub fn advance(&self, duration_ms: u32) -> bool {
+        let Ok(mut state) = self.0.lock() else {
+            return false;
+        };
+        let next = state
+            .current()
+            .and_then(|index| state.planned.get(index + 1))
+            .filter(|next| next.length == samples(duration_ms as u64))
+            .map(|next| next.generation);
+
+        match next {
+            Some(generation) => {
+                state.current = generation;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn stage(&self) -> Box<dyn Stage> {
+        Box::new(self.clone())
+    }
+}
+
+impl Stage for Crossfade {
+    fn process(&mut self, samples: &mut Vec<f64>) {
+        let Ok(mut state) = self.0.lock() else {
+            return;
+        };
+        let state = &mut *state;
+
+        if mem::take(&mut state.cut) {
+            state.holding.clear();
+            state.mixing.clear();
+        }
+
+        // First packet of the next track, whatever was held back gets mixed into it
+        if state.ended() {
+            state.planned.pop_front();
+            state.mixing = mem::take(&mut state.holding);
+            state.mixing_len = state.mixing.len();
+        }
+
+        let mixed = state.mixing_len - state.mixing.len();
+
+        for (index, sample) in samples.iter_mut().enumerate() {
+            let Some(tail) = state.mixing.pop_front() else {
+                break;
+            };
+            let (fade_in, fade_out) = equal_power((mixed + index) as f64 / state.mixing_len as f64);
+
+            *sample = *sample * fade_in + tail * fade_out;
+        }
+
+        let Some(playing) = state.planned.front_mut() else {
+            return;
+        };
+        playing.remaining = playing.remaining.saturating_sub(samples.len() as u64);
+
+        // Grows towards the whole fade as the end approaches, the decoder only
+        // runs that much ahead of the sink
+        let held = playing.fade - ((playing.remaining / HOLD_LEAD) & !1).min(playing.fade);
+        if held == 0 && state.holding.is_empty() {
+            return;
+        }
+
+        // The track may decode longer than announced, the held part acts as a
+        // delay line and never grows past the fade length
+        state.holding.extend(samples.drain(..));
+        let excess = state.holding.len().saturating_sub(held as usize);
+        samples.extend(state.holding.drain(..excess));
+    }
+
+    /// Keeps the tail of a track that ended into an announced one: switching between
+    /// a spotify and a local track stops one sink and starts another, the first
+    /// packet written to the new one still mixes the tail in.
+    fn flush(&mut self) -> Vec<f64> {
+        let Ok(mut state) = self.0.lock() else {
+            return Vec::new();
+        };
+
+        if state.ended() {
+            return Vec::new();
+        }
+
+        state.mixing.clear();
+        state.holding.drain(..).collect()
+    }
+}
+
+/// Interleaved samples in `ms`, always a whole number of frames.
+fn samples(ms: u64) -> u64 {
+    ms * SAMPLE_RATE as u64 / 1000 * NUM_CHANNELS as u64
+}
+
+/// Gains for the incoming and outgoing tracks, keeps the perceived loudness constant.
+fn equal_power(progress: f64) -> (f64, f64) {
+    let angle = progress.clamp(0.0, 1.0) * FRAC_PI_2;
+
+    (angle.sin(), angle.cos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spotify::pipeline::Pipeline;
+    use librespot::playback::{
+        audio_backend::{Sink, SinkResult},
+        convert::Converter,
+        decoder::AudioPacket,
+    };
+    use std::f64::consts::TAU;
+
+    const PACKET: usize = 4096;
+    const TRACK_MS: u32 = 1000;
+    const FADE: Duration = Duration::from_millis(200);
+
+    /// Sink keeping every packet written to it.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<Vec<f64>>>>);
+
+    impl Recorder {
+        fn samples(&self) -> Vec<f64> {
+            self.0.lock().unwrap().concat()
+        }
+
+        fn packets(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+    }
+
+    impl Sink for Recorder {
+        fn start(&mut self) -> SinkResult<()> {
+            Ok(())
+        }
+
+        fn stop(&mut self) -> SinkResult<()> {
+            Ok(())
+        }
+
+        fn write(&mut self, packet: AudioPacket, _converter: &mut Converter) -> SinkResult<()> {
+            if let AudioPacket::Samples(samples) = packet {
+                self.0.lock().unwrap().push(samples);
+            }
+
+            Ok(())
+        }
+    }
+
+    fn tone(frequency: f64) -> Vec<f64> {
+        let frames = samples(TRACK_MS as u64) as usize / NUM_CHANNELS as usize;
+
+        (0..frames)
+            .flat_map(|frame| {
+                let sample = (TAU * frequency * frame as f64 / SAMPLE_RATE as f64).sin();
+
+                [sample; NUM_CHANNELS as usize]
+            })
+            .collect()
+    }
+
+    fn play(pipeline: &mut Pipeline, track: &[f64]) {
+        let mut converter = Converter::new(None);
+
+        for packet in track.chunks(PACKET) {
+            pipeline
+                .write(AudioPacket::Samples(packet.to_vec()), &mut converter)
+                .unwrap();
+        }
+    }
+
+    /// A crossfade with the track after the one beginning.
+    fn announced() -> Crossfade {
+        let crossfade = Crossfade::default();
+
+        crossfade.begin_track(TRACK_MS);
+        crossfade.set_next(Some(TRACK_MS), FADE);
+
+        crossfade
+    }
+
+    /// What a crossfade from `first` into `second` should sound like.
+    fn expected(first: &[f64], second: &[f64]) -> Vec<f64> {
+        let fade = samples(FADE.as_millis() as u64) as usize;
+        let (body, tail) = first.split_at(first.len() - fade);
+
+        let overlap = second[..fade]
+            .iter()
+            .zip(tail)
+            .enumerate()
+            .map(|(i, (a, b))| {
+                let (fade_in, fade_out) = equal_power(i as f64 / fade as f64);
+
+                a * fade_in + b * fade_out
+            });
+
+        body.iter()
+            .copied()
+            .chain(overlap)
+            .chain(second[fade..].iter().copied())
+            .collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+
+        for (index, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "sample {index}: {actual} instead of {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn overlap_is_the_weighted_sum_of_both_tracks() {
+        let (first, second) = (tone(440.0), tone(660.0));
+        let crossfade = announced();
+        let recorder = Recorder::default();
+        let mut pipeline = Pipeline::new(Box::new(recorder.clone()), vec![crossfade.stage()]);
+
+        play(&mut pipeline, &first);
+        assert!(crossfade.advance(TRACK_MS));
+        play(&mut pipeline, &second);
+
+        assert_close(&recorder.samples(), &expected(&first, &second));
+    }
+
+    #[test]
+    fn late_packets_of_the_previous_track_fade_out() {
+        let (first, second) = (tone(440.0), tone(660.0));
+        let crossfade = announced();
+        let recorder = Recorder::default();
+        let mut pipeline = Pipeline::new(Box::new(recorder.clone()), vec![crossfade.stage()]);
+
+        // The end of track event can arrive before the player wrote its last packets
+        let (early, late) = first.split_at(first.len() - 3 * PACKET);
+        play(&mut pipeline, early);
+        assert!(crossfade.advance(TRACK_MS));
+        play(&mut pipeline, late);
+        play(&mut pipeline, &second);
+
+        assert_close(&recorder.samples(), &expected(&first, &second));
+    }
+
+    #[test]
+    fn every_packet_reaches_the_sink() {
+        let first = tone(440.0);
+        let crossfade = announced();
+        let recorder = Recorder::default();
+        let mut pipeline = Pipeline::new(Box::new(recorder.clone()), vec![crossfade.stage()]);
+
+        play(&mut pipeline, &first);
+
+        assert_eq!(recorder.packets(), first.chunks(PACKET).count());
+    }
+
+    #[test]
+    fn announcing_after_advancing_fades_into_the_third_track() {
+        let (first, second, third) = (tone(440.0), tone(660.0), tone(880.0));
+        let crossfade = announced();
+        let recorder = Recorder::default();
+        let mut pipeline = Pipeline::new(Box::new(recorder.clone()), vec![crossfade.stage()]);
+
+        play(&mut pipeline, &first);
+        assert!(crossfade.advance(TRACK_MS));
+        crossfade.set_next(Some(TRACK_MS), FADE);
+        play(&mut pipeline, &second);
+        assert!(crossfade.advance(TRACK_MS));
+        play(&mut pipeline, &third);
+
+        let expected = expected(&expected(&first, &second), &third);
+        assert_close(&recorder.samples(), &expected);
+    }
+
+    #[test]
+    fn skipping_drops_the_held_tail() {
+        let (first, second) = (tone(440.0), tone(660.0));
+        let crossfade = announced();
+        let recorder = Recorder::default();
+        let mut pipeline = Pipeline::new(Box::new(recorder.clone()), vec![crossfade.stage()]);
+
+        play(&mut pipeline, &first);
+        crossfade.begin_track(TRACK_MS);
+        play(&mut pipeline, &second);
+
+        let fade = samples(FADE.as_millis() as u64) as usize;
+        let played: Vec<f64> = recorder.samples();
+        let expected: Vec<f64> = first[..first.len() - fade]
+            .iter()
+            .chain(&second)
+            .copied()
+            .collect();
+        assert_close(&played, &expected);
+    }
+
+    #[test]
+    fn stopping_plays_the_held_tail() {
+        let first = tone(440.0);
+        let crossfade = announced();
+        let recorder = Recorder::default();
+        let mut pipeline = Pipeline::new(Box::new(recorder.clone()), vec![crossfade.stage()]);
+
+        // Stopped during the fade, the next track never started
+        let played = &first[..first.len() - 2 * PACKET];
+        play(&mut pipeline, played);
+        pipeline.stop().unwrap();
+
+        assert_close(&recorder.samples(), played);
+    }
+
+    #[test]
+    fn crossfade_survives_switching_sinks() {
+        let (first, second) = (tone(440.0), tone(660.0));
+        let crossfade = announced();
+        let (spotify, local) = (Recorder::default(), Recorder::default());
+        let mut pipeline = Pipeline::new(Box::new(spotify.clone()), vec![crossfade.stage()]);
+
+        play(&mut pipeline, &first);
+
+        // The next track is a local one, the spotify player is stopped before it starts
+        assert!(crossfade.advance(TRACK_MS));
+        pipeline.stop().unwrap();
+
+        let mut pipeline = Pipeline::new(Box::new(local.clone()), vec![crossfade.stage()]);
+        play(&mut pipeline, &second);
+
+        let played: Vec<f64> = spotify
+            .samples()
+            .into_iter()
+            .chain(local.samples())
+            .collect();
+        assert_close(&played, &expected(&first, &second));
+    }
+}
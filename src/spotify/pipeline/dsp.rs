@@ -0,0 +1,317 @@
+use super::Stage;
+use crate::{context::config::Equalizer, data::equalizer::FREQUENCIES};
+use librespot::playback::{NUM_CHANNELS, SAMPLE_RATE};
+use std::{
+    f64::consts::PI,
+    sync::{Arc, Mutex},
+};
+
+const CHANNELS: usize = NUM_CHANNELS as usize;
+/// Octave wide bands.
+const Q: f64 = std::f64::consts::SQRT_2;
+/// -1 dBFS, leaves some headroom for the conversion to the output format.
+const LIMITER_THRESHOLD: f64 = 0.891;
+const LIMITER_RELEASE_MS: f64 = 100.0;
+
+/// Handle to the equalizer, balance, mono downmix and limiter applied to the decoded samples.
+///
+/// Settings can be changed while playing, the stage picks them up on its next packet.
+#[derive(Debug, Clone)]
+pub struct Dsp(Arc<Mutex<Chain>>);
+
+#[derive(Debug)]
+struct Chain {
+    settings: Equalizer,
+    preamp: f64,
+    filters: Vec<Biquad>,
+    /// Filter memory, one set per band and channel.
+    history: Vec<[History; CHANNELS]>,
+    limiter: Limiter,
+}
+
+impl Dsp {
+    pub fn new(settings: &Equalizer) -> Self {
+        let mut chain = Chain {
+            settings: settings.clone(),
+            preamp: 1.0,
+            filters: Vec::new(),
+            history: vec![Default::default(); FREQUENCIES.len()],
+            limiter: Limiter::new(),
+        };
+        chain.configure();
+
+        Self(Arc::new(Mutex::new(chain)))
+    }
+
+    pub fn set(&self, settings: &Equalizer) {
+        if let Ok(mut chain) = self.0.lock() {
+            chain.settings = settings.clone();
+            chain.configure();
+        }
+    }
+
+    pub fn stage(&self) -> Box<dyn Stage> {
+        Box::new(self.clone())
+    }
+}
+
+impl Chain {
+    fn configure(&mut self) {
+        let settings = &self.settings;
+
+        self.preamp = db_to_gain(settings.preamp);
+        // Filter memory is kept so a live change doesn't click
+        self.filters = FREQUENCIES
+            .iter()
+            .zip(settings.bands)
+            .map(|(&frequency, gain)| Biquad::peaking(frequency, gain))
+            .collect();
+    }
+}
+
+impl Stage for Dsp {
+    fn process(&mut self, samples: &mut Vec<f64>) {
+        let Ok(mut chain) = self.0.lock() else {
+            return;
+        };
+        let Chain {
+            settings,
+            preamp,
+            filters,
+            history,
+            limiter,
+        } = &mut *chain;
+
+        // The whole chain is opt in, nothing touches the samples until it is enabled
+        if !settings.enabled {
+            return;
+        }
+
+        let (left, right) = balance(settings.balance);
+
+        for frame in samples.chunks_exact_mut(CHANNELS) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample *= *preamp;
+
+                for (filter, history) in filters.iter().zip(history.iter_mut()) {
+                    if !filter.is_flat() {
+                        *sample = filter.process(*sample, &mut history[channel]);
+                    }
+                }
+            }
+
+            frame[0] *= left;
+            frame[1] *= right;
+
+            if settings.mono {
+                let mid = (frame[0] + frame[1]) / 2.0;
+                frame.fill(mid);
+            }
+
+            if settings.limiter {
+                limiter.process(frame);
+            }
+        }
+    }
+}
+
+/// Peaking filter from the Audio EQ Cookbook, normalized by `a0`.
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct History {
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn peaking(frequency: f64, gain_db: f64) -> Self {
+        let a = 10f64.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * frequency / SAMPLE_RATE as f64;
+        let alpha = w0.sin() / (2.0 * Q);
+        let a0 = 1.0 + alpha / a;
+
+        Self {
+            b0: (1.0 + alpha * a) / a0,
+            b1: -2.0 * w0.cos() / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha / a) / a0,
+        }
+    }
+
+    /// A 0 dB band leaves the signal untouched, skipping it saves CPU.
+    fn is_flat(&self) -> bool {
+        self.b0 == 1.0 && self.b2 == self.a2
+    }
+
+    /// Transposed direct form II.
+    fn process(&self, input: f64, history: &mut History) -> f64 {
+        let output = self.b0 * input + history.z1;
+        history.z1 = self.b1 * input - self.a1 * output + history.z2;
+        history.z2 = self.b2 * input - self.a2 * output;
+
+        output
+    }
+}
+
+/// Peak limiter with instant attack, both channels share the gain to keep the stereo image.
+#[derive(Debug)]
+struct Limiter {
+    gain: f64,
+    release: f64,
+}
+
+impl Limiter {
+    fn new() -> Self {
+        Self {
+            gain: 1.0,
+            release: (-1.0 / (LIMITER_RELEASE_MS / 1000.0 * SAMPLE_RATE as f64)).exp(),
+        }
+    }
+
+    fn process(&mut self, frame: &mut [f64]) {
+        let peak = frame
+            .iter()
+            .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+
+        self.gain = 1.0 - (1.0 - self.gain) * self.release;
+        if peak * self.gain > LIMITER_THRESHOLD {
+            self.gain = LIMITER_THRESHOLD / peak;
+        }
+
+        frame.iter_mut().for_each(|sample| *sample *= self.gain);
+    }
+}
+
+fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+/// Gains for the left and right channels, panning only ever attenuates.
+fn balance(balance: f64) -> (f64, f64) {
+    let balance = balance.clamp(-1.0, 1.0);
+
+    ((1.0 - balance).min(1.0), (1.0 + balance).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    fn settings() -> Equalizer {
+        Equalizer {
+            enabled: true,
+            limiter: false,
+            ..Equalizer::default()
+        }
+    }
+
+    fn run(settings: &Equalizer, mut samples: Vec<f64>) -> Vec<f64> {
+        Dsp::new(settings).process(&mut samples);
+
+        samples
+    }
+
+    /// One second of a stereo sine.
+    fn sine(frequency: f64, amplitude: f64) -> Vec<f64> {
+        (0..SAMPLE_RATE as usize)
+            .flat_map(|frame| {
+                let sample = (TAU * frequency * frame as f64 / SAMPLE_RATE as f64).sin();
+
+                [amplitude * sample; CHANNELS]
+            })
+            .collect()
+    }
+
+    /// Skips the first half, filters need a moment to settle.
+    fn rms(samples: &[f64]) -> f64 {
+        let settled = &samples[samples.len() / 2..];
+
+        (settled.iter().map(|sample| sample * sample).sum::<f64>() / settled.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn boosting_a_band_by_6_db_doubles_its_level() {
+        let mut boosted = settings();
+        boosted.bands[FREQUENCIES.iter().position(|f| *f == 1000.0).unwrap()] = 6.0;
+
+        let input = sine(1000.0, 0.25);
+        let ratio = rms(&run(&boosted, input.clone())) / rms(&input);
+
+        assert!((ratio - 2.0).abs() < 0.02, "the level changed by {ratio}");
+    }
+
+    #[test]
+    fn flat_chain_is_unity() {
+        let input = sine(440.0, 0.5);
+
+        assert_eq!(run(&settings(), input.clone()), input);
+    }
+
+    #[test]
+    fn disabled_chain_leaves_the_samples_alone() {
+        let loud = Equalizer {
+            enabled: false,
+            preamp: 12.0,
+            balance: 1.0,
+            mono: true,
+            limiter: true,
+            ..Equalizer::default()
+        };
+        let input = sine(440.0, 2.0);
+
+        assert_eq!(run(&loud, input.clone()), input);
+    }
+
+    #[test]
+    fn mono_averages_both_channels() {
+        let mono = Equalizer {
+            mono: true,
+            ..settings()
+        };
+
+        assert_eq!(
+            run(&mono, vec![0.2, 0.6, -1.0, 0.0]),
+            [0.4, 0.4, -0.5, -0.5]
+        );
+    }
+
+    #[test]
+    fn balance_only_attenuates_the_other_side() {
+        let right = Equalizer {
+            balance: 0.5,
+            ..settings()
+        };
+        let left = Equalizer {
+            balance: -1.0,
+            ..settings()
+        };
+
+        assert_eq!(run(&right, vec![0.8, 0.8]), [0.4, 0.8]);
+        assert_eq!(run(&left, vec![0.8, 0.8]), [0.8, 0.0]);
+    }
+
+    #[test]
+    fn limiter_never_exceeds_full_scale() {
+        let boosted = Equalizer {
+            bands: [12.0; 10],
+            preamp: 12.0,
+            limiter: true,
+            ..settings()
+        };
+
+        let output = run(&boosted, sine(100.0, 1.0));
+
+        assert!(output.iter().all(|sample| sample.abs() <= 1.0));
+        assert!(output.iter().any(|sample| sample.abs() > 0.5));
+    }
+}
@@ -0,0 +1,299 @@
+use super::Stage;
+use crate::data::visualizer::{Frame, BARS, SCOPE_LENGTH};
+use librespot::playback::{NUM_CHANNELS, SAMPLE_RATE};
+use std::{
+    collections::VecDeque,
+    f32::consts::PI,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+const FFT_SIZE: usize = 2048;
+/// Packets waiting for the analysis, newer ones are dropped rather than blocking playback.
+const BACKLOG: usize = 8;
+const LOWEST_FREQUENCY: f32 = 30.0;
+const HIGHEST_FREQUENCY: f32 = 16000.0;
+/// Bars below this level are drawn empty.
+const FLOOR_DB: f32 = -60.0;
+/// How much of its height a bar keeps on every analysis, so it falls smoothly.
+const DECAY: f32 = 0.85;
+/// Mono samples summarized by each waveform peak, 50 ms.
+const WAVEFORM_CHUNK: usize = SAMPLE_RATE as usize / 20;
+
+/// Taps the samples right before the sink and analyses them on a background thread.
+///
+/// Disabled by default, the tap costs nothing until [`Visualizer::set_enabled`] is called.
+/// Each handle shares the same analysis thread, which ends once all of them are dropped.
+#[derive(Debug, Clone)]
+pub struct Visualizer {
+    enabled: Arc<AtomicBool>,
+    sender: SyncSender<Vec<f64>>,
+    frame: Arc<Mutex<Frame>>,
+}
+
+impl Default for Visualizer {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::sync_channel(BACKLOG);
+        let frame = Arc::new(Mutex::new(Frame::default()));
+
+        let output = frame.clone();
+        thread::spawn(move || analyse(receiver, output));
+
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            sender,
+            frame,
+        }
+    }
+}
+
+impl Visualizer {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+
+        if !enabled {
+            if let Ok(mut frame) = self.frame.lock() {
+                *frame = Frame::default();
+            }
+        }
+    }
+
+    /// Copy of the latest analysis.
+    pub fn frame(&self) -> Frame {
+        self.frame
+            .lock()
+            .map(|frame| frame.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn stage(&self) -> Box<dyn Stage> {
+        Box::new(self.clone())
+    }
+}
+
+impl Stage for Visualizer {
+    fn process(&mut self, samples: &mut Vec<f64>) {
+        if self.enabled.load(Ordering::Relaxed) {
+            let _ = self.sender.try_send(samples.clone());
+        }
+    }
+}
+
+fn analyse(receiver: Receiver<Vec<f64>>, output: Arc<Mutex<Frame>>) {
+    let ranges = bar_ranges();
+    let window = hann();
+
+    let mut history = VecDeque::from(vec![0.0f32; FFT_SIZE]);
+    let mut peak = 0.0f32;
+    let mut chunk = 0;
+
+    while let Ok(samples) = receiver.recv() {
+        let mut peaks = Vec::new();
+
+        for frame in samples.chunks_exact(NUM_CHANNELS as usize) {
+            let mono = (frame.iter().sum::<f64>() / frame.len() as f64) as f32;
+
+            history.pop_front();
+            history.push_back(mono);
+
+            peak = peak.max(mono.abs());
+            chunk += 1;
+            if chunk == WAVEFORM_CHUNK {
+                peaks.push(peak.min(1.0));
+                peak = 0.0;
+                chunk = 0;
+            }
+        }
+
+        let levels = levels(&history, &window, &ranges);
+
+        let Ok(mut frame) = output.lock() else {
+            return;
+        };
+
+        for (bar, level) in frame.bars.iter_mut().zip(levels) {
+            *bar = decay(*bar, level);
+        }
+
+        frame.scope.clear();
+        frame
+            .scope
+            .extend(history.iter().skip(FFT_SIZE - SCOPE_LENGTH));
+
+        for peak in peaks {
+            frame.waveform.pop_front();
+            frame.waveform.push_back(peak);
+        }
+    }
+}
+
+/// Level of every bar for the last [`FFT_SIZE`] mono samples, a full scale sine is 1.0.
+fn levels(history: &VecDeque<f32>, window: &[f32], ranges: &[Range<usize>]) -> [f32; BARS] {
+    let mut real: Vec<f32> = history.iter().zip(window).map(|(s, w)| s * w).collect();
+    let mut imaginary = vec![0.0; FFT_SIZE];
+    fft(&mut real, &mut imaginary);
+
+    let mut levels = [0.0; BARS];
+    for (level, range) in levels.iter_mut().zip(ranges) {
+        let magnitude = range
+            .clone()
+            .map(|bin| real[bin].hypot(imaginary[bin]))
+            .fold(0.0, f32::max);
+        // Amplitude of a full scale sine, the hann window halves it
+        let amplitude = magnitude * 4.0 / FFT_SIZE as f32;
+
+        *level = ((20.0 * amplitude.log10() - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+    }
+
+    levels
+}
+
+/// A bar jumps up to a louder level and falls back smoothly.
+fn decay(bar: f32, level: f32) -> f32 {
+    level.max(bar * DECAY)
+}
+
+fn hann() -> Vec<f32> {
+    (0..FFT_SIZE)
+        .map(|index| 0.5 - 0.5 * (2.0 * PI * index as f32 / FFT_SIZE as f32).cos())
+        .collect()
+}
+
+/// FFT bins covered by each bar, never empty.
+fn bar_ranges() -> Vec<Range<usize>> {
+    let ratio = HIGHEST_FREQUENCY / LOWEST_FREQUENCY;
+    let bin = |frequency: f32| (frequency * FFT_SIZE as f32 / SAMPLE_RATE as f32) as usize;
+
+    (0..BARS)
+        .map(|index| {
+            let low = LOWEST_FREQUENCY * ratio.powf(index as f32 / BARS as f32);
+            let high = LOWEST_FREQUENCY * ratio.powf((index + 1) as f32 / BARS as f32);
+            let start = bin(low).max(1);
+
+            start..bin(high).max(start + 1)
+        })
+        .collect()
+}
+
+/// In place iterative radix-2 FFT, the length must be a power of two.
+fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+    let length = real.len();
+
+    let mut reversed = 0;
+    for index in 1..length {
+        let mut bit = length >> 1;
+        while reversed & bit != 0 {
+            reversed ^= bit;
+            bit >>= 1;
+        }
+        reversed |= bit;
+
+        if index < reversed {
+            real.swap(index, reversed);
+            imaginary.swap(index, reversed);
+        }
+    }
+
+    let mut size = 2;
+    while size <= length {
+        let step = -2.0 * PI / size as f32;
+
+        for start in (0..length).step_by(size) {
+            for offset in 0..size / 2 {
+                let (sin, cos) = (step * offset as f32).sin_cos();
+                let (even, odd) = (start + offset, start + offset + size / 2);
+
+                let odd_real = real[odd] * cos - imaginary[odd] * sin;
+                let odd_imaginary = real[odd] * sin + imaginary[odd] * cos;
+
+                real[odd] = real[even] - odd_real;
+                imaginary[odd] = imaginary[even] - odd_imaginary;
+                real[even] += odd_real;
+                imaginary[even] += odd_imaginary;
+            }
+        }
+
+        size <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32) -> VecDeque<f32> {
+        (0..FFT_SIZE)
+            .map(|index| {
+                amplitude * (2.0 * PI * frequency * index as f32 / SAMPLE_RATE as f32).sin()
+            })
+            .collect()
+    }
+
+    /// Frequency at the center of FFT bin `bin`, it does not leak into the ones next to it.
+    fn frequency(bin: usize) -> f32 {
+        bin as f32 * SAMPLE_RATE as f32 / FFT_SIZE as f32
+    }
+
+    #[test]
+    fn bars_cover_the_spectrum_in_order() {
+        let ranges = bar_ranges();
+        let bin = |frequency: f32| (frequency * FFT_SIZE as f32 / SAMPLE_RATE as f32) as usize;
+
+        assert_eq!(ranges.len(), BARS);
+        assert_eq!(ranges[0].start, bin(LOWEST_FREQUENCY).max(1));
+        assert!(ranges[BARS - 1].end >= bin(HIGHEST_FREQUENCY));
+        for pair in ranges.windows(2) {
+            assert!(!pair[0].is_empty());
+            assert!(pair[0].start <= pair[1].start);
+        }
+    }
+
+    #[test]
+    fn full_scale_sine_fills_its_bar_only() {
+        let ranges = bar_ranges();
+
+        for bin in [5, 47, 371] {
+            let levels = levels(&sine(frequency(bin), 1.0), &hann(), &ranges);
+
+            for (level, range) in levels.iter().zip(&ranges) {
+                if range.contains(&bin) {
+                    assert!(*level > 0.99, "bin {bin}: {levels:?}");
+                } else if range.start > bin + 4 || range.end + 4 <= bin {
+                    assert!(*level < 0.2, "bin {bin}: {levels:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quieter_sine_is_lower_by_its_decibels() {
+        let ranges = bar_ranges();
+        let bar = ranges.iter().position(|range| range.contains(&47)).unwrap();
+
+        // -20 dB is a third of the way down to the floor
+        let level = levels(&sine(frequency(47), 0.1), &hann(), &ranges)[bar];
+        assert!((level - (1.0 - 20.0 / -FLOOR_DB)).abs() < 0.01, "{level}");
+
+        let silence = levels(&sine(frequency(47), 0.0), &hann(), &ranges);
+        assert!(silence.iter().all(|level| *level == 0.0));
+    }
+
+    #[test]
+    fn bars_rise_at_once_and_fall_smoothly() {
+        assert_eq!(decay(0.2, 0.9), 0.9);
+        assert_eq!(decay(1.0, 0.0), DECAY);
+
+        let mut bar = 1.0;
+        for _ in 0..10 {
+            let fallen = decay(bar, 0.0);
+            assert!(fallen < bar && fallen > 0.0);
+            bar = fallen;
+        }
+        assert!((bar - DECAY.powi(10)).abs() < 1e-6);
+    }
+}
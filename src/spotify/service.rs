@@ -1,15 +1,53 @@
-use super::setup::Setup;
+use super::{
+    local::LocalPlayer,
+    pipeline::{Crossfade, Dsp, Pipeline, Stage, Visualizer},
+    setup::Setup,
+};
+use crate::data::{local, storage::Pin};
+use anyhow::{anyhow, Result};
 use librespot::{
-    core::Session,
-    playback::{mixer::Mixer, player::Player},
+    core::{Session, SpotifyId},
+    metadata::{Album, Metadata, Track},
+    playback::{
+        audio_backend::{Sink, SinkBuilder},
+        config::AudioFormat,
+        mixer::Mixer,
+        player::Player,
+    },
 };
-use std::sync::Arc;
+use std::{fmt::Debug, sync::Arc};
+
+const SCOPES: &str = "user-library-read,user-follow-read,user-follow-modify,\
+    playlist-read-private,playlist-read-collaborative";
 
 #[derive(Clone)]
 pub struct Service {
     pub session: Session,
     pub mixer: Arc<dyn Mixer>,
     pub player: Arc<Player>,
+    pub local: LocalPlayer,
+    pub crossfade: Crossfade,
+    pub dsp: Dsp,
+    pub visualizer: Visualizer,
+    output: Output,
+}
+
+/// What is needed to open a sink, the librespot and the local players each open their own.
+#[derive(Clone)]
+struct Output {
+    backend: SinkBuilder,
+    device: Option<String>,
+    format: AudioFormat,
+}
+
+impl Debug for Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Service")
+            .field("session", &self.session.session_id())
+            .field("mixer", &"Debug not implemented")
+            .field("player", &"Debug not implemented")
+            .finish()
+    }
 }
 
 impl Service {
@@ -17,17 +55,16 @@ impl Service {
         let mut session = Session::new(setup.session_config.clone(), setup.cache.clone());
 
         let mixer = (setup.mixer)(setup.mixer_config.clone());
-
-        let soft_volume = mixer.get_soft_volume();
-        let format = setup.format;
-        let backend = setup.backend;
-        let device = setup.device.clone();
-
-        let player = Player::new(
-            setup.player_config.clone(),
-            session.clone(),
-            soft_volume,
-            move || (backend)(device, format),
+        let crossfade = Crossfade::default();
+        let dsp = Dsp::new(&setup.equalizer);
+        let visualizer = Visualizer::default();
+        let output = Output::new(&setup);
+        let player = new_player(
+            &setup,
+            &session,
+            mixer.as_ref(),
+            output.clone(),
+            stages(&crossfade, &dsp, &visualizer),
         );
 
         if session.is_invalid() {
@@ -39,6 +76,192 @@ impl Service {
             session,
             mixer,
             player,
+            local: LocalPlayer::default(),
+            crossfade,
+            dsp,
+            visualizer,
+            output,
         })
     }
+
+    pub async fn connect(setup: Setup) -> Result<Self> {
+        let credentials = setup
+            .credentials
+            .clone()
+            .ok_or(anyhow!("No cached credentials found, login is required"))?;
+
+        let service = Self::load(setup).ok_or(anyhow!("Cannot load the spotify service"))?;
+        service.session.connect(credentials, true).await?;
+
+        log::info!("Connected to Spotify as {}", service.session.username());
+
+        Ok(service)
+    }
+
+    pub async fn token(&self) -> Result<String> {
+        let token = self.session.token_provider().get_token(SCOPES).await?;
+
+        Ok(token.access_token)
+    }
+
+    /// Plays a spotify or a local track, stopping the player of the other kind.
+    pub fn play(&self, uri: &str) -> Result<()> {
+        self.open(uri, 0, true)
+    }
+
+    /// Loads a track without starting it, ready to resume at `position_ms`.
+    pub fn cue(&self, uri: &str, position_ms: u32) -> Result<()> {
+        self.open(uri, position_ms, false)
+    }
+
+    /// Pauses, resumes and seeks go to the local player while it has a file open.
+    pub fn pause(&self) {
+        if self.local.is_loaded() {
+            self.local.pause();
+        } else {
+            self.player.pause();
+        }
+    }
+
+    pub fn resume(&self) {
+        if self.local.is_loaded() {
+            self.local.resume();
+        } else {
+            self.player.play();
+        }
+    }
+
+    pub fn seek(&self, position_ms: u32) {
+        if self.local.is_loaded() {
+            self.local.seek(position_ms);
+        } else {
+            self.player.seek(position_ms);
+        }
+    }
+
+    /// Local tracks open instantly, only spotify ones are preloaded.
+    pub fn preload(&self, uri: &str) -> Result<()> {
+        if local::is_local(uri) {
+            return Ok(());
+        }
+
+        self.player.preload(playable_id(uri)?);
+
+        Ok(())
+    }
+
+    pub async fn pin(&self, uri: &str) -> Result<Pin> {
+        pin(&self.session, uri).await
+    }
+
+    fn open(&self, uri: &str, position_ms: u32, start: bool) -> Result<()> {
+        if let Some(file) = local::file(uri) {
+            self.player.stop();
+            self.local.play(
+                file,
+                position_ms,
+                start,
+                self.mixer.get_soft_volume(),
+                self.output.clone().sink(self.stages()),
+            );
+
+            return Ok(());
+        }
+
+        self.local.stop();
+        self.player.load(playable_id(uri)?, start, position_ms);
+
+        Ok(())
+    }
+
+    fn stages(&self) -> Vec<Box<dyn Stage>> {
+        stages(&self.crossfade, &self.dsp, &self.visualizer)
+    }
+}
+
+impl Output {
+    fn new(setup: &Setup) -> Self {
+        Self {
+            backend: setup.backend,
+            device: setup.device.clone(),
+            format: setup.format,
+        }
+    }
+
+    fn sink(self, stages: Vec<Box<dyn Stage>>) -> Box<dyn Sink> {
+        Box::new(Pipeline::new(
+            (self.backend)(self.device, self.format),
+            stages,
+        ))
+    }
+}
+
+fn new_player(
+    setup: &Setup,
+    session: &Session,
+    mixer: &dyn Mixer,
+    output: Output,
+    stages: Vec<Box<dyn Stage>>,
+) -> Arc<Player> {
+    let soft_volume = mixer.get_soft_volume();
+
+    Player::new(
+        setup.player_config.clone(),
+        session.clone(),
+        soft_volume,
+        move || output.sink(stages),
+    )
+}
+
+/// The limiter has to see the crossfaded signal and the visualizer what is actually heard.
+fn stages(crossfade: &Crossfade, dsp: &Dsp, visualizer: &Visualizer) -> Vec<Box<dyn Stage>> {
+    vec![crossfade.stage(), dsp.stage(), visualizer.stage()]
+}
+
+/// Connects a session without a player, for what only needs metadata.
+pub async fn session(setup: &Setup) -> Result<Session> {
+    let credentials = setup
+        .credentials
+        .clone()
+        .ok_or(anyhow!("No cached credentials found, login is required"))?;
+
+    let session = Session::new(setup.session_config.clone(), setup.cache.clone());
+    session.connect(credentials, true).await?;
+
+    Ok(session)
+}
+
+/// Resolves where the audio files of every track of an album are cached.
+pub async fn pin(session: &Session, uri: &str) -> Result<Pin> {
+    let cache = session
+        .cache()
+        .ok_or(anyhow!("The audio cache is disabled"))?;
+    let album = Album::get(session, &SpotifyId::from_uri(uri)?).await?;
+
+    let mut files = Vec::new();
+    for id in album.tracks() {
+        let track = Track::get(session, id).await?;
+        files.extend(
+            track
+                .files
+                .values()
+                .filter_map(|file| cache.file_path(*file)),
+        );
+    }
+
+    Ok(Pin {
+        uri: uri.to_string(),
+        name: album.name,
+        files,
+    })
+}
+
+fn playable_id(uri: &str) -> Result<SpotifyId> {
+    let id = SpotifyId::from_uri(uri)?;
+
+    if !id.is_playable() {
+        return Err(anyhow!("{uri} is not a playable item"));
+    }
+
+    Ok(id)
 }
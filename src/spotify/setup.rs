@@ -1,4 +1,12 @@
-use crate::{environment, Config};
+use super::pipeline::crossfade::MAX_CROSSFADE;
+use crate::{
+    context::config::Equalizer,
+    data::{
+        equalizer::{Preset, GAIN_RANGE},
+        storage, volume,
+    },
+    environment, Config,
+};
 use anyhow::{anyhow, Result};
 use librespot::{
     connect::ConnectConfig,
@@ -6,11 +14,14 @@ use librespot::{
     discovery::{Credentials, DeviceType},
     playback::{
         audio_backend::{self, SinkBuilder},
-        config::{AudioFormat, Bitrate, PlayerConfig},
+        config::{
+            AudioFormat, Bitrate, NormalisationMethod, NormalisationType, PlayerConfig, VolumeCtrl,
+        },
         mixer::{self, MixerConfig, MixerFn},
+        player::duration_to_coefficient,
     },
 };
-use std::{fmt::Debug, str::FromStr};
+use std::{fmt::Debug, str::FromStr, time::Duration};
 use thiserror::Error;
 
 const DEVICE: &str = "Oxify";
@@ -34,11 +45,15 @@ pub struct Setup {
     pub device: Option<String>,
     pub mixer: MixerFn,
     pub cache: Option<Cache>,
+    /// Bytes the audio cache may take, enforced by [`crate::data::storage::evict`].
+    pub cache_limit: u64,
     pub player_config: PlayerConfig,
     pub session_config: SessionConfig,
     pub connect_config: ConnectConfig,
     pub mixer_config: MixerConfig,
     pub credentials: Option<Credentials>,
+    pub crossfade: Duration,
+    pub equalizer: Equalizer,
 }
 
 impl Debug for Setup {
@@ -52,11 +67,14 @@ impl Debug for Setup {
                 "cache",
                 &self.cache.as_ref().map(|_| "Debug not implemented"),
             )
+            .field("cache_limit", &self.cache_limit)
             .field("player_config", &"Debug not implemented")
             .field("session_config", &self.session_config)
             .field("connect_config", &self.connect_config)
             .field("mixer_config", &self.mixer_config)
             .field("credentials", &self.credentials)
+            .field("crossfade", &self.crossfade)
+            .field("equalizer", &self.equalizer)
             .finish()
     }
 }
@@ -91,29 +109,35 @@ impl Setup {
                 cache_dir
             };
 
-            let data_path = {
-                let data_dir = environment::data_dir();
-                if !data_dir.exists() {
-                    log::info!("Data directory doesn't exist, creating it.");
-                    tokio::fs::create_dir(&data_dir)
+            let audio_path = {
+                let audio_dir = environment::audio_cache_dir();
+                if !audio_dir.exists() {
+                    log::info!("Audio cache directory doesn't exist, creating it.");
+                    tokio::fs::create_dir_all(&audio_dir)
                         .await
                         .ok()
-                        .map(|_| data_dir.clone());
+                        .map(|_| audio_dir.clone());
                 };
 
                 log::info!(
-                    "Using {} as a data path.",
-                    data_dir.to_str().unwrap_or_default()
+                    "Using {} as an audio cache path.",
+                    audio_dir.to_str().unwrap_or_default()
                 );
-                data_dir
+
+                match storage::move_legacy_audio(&environment::data_dir(), &audio_dir) {
+                    Ok(0) => {}
+                    Ok(moved) => log::info!("Moved {moved} folders of cached audio into it."),
+                    Err(err) => log::warn!("Cannot move the previously cached audio: {err}"),
+                }
+                audio_dir
             };
 
-            let limit = parse_file_size(&config.audio.cache_limit_size)?;
+            // The size is enforced by oxify, librespot's limiter knows nothing of pinned albums
             match Cache::new(
                 Some(credentials_path.clone()),
                 Some(credentials_path),
-                Some(data_path),
-                Some(limit),
+                Some(audio_path),
+                None,
             ) {
                 Ok(cache) => Some(cache),
                 Err(e) => {
@@ -123,14 +147,59 @@ impl Setup {
             }
         };
 
+        let cache_limit = parse_file_size(&config.audio.cache_limit_size)?;
+
         let mut player_config = PlayerConfig::default();
         player_config.bitrate = Bitrate::from_str(&config.audio.bitrate.to_string())
             .map_err(|_| anyhow!("Incorrect bitrate, options are: 96, 160, 320"))?;
 
+        let normalisation = &config.audio.normalisation;
+        player_config.normalisation = normalisation.enabled;
+        player_config.normalisation_type = NormalisationType::from_str(&normalisation.kind)
+            .map_err(|_| {
+                anyhow!("Incorrect normalisation type, options are: album, track, auto")
+            })?;
+        player_config.normalisation_method = NormalisationMethod::from_str(&normalisation.method)
+            .map_err(|_| {
+            anyhow!("Incorrect normalisation method, options are: basic, dynamic")
+        })?;
+        player_config.normalisation_pregain_db = normalisation.pregain;
+        player_config.normalisation_threshold_dbfs = normalisation.threshold;
+        player_config.normalisation_attack_cf =
+            duration_to_coefficient(Duration::from_millis(normalisation.attack));
+        player_config.normalisation_release_cf =
+            duration_to_coefficient(Duration::from_millis(normalisation.release));
+        player_config.normalisation_knee_db = normalisation.knee;
+        player_config.gapless = config.audio.gapless;
+
+        let crossfade = Duration::from_secs(config.audio.crossfade).min(MAX_CROSSFADE);
+
+        let mut equalizer = config.audio.equalizer.clone();
+        let preset = Preset::from_str(&equalizer.preset).map_err(|_| {
+            anyhow!("Incorrect equalizer preset, options are: flat, bass, treble, vocal, rock, electronic, custom")
+        })?;
+        if let Some(gains) = preset.gains() {
+            equalizer.bands = gains;
+        }
+        equalizer.bands = equalizer
+            .bands
+            .map(|gain| gain.clamp(*GAIN_RANGE.start(), *GAIN_RANGE.end()));
+        equalizer.preamp = equalizer
+            .preamp
+            .clamp(*GAIN_RANGE.start(), *GAIN_RANGE.end());
+
+        let volume_ctrl =
+            VolumeCtrl::from_str_with_range(&config.audio.volume_ctrl, config.audio.volume_range)
+                .map_err(|_| {
+                anyhow!("Incorrect volume control, options are: cubic, log, linear, fixed")
+            })?;
+
         let connect_config = ConnectConfig {
             name: DEVICE.to_string(),
             device_type: DeviceType::Computer,
-            initial_volume: config.audio.initial_volume,
+            initial_volume: volume::from_percent(config.audio.initial_volume as f32),
+            disable_volume: matches!(volume_ctrl, VolumeCtrl::Fixed),
+            volume_steps: 100 / config.audio.volume_step.clamp(1, 100),
             ..Default::default()
         };
 
@@ -139,7 +208,10 @@ impl Setup {
             ..Default::default()
         };
 
-        let mixer_config = MixerConfig::default();
+        let mixer_config = MixerConfig {
+            volume_ctrl,
+            ..Default::default()
+        };
 
         let credentials = access_token.map_or_else(
             || cache.as_ref().and_then(Cache::credentials),
@@ -152,11 +224,14 @@ impl Setup {
             device,
             mixer,
             cache,
+            cache_limit,
             player_config,
             connect_config,
             session_config,
             mixer_config,
             credentials,
+            crossfade,
+            equalizer,
         })
     }
 
@@ -171,7 +246,11 @@ impl Setup {
     }
 }
 
-fn parse_file_size(input: &str) -> Result<u64> {
+pub fn parse_file_size(input: &str) -> Result<u64> {
+    // Sizes are shown as `500 MiB`, so the byte unit and the space are accepted too
+    let input = input.trim();
+    let input = input.strip_suffix(['B', 'b']).unwrap_or(input).trim_end();
+
     let mut iter = input.chars();
     let mut suffix = iter.next_back().ok_or(ParseFileSizeError::EmptyInput)?;
     let mut suffix_len = 0;
@@ -207,7 +286,7 @@ fn parse_file_size(input: &str) -> Result<u64> {
 
         for _ in (&mut iter).rev().take(suffix_len) {}
 
-        iter.as_str().parse::<f64>()?
+        iter.as_str().trim_end().parse::<f64>()?
     };
 
     if !num.is_finite() {
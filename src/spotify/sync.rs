@@ -0,0 +1,84 @@
+use super::Api;
+use crate::data::{
+    cache::Cache,
+    library::{Playlist, Selection, Track},
+};
+use anyhow::Result;
+
+/// Fetches the playlists, then the tracks of the stored ones whose snapshot changed.
+///
+/// Playlists never opened are left for when they are, as libraries tend to have plenty of them.
+/// One failing playlist is skipped, it stays stale and is tried again on the next sync.
+pub async fn playlists(
+    api: &Api,
+    cache: &Cache,
+) -> Result<(Vec<Playlist>, Vec<(String, Vec<Track>)>)> {
+    let playlists = api.playlists().await?;
+    let saved = playlists.clone();
+    let snapshots = blocking(cache, move |cache| {
+        let snapshots = cache.snapshots()?;
+        cache.save_playlists(&saved)?;
+
+        Ok(snapshots)
+    })
+    .await?;
+
+    let stale = playlists.iter().filter(|playlist| {
+        snapshots
+            .get(&playlist.id)
+            .is_some_and(|snapshot| snapshot.as_deref() != Some(playlist.snapshot_id.as_str()))
+    });
+
+    let mut updated = Vec::new();
+    for playlist in stale {
+        let selection = Selection::Playlist(playlist.id.clone());
+        let synced = match api.playlist_tracks(&playlist.id).await {
+            Ok(tracks) => {
+                blocking(cache, move |cache| {
+                    cache.save_tracks(&selection, &tracks).map(|_| tracks)
+                })
+                .await
+            }
+            Err(err) => Err(err),
+        };
+
+        match synced {
+            Ok(tracks) => updated.push((playlist.id.clone(), tracks)),
+            Err(err) => log::warn!("Cannot sync the playlist {}: {err}", playlist.name),
+        }
+    }
+
+    Ok((playlists, updated))
+}
+
+/// Fetches the tracks of `selection`, `None` when the stored ones are still up to date.
+pub async fn tracks(api: &Api, cache: &Cache, selection: &Selection) -> Result<Option<Vec<Track>>> {
+    let tracks = match selection {
+        Selection::Playlist(id) => {
+            let stored = id.clone();
+            if blocking(cache, move |cache| cache.is_fresh(&stored)).await? {
+                return Ok(None);
+            }
+            api.playlist_tracks(id).await?
+        }
+        Selection::Section(section) => api.section(*section).await?,
+    };
+
+    let selection = selection.clone();
+    blocking(cache, move |cache| {
+        cache.save_tracks(&selection, &tracks).map(|_| tracks)
+    })
+    .await
+    .map(Some)
+}
+
+/// Runs `query` on the blocking pool, sqlite calls would stall the other tasks otherwise.
+async fn blocking<T, F>(cache: &Cache, query: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Cache) -> Result<T> + Send + 'static,
+{
+    let cache = cache.clone();
+
+    tokio::task::spawn_blocking(move || query(&cache)).await?
+}
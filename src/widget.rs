@@ -0,0 +1,11 @@
+pub mod badge;
+pub mod icon_button;
+pub mod link;
+pub mod text;
+pub mod visualizer;
+
+pub use badge::badge;
+pub use icon_button::{icon_button, labeled_button};
+pub use link::link;
+pub use text::text;
+pub use visualizer::visualizer;
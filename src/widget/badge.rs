@@ -0,0 +1,11 @@
+use iced::{widget::container, Element};
+
+use crate::{appaerance as ui, widget::text};
+
+/// Short label shown next to a title, like the `local` one of local tracks.
+pub fn badge<'a, Message: 'a>(label: &'a str) -> Element<'a, Message> {
+    container(text(label).size(11))
+        .padding([0, 4])
+        .style(ui::container::badge)
+        .into()
+}
@@ -0,0 +1,30 @@
+use iced::{
+    alignment,
+    widget::{button, row, Button},
+};
+
+use crate::{
+    appaerance::{
+        self as ui,
+        icon::{icon, Icon},
+    },
+    widget::text,
+};
+
+/// Secondary button showing only `icon`, highlighted while `selected`.
+pub fn icon_button<'a, Message: 'a>(icon: Icon, selected: bool) -> Button<'a, Message> {
+    button(self::icon(icon))
+        .padding([2, 8])
+        .style(move |theme, status| ui::button::secondary(theme, status, selected))
+}
+
+/// Secondary button showing `icon` before `label`.
+pub fn labeled_button<'a, Message: 'a>(icon: Icon, label: &'a str) -> Button<'a, Message> {
+    button(
+        row![self::icon(icon), text(label)]
+            .spacing(8)
+            .align_y(alignment::Vertical::Center),
+    )
+    .padding([4, 10])
+    .style(|theme, status| ui::button::secondary(theme, status, false))
+}
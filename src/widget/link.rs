@@ -0,0 +1,18 @@
+use iced::widget::{
+    button,
+    text::{IntoFragment, Wrapping},
+    Button,
+};
+
+use crate::{appaerance as ui, widget::text};
+
+/// Label opening the page of what it names, plain text without `on_press`.
+pub fn link<'a, Message: 'a>(
+    label: impl IntoFragment<'a>,
+    on_press: Option<Message>,
+) -> Button<'a, Message> {
+    button(text(label).wrapping(Wrapping::None))
+        .padding(0)
+        .style(ui::button::link)
+        .on_press_maybe(on_press)
+}
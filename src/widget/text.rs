@@ -0,0 +1,7 @@
+use crate::data::font;
+use iced::widget::{self, text::IntoFragment, Text};
+
+/// Text in the configured body font, which the default font of iced cannot follow once running.
+pub fn text<'a>(content: impl IntoFragment<'a>) -> Text<'a> {
+    widget::text(content).font(font::BODY.get())
+}
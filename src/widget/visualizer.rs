@@ -0,0 +1,101 @@
+use crate::data::visualizer::{Frame, Style};
+use iced::{
+    mouse,
+    widget::canvas::{self, Path, Stroke},
+    Element, Length, Point, Rectangle, Renderer, Size, Theme,
+};
+
+use crate::appaerance as ui;
+
+const HEIGHT: f32 = 64.0;
+const BAR_GAP: f32 = 2.0;
+
+pub fn visualizer<'a, Message: 'a>(frame: &'a Frame, style: Style) -> Element<'a, Message> {
+    canvas::Canvas::new(Visualizer { frame, style })
+        .width(Length::Fill)
+        .height(HEIGHT)
+        .into()
+}
+
+struct Visualizer<'a> {
+    frame: &'a Frame,
+    style: Style,
+}
+
+impl<Message> canvas::Program<Message> for Visualizer<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let colors = ui::visualizer::primary(theme);
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let Size { width, height } = frame.size();
+
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), colors.background);
+
+        match self.style {
+            Style::Bars => {
+                let bar_width = width / self.frame.bars.len() as f32;
+
+                for (index, level) in self.frame.bars.iter().enumerate() {
+                    let bar_height = level * height;
+
+                    frame.fill_rectangle(
+                        Point::new(
+                            index as f32 * bar_width + BAR_GAP / 2.0,
+                            height - bar_height,
+                        ),
+                        Size::new((bar_width - BAR_GAP).max(1.0), bar_height),
+                        colors.foreground,
+                    );
+                }
+            }
+            Style::Oscilloscope => {
+                let step = width / (self.frame.scope.len().max(2) - 1) as f32;
+                let middle = height / 2.0;
+
+                let path = Path::new(|builder| {
+                    for (index, sample) in self.frame.scope.iter().enumerate() {
+                        let point = Point::new(
+                            index as f32 * step,
+                            middle - sample.clamp(-1.0, 1.0) * middle,
+                        );
+
+                        if index == 0 {
+                            builder.move_to(point);
+                        } else {
+                            builder.line_to(point);
+                        }
+                    }
+                });
+
+                frame.stroke(
+                    &path,
+                    Stroke::default().with_color(colors.accent).with_width(1.5),
+                );
+            }
+            Style::Waveform => {
+                let peak_width = width / self.frame.waveform.len() as f32;
+                let middle = height / 2.0;
+
+                for (index, peak) in self.frame.waveform.iter().enumerate() {
+                    let half = (peak * middle).max(0.5);
+
+                    frame.fill_rectangle(
+                        Point::new(index as f32 * peak_width, middle - half),
+                        Size::new(peak_width.max(1.0), half * 2.0),
+                        colors.foreground,
+                    );
+                }
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}